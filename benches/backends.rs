@@ -0,0 +1,94 @@
+//! Extension lookup backends compared on the same normalized queries.
+//!
+//! Each query goes through the normalization of the built-in lookups
+//! (`minimime::bench_support`) before reaching a table: the sorted static
+//! table, a map with the crate's hasher, a `BTreeMap`, and `Db` itself. A
+//! candidate backend can be added as one more function. Run with
+//! `cargo bench --bench backends --features bench-support`.
+
+use std::{collections::BTreeMap, collections::HashMap, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use minimime::{
+    bench_support::{self, TableHasher, FOLD_BUFFER_LEN},
+    tables::{self, Entry},
+    Db,
+};
+
+const FILENAMES: [&str; 4] = [
+    "index.html",
+    "Reports/Q3.PDF",
+    "backup.tar.gz",
+    "notes.frog",
+];
+
+/// Finds a file name's entry with `find`, normalizing the name as the
+/// built-in lookups do.
+fn lookup<'a>(filename: &str, find: impl Fn(&str) -> Option<&'a Entry>) -> Option<&'a Entry> {
+    let mut buf = [0; FOLD_BUFFER_LEN];
+    let compound = bench_support::compound_extension(filename)
+        .and_then(|extension| find(&bench_support::fold_ascii_lowercase(extension, &mut buf)));
+    compound.or_else(|| {
+        let extension = bench_support::extension(filename)?;
+        find(&bench_support::fold_ascii_lowercase(extension, &mut buf))
+    })
+}
+
+fn normalize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("normalize");
+    for filename in FILENAMES {
+        group.bench_with_input(
+            BenchmarkId::new("extension", filename),
+            filename,
+            |b, name| b.iter(|| bench_support::extension(black_box(name))),
+        );
+        group.bench_with_input(BenchmarkId::new("fold", filename), filename, |b, name| {
+            let mut buf = [0; FOLD_BUFFER_LEN];
+            b.iter(|| bench_support::fold_ascii_lowercase(black_box(name), &mut buf).len())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("to_ascii_lowercase", filename),
+            filename,
+            |b, name| b.iter(|| black_box(name).to_ascii_lowercase()),
+        );
+    }
+    group.finish();
+}
+
+fn backends(c: &mut Criterion) {
+    let hashed: HashMap<&str, &Entry, TableHasher> = tables::BY_EXTENSION
+        .iter()
+        .map(|entry| (entry.extension, entry))
+        .collect();
+    let ordered: BTreeMap<&str, &Entry> = tables::BY_EXTENSION
+        .iter()
+        .map(|entry| (entry.extension, entry))
+        .collect();
+    let db = Db::new().unwrap();
+
+    let mut group = c.benchmark_group("backend");
+    for filename in FILENAMES {
+        group.bench_with_input(
+            BenchmarkId::new("sorted_table", filename),
+            filename,
+            |b, name| b.iter(|| lookup(black_box(name), tables::find_by_extension)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("hash_map", filename),
+            filename,
+            |b, name| b.iter(|| lookup(black_box(name), |key| hashed.get(key).copied())),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("btree_map", filename),
+            filename,
+            |b, name| b.iter(|| lookup(black_box(name), |key| ordered.get(key).copied())),
+        );
+        group.bench_with_input(BenchmarkId::new("db", filename), filename, |b, name| {
+            b.iter(|| db.lookup_by_filename(black_box(name)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, normalize, backends);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+//! Throughput of the global lookups from many threads at once.
+//!
+//! Compares `minimime::lookup_by_filename`, which reads a per-thread
+//! snapshot of the database, with the same lookup through a shared `Mutex`
+//! and `RwLock`. Run with `cargo bench --bench concurrent_lookups`.
+
+use std::{
+    hint::black_box,
+    sync::{Mutex, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use minimime::Db;
+
+const LOOKUPS_PER_THREAD: usize = 200_000;
+const FILENAMES: [&str; 8] = [
+    "index.html",
+    "app.js",
+    "style.css",
+    "logo.png",
+    "report.pdf",
+    "data.json",
+    "archive.tar.gz",
+    "unknown.frog",
+];
+
+/// Runs `lookup` on every thread and returns the wall time for all of them.
+fn run(threads: usize, lookup: impl Fn(&str) -> bool + Sync) -> Duration {
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for i in 0..LOOKUPS_PER_THREAD {
+                    black_box(lookup(black_box(FILENAMES[i % FILENAMES.len()])));
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn main() {
+    minimime::preload();
+    let mutex = Mutex::new(Db::new().unwrap());
+    let rwlock = RwLock::new(Db::new().unwrap());
+
+    let max_threads = thread::available_parallelism().map_or(4, usize::from);
+    println!(
+        "{:>7}  {:>14}  {:>14}  {:>14}",
+        "threads", "global ns/op", "Mutex ns/op", "RwLock ns/op"
+    );
+    let mut threads = 1;
+    while threads <= max_threads {
+        let per_op =
+            |elapsed: Duration| elapsed.as_nanos() as f64 / (threads * LOOKUPS_PER_THREAD) as f64;
+        let global = run(threads, |name| minimime::lookup_by_filename(name).is_some());
+        let mutex = run(threads, |name| {
+            mutex
+                .lock()
+                .unwrap()
+                .lookup_by_filename(name)
+                .cloned()
+                .is_some()
+        });
+        let rwlock = run(threads, |name| {
+            rwlock
+                .read()
+                .unwrap()
+                .lookup_by_filename(name)
+                .cloned()
+                .is_some()
+        });
+        println!(
+            "{threads:>7}  {:>14.1}  {:>14.1}  {:>14.1}",
+            per_op(global),
+            per_op(mutex),
+            per_op(rwlock)
+        );
+        threads *= 2;
+    }
+}
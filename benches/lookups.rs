@@ -0,0 +1,111 @@
+//! Latency of single lookups, of building a database, and throughput of
+//! lookups from many threads.
+//!
+//! Run with `cargo bench --bench lookups`; criterion keeps the results of
+//! the previous run in `target/criterion` and reports changes against them.
+
+use std::{hint::black_box, thread, time::Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use minimime::{mapped::MappedDb, Db};
+
+/// `(case, extension, file name, content type)` queries: a hit, a miss, and
+/// a hit that has to be case-folded.
+const QUERIES: [(&str, &str, &str, &str); 3] = [
+    ("hit", "pdf", "reports/q3.pdf", "application/pdf"),
+    ("miss", "frog", "notes.frog", "application/x-frog"),
+    (
+        "mixed_case",
+        "PdF",
+        "Reports/Q3.PDF",
+        "Application/PDF; charset=binary",
+    ),
+];
+
+fn lookup(c: &mut Criterion) {
+    minimime::preload();
+    let db = Db::new().unwrap();
+    let mut group = c.benchmark_group("lookup");
+    for (case, extension, filename, content_type) in QUERIES {
+        group.bench_with_input(
+            BenchmarkId::new("global_extension", case),
+            extension,
+            |b, query| b.iter(|| minimime::lookup_by_extension(black_box(query))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("global_filename", case),
+            filename,
+            |b, query| b.iter(|| minimime::lookup_by_filename(black_box(query))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("db_extension", case),
+            extension,
+            |b, query| b.iter(|| db.lookup_by_extension(black_box(query))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("db_filename", case),
+            filename,
+            |b, query| b.iter(|| db.lookup_by_filename(black_box(query))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("db_content_type", case),
+            content_type,
+            |b, query| b.iter(|| db.lookup_by_content_type(black_box(query))),
+        );
+    }
+    group.finish();
+}
+
+fn init(c: &mut Criterion) {
+    let ext_db = include_str!("../src/db/ext_mime.db");
+    let mapped = Db::new().unwrap().to_mapped_bytes();
+    let mut group = c.benchmark_group("init");
+    group.bench_function("db_new", |b| b.iter(|| Db::new().unwrap()));
+    group.bench_function("db_parse_ext_mime", |b| {
+        b.iter(|| Db::parse(black_box(ext_db)).unwrap())
+    });
+    group.bench_function("mapped_db_new", |b| {
+        b.iter(|| MappedDb::new(black_box(&mapped[..])).unwrap())
+    });
+    group.finish();
+}
+
+/// Lookups each thread runs per iteration, so spawning doesn't dominate.
+const LOOKUPS_PER_ITER: u64 = 64;
+
+fn concurrent(c: &mut Criterion) {
+    minimime::preload();
+    let db = Db::new().unwrap();
+    let filenames = QUERIES.map(|(_, _, filename, _)| filename);
+    let max_threads = thread::available_parallelism().map_or(4, usize::from);
+    let mut group = c.benchmark_group("concurrent");
+    let mut threads = 1;
+    while threads <= max_threads {
+        group.throughput(Throughput::Elements(threads as u64 * LOOKUPS_PER_ITER));
+        let run = |lookup: &(dyn Fn(&str) -> bool + Sync), iters: u64| {
+            let start = Instant::now();
+            thread::scope(|scope| {
+                for _ in 0..threads {
+                    scope.spawn(|| {
+                        for i in 0..iters * LOOKUPS_PER_ITER {
+                            let name = filenames[i as usize % filenames.len()];
+                            black_box(lookup(black_box(name)));
+                        }
+                    });
+                }
+            });
+            start.elapsed()
+        };
+        group.bench_function(BenchmarkId::new("global", threads), |b| {
+            b.iter_custom(|iters| run(&|name| minimime::lookup_by_filename(name).is_some(), iters))
+        });
+        group.bench_function(BenchmarkId::new("shared_db", threads), |b| {
+            b.iter_custom(|iters| run(&|name| db.lookup_by_filename(name).is_some(), iters))
+        });
+        threads *= 2;
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lookup, init, concurrent);
+criterion_main!(benches);
@@ -0,0 +1,530 @@
+//! Generates the `const` tables of `src/tables.rs` from the embedded
+//! database files: whole-database tables sorted for binary search, and
+//! per-category tables sorted by extension.
+//!
+//! Rows may carry an optional fourth column, an integer weight. When several
+//! rows share a key (an extension, or a content type), the one with the
+//! highest weight is kept, and among equal weights the first one in the file.
+//!
+//! Rows are tagged by the rules in [`TAGS`], plus any comma-separated tags
+//! in an optional fifth column.
+//!
+//! It also generates the borrowed tag slices that let `Entry::to_info` build
+//! an `Info` without allocating.
+//!
+//! Every content type gets the stable numeric ID assigned to it in
+//! `src/db/content_type_ids.db`; the build fails if one is missing.
+//!
+//! It also writes the metadata behind `database_version`: the `updated`
+//! date of `src/db/version.db`, and a hash of the database files.
+//!
+//! The lines it skips as malformed, and those that lose to another row with
+//! the same key, are recorded for `Db::new_with_report`.
+//!
+//! Without the `ext-db` feature the extension tables (`BY_EXTENSION` and
+//! the per-category ones) are generated empty, and without
+//! `content-type-db` the content type tables (`BY_CONTENT_TYPE`, `BY_ID`).
+
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+/// Table name for each top-level media type; anything else goes to `OTHER`.
+const CATEGORIES: &[(&str, &str)] = &[
+    ("application", "APPLICATIONS"),
+    ("audio", "AUDIO"),
+    ("font", "FONTS"),
+    ("image", "IMAGES"),
+    ("message", "MESSAGES"),
+    ("model", "MODELS"),
+    ("text", "TEXT"),
+    ("video", "VIDEOS"),
+];
+
+/// Tag rules: a row gets the tag if its extension is listed, or its content
+/// type matches one of the patterns (a trailing `*` matches any rest).
+const TAGS: &[(&str, &[&str], &[&str])] = &[
+    (
+        "archive",
+        &[
+            "7z", "bz2", "cab", "gz", "iso", "jar", "lz", "lzma", "rar", "tar", "tar.bz2",
+            "tar.gz", "tar.xz", "tar.zst", "tbz", "tgz", "xz", "zip", "zst",
+        ],
+        &[
+            "application/gzip",
+            "application/vnd.docker.image.rootfs.*",
+            "application/vnd.oci.image.layer.*",
+            "application/x-7z-compressed",
+            "application/x-bzip2",
+            "application/x-rar-compressed",
+            "application/x-tar",
+            "application/x-xz",
+            "application/zip",
+            "application/zstd",
+        ],
+    ),
+    (
+        "document",
+        &["djvu", "epub", "mobi", "oxps", "pdf", "ps", "xps"],
+        &[
+            "application/epub+zip",
+            "application/oxps",
+            "application/pdf",
+            "application/postscript",
+            "application/vnd.ms-xpsdocument",
+            "application/x-mobipocket-ebook",
+            "image/vnd.djvu",
+        ],
+    ),
+    (
+        "executable",
+        &[
+            "apk", "bat", "cmd", "com", "deb", "dll", "dylib", "exe", "jar", "msi", "ps1", "sh",
+            "so", "vbs",
+        ],
+        &[
+            "application/java-archive",
+            "application/vnd.android.package-archive",
+            "application/vnd.microsoft.portable-executable",
+            "application/x-debian-package",
+            "application/x-executable",
+            "application/x-ms-dos-executable",
+            "application/x-msdos-program",
+            "application/x-msdownload",
+            "application/x-sh",
+        ],
+    ),
+    (
+        "font",
+        &["otf", "ttf", "woff", "woff2"],
+        &[
+            "application/font-*",
+            "application/vnd.ms-fontobject",
+            "application/x-font-*",
+            "font/*",
+        ],
+    ),
+    ("media", &[], &["audio/*", "image/*", "video/*"]),
+    (
+        "office",
+        &[
+            "doc", "docm", "docx", "dot", "dotx", "key", "numbers", "odg", "odp", "ods", "odt",
+            "pages", "pps", "ppsx", "ppt", "pptm", "pptx", "rtf", "xls", "xlsm", "xlsx", "xlt",
+            "xltx",
+        ],
+        &[
+            "application/msword",
+            "application/rtf",
+            "application/vnd.ms-excel*",
+            "application/vnd.ms-powerpoint*",
+            "application/vnd.ms-word*",
+            "application/vnd.oasis.opendocument.*",
+            "application/vnd.openxmlformats-officedocument.*",
+            "application/x-iwork-*",
+        ],
+    ),
+    (
+        "web",
+        &[
+            "cjs",
+            "css",
+            "htm",
+            "html",
+            "js",
+            "json",
+            "jsx",
+            "map",
+            "mjs",
+            "svelte",
+            "svg",
+            "tsx",
+            "vue",
+            "wasm",
+            "webmanifest",
+            "woff",
+            "woff2",
+            "xhtml",
+        ],
+        &[
+            "application/javascript",
+            "application/json",
+            "application/manifest+json",
+            "application/wasm",
+            "application/xhtml+xml",
+            "image/svg+xml",
+            "text/css",
+            "text/html",
+            "text/javascript",
+            "text/typescript",
+        ],
+    ),
+];
+
+/// A parsed database row.
+#[derive(Clone)]
+struct Row<'a> {
+    /// 1-based line number in the database file
+    line: usize,
+    /// The line as written
+    raw: &'a str,
+    /// `[extension, content_type, encoding]`
+    fields: [&'a str; 3],
+    weight: u16,
+    /// Stable ID of the content type, filled in from the ID file
+    id: u16,
+    /// Sorted, deduplicated tags
+    tags: Vec<&'a str>,
+    /// Every extension of the content type, the preferred one first, filled
+    /// in by [`group_extensions`]
+    extensions: Vec<&'a str>,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let ext_db = read_db("src/db/ext_mime.db");
+    let content_type_db = read_db("src/db/content_type_mime.db");
+    let id_db = read_db("src/db/content_type_ids.db");
+    let version_db = read_db("src/db/version.db");
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    let updated = version_db
+        .lines()
+        .find_map(|line| line.strip_prefix("updated "))
+        .map(str::trim)
+        .expect("src/db/version.db has no `updated` line");
+    let hash = hash_files(&[&ext_db, &content_type_db, &id_db]);
+    fs::write(
+        Path::new(&out_dir).join("version.rs"),
+        format!("const UPDATED: &str = {updated:?};\nconst HASH: u64 = {hash:#018x};\n"),
+    )
+    .expect("failed to write version.rs");
+
+    let ids = read_ids(&id_db);
+    let mut skipped = Vec::new();
+    let mut dropped = Vec::new();
+    let mut by_extension = Vec::new();
+    let mut by_content_type = Vec::new();
+    for (file, db, key, feature, rows) in [
+        (
+            "src/db/ext_mime.db",
+            &ext_db,
+            0,
+            "CARGO_FEATURE_EXT_DB",
+            &mut by_extension,
+        ),
+        (
+            "src/db/content_type_mime.db",
+            &content_type_db,
+            1,
+            "CARGO_FEATURE_CONTENT_TYPE_DB",
+            &mut by_content_type,
+        ),
+    ] {
+        let (parsed, malformed) = parse(db);
+        let (kept, lost) = dedupe(parsed, key);
+        // Rows of compiled-out tables still need IDs, so a missing one
+        // fails every build.
+        let kept = assign_ids(kept, &ids);
+        if env::var_os(feature).is_some() {
+            *rows = kept;
+            skipped.extend(malformed.into_iter().map(|line| (file, line)));
+            dropped.extend(lost.into_iter().map(|pair| (file, pair)));
+        }
+    }
+    write_load_report(&out_dir, &skipped, &dropped);
+    group_extensions(&mut by_extension, &mut by_content_type);
+
+    let mut tables: BTreeMap<&str, Vec<Row>> = BTreeMap::new();
+    for row in &by_extension {
+        let top_level = row.fields[1].split('/').next().unwrap_or_default();
+        let table = CATEGORIES
+            .iter()
+            .find(|(media_type, _)| *media_type == top_level)
+            .map_or("OTHER", |(_, name)| name);
+        tables.entry(table).or_default().push(row.clone());
+    }
+    let docs = CATEGORIES
+        .iter()
+        .map(|(media_type, name)| (*name, format!("whose content type is `{media_type}/*`")))
+        .chain([(
+            "OTHER",
+            "whose top-level type has no dedicated table".to_string(),
+        )]);
+    let mut out = String::new();
+    for (name, doc) in docs {
+        let rows = tables.remove(name).unwrap_or_default();
+        push_table(
+            &mut out,
+            &format!("Entries {doc}, sorted by extension."),
+            name,
+            &rows,
+        );
+    }
+
+    push_table(
+        &mut out,
+        "Every extension entry, sorted by extension.",
+        "BY_EXTENSION",
+        &by_extension,
+    );
+    push_table(
+        &mut out,
+        "The preferred entry for each content type, sorted by content type.",
+        "BY_CONTENT_TYPE",
+        &by_content_type,
+    );
+    let mut by_id = by_content_type.clone();
+    by_id.sort_by_key(|row| row.id);
+    push_table(
+        &mut out,
+        "The preferred entry for each content type, sorted by ID.",
+        "BY_ID",
+        &by_id,
+    );
+    let mut all_tags: Vec<&str> = TAGS.iter().map(|(tag, _, _)| *tag).collect();
+    all_tags.extend(by_extension.iter().flat_map(|row| row.tags.iter().copied()));
+    all_tags.sort_unstable();
+    all_tags.dedup();
+    out.push_str(&format!(
+        "/// Every tag used in the database, sorted.\npub const TAGS: &[&str] = &{all_tags:?};\n"
+    ));
+    fs::write(Path::new(&out_dir).join("tables.rs"), out).expect("failed to write tables.rs");
+
+    let mut tag_sets: Vec<&[&str]> = by_extension
+        .iter()
+        .chain(&by_content_type)
+        .map(|row| &row.tags[..])
+        .collect();
+    tag_sets.sort_unstable();
+    tag_sets.dedup();
+    let mut out = String::from(
+        "/// Borrowed [`Info::tags`] for each set of tags in the embedded database,\n\
+         /// so converting an [`Entry`] allocates nothing.\n\
+         fn static_tags(tags: &[&str]) -> Option<&'static [Cow<'static, str>]> {\n",
+    );
+    if tag_sets.is_empty() {
+        // Both tables are compiled out; a match with only the fallback arm
+        // would be flagged as unreachable code.
+        out.push_str("    let _ = tags;\n    None\n}\n");
+        fs::write(Path::new(&out_dir).join("static_tags.rs"), out)
+            .expect("failed to write static_tags.rs");
+        return;
+    }
+    out.push_str("    Some(match tags {\n");
+    for tags in tag_sets {
+        let borrowed: Vec<String> = tags
+            .iter()
+            .map(|tag| format!("Cow::Borrowed({tag:?})"))
+            .collect();
+        out.push_str(&format!(
+            "        {tags:?} => &[{}],\n",
+            borrowed.join(", ")
+        ));
+    }
+    out.push_str("        _ => return None,\n    })\n}\n");
+    fs::write(Path::new(&out_dir).join("static_tags.rs"), out)
+        .expect("failed to write static_tags.rs");
+}
+
+fn read_db(path: &str) -> String {
+    println!("cargo:rerun-if-changed={path}");
+    fs::read_to_string(path).unwrap_or_else(|err| panic!("failed to read {path}: {err}"))
+}
+
+/// Hashes the database files with 64-bit FNV-1a, ending each file with a
+/// `0xff` byte (which UTF-8 text never contains).
+fn hash_files(files: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for file in files {
+        for &byte in file.as_bytes().iter().chain(&[0xff]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Parses `extension content_type encoding [weight [tags]]` lines, skipping
+/// blank lines and `#` comments. Malformed lines are skipped too, and
+/// returned with their line numbers.
+fn parse(db: &str) -> (Vec<Row<'_>>, Vec<(usize, &str)>) {
+    let mut malformed = Vec::new();
+    let rows = db
+        .lines()
+        .enumerate()
+        .filter_map(|(index, raw)| {
+            let line = raw.split('#').next().unwrap_or_default();
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() {
+                return None;
+            }
+            let weight = match parts.get(3) {
+                _ if parts.len() < 3 => None,
+                Some(weight) => weight.parse().ok(),
+                None => Some(0),
+            };
+            let Some(weight) = weight else {
+                malformed.push((index + 1, raw));
+                return None;
+            };
+            let fields = [parts[0], parts[1], parts[2]];
+            let mut tags: Vec<&str> = parts
+                .get(4)
+                .map(|tags| tags.split(',').filter(|tag| !tag.is_empty()).collect())
+                .unwrap_or_default();
+            tags.extend(
+                TAGS.iter()
+                    .filter(|(_, extensions, content_types)| {
+                        extensions.contains(&fields[0])
+                            || content_types
+                                .iter()
+                                .any(|pattern| matches(pattern, fields[1]))
+                    })
+                    .map(|(tag, _, _)| *tag),
+            );
+            tags.sort_unstable();
+            tags.dedup();
+            Some(Row {
+                line: index + 1,
+                raw,
+                fields,
+                weight,
+                id: 0,
+                tags,
+                extensions: Vec::new(),
+            })
+        })
+        .collect();
+    (rows, malformed)
+}
+
+/// Parses `id content_type` lines, checking that IDs and content types are
+/// unique.
+fn read_ids(db: &str) -> BTreeMap<&str, u16> {
+    let mut ids = BTreeMap::new();
+    let mut seen = std::collections::BTreeSet::new();
+    for line in db.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut parts = line.split_whitespace();
+        let (Some(id), Some(content_type)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let id: u16 = id
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid ID {id:?} for {content_type}"));
+        assert!(id != 0, "ID 0 is reserved ({content_type})");
+        assert!(seen.insert(id), "duplicate ID {id}");
+        assert!(
+            ids.insert(content_type, id).is_none(),
+            "duplicate ID entry for {content_type}"
+        );
+    }
+    ids
+}
+
+/// Fills in the ID of each row's content type.
+fn assign_ids<'a>(mut rows: Vec<Row<'a>>, ids: &BTreeMap<&str, u16>) -> Vec<Row<'a>> {
+    for row in &mut rows {
+        row.id = *ids.get(row.fields[1]).unwrap_or_else(|| {
+            panic!(
+                "{} has no ID: append it to src/db/content_type_ids.db",
+                row.fields[1]
+            )
+        });
+    }
+    rows
+}
+
+/// Fills in the extensions of every row's content type: the extension of
+/// its row in the content type table first, then the others of the
+/// extension table in order.
+fn group_extensions<'a>(by_extension: &mut [Row<'a>], by_content_type: &mut [Row<'a>]) {
+    let mut groups: BTreeMap<String, Vec<&'a str>> = BTreeMap::new();
+    for row in by_content_type.iter().chain(by_extension.iter()) {
+        let extensions = groups
+            .entry(row.fields[1].to_ascii_lowercase())
+            .or_default();
+        if !extensions
+            .iter()
+            .any(|extension| extension.eq_ignore_ascii_case(row.fields[0]))
+        {
+            extensions.push(row.fields[0]);
+        }
+    }
+    for row in by_extension.iter_mut().chain(by_content_type.iter_mut()) {
+        row.extensions = groups[&row.fields[1].to_ascii_lowercase()].clone();
+    }
+}
+
+/// Matches a content type against a tag rule pattern.
+fn matches(pattern: &str, content_type: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => content_type.starts_with(prefix),
+        None => content_type == pattern,
+    }
+}
+
+/// Sorts rows by the given column, ASCII case-insensitively, keeping one row
+/// per key: the highest weight, then the earliest in the file. Also returns
+/// the dropped rows, each with the row kept in its place.
+fn dedupe<'a>(mut rows: Vec<Row<'a>>, key: usize) -> (Vec<Row<'a>>, Vec<(Row<'a>, Row<'a>)>) {
+    rows.sort_by_cached_key(|row| {
+        (
+            row.fields[key].to_ascii_lowercase(),
+            std::cmp::Reverse(row.weight),
+        )
+    });
+    let mut kept: Vec<Row> = Vec::with_capacity(rows.len());
+    let mut dropped = Vec::new();
+    for row in rows {
+        match kept.last() {
+            Some(last) if last.fields[key].eq_ignore_ascii_case(row.fields[key]) => {
+                dropped.push((last.clone(), row));
+            }
+            _ => kept.push(row),
+        }
+    }
+    (kept, dropped)
+}
+
+/// Writes the lines the build skipped or dropped, for `Db::new_with_report`.
+fn write_load_report(
+    out_dir: &str,
+    skipped: &[(&str, (usize, &str))],
+    dropped: &[(&str, (Row, Row))],
+) {
+    let mut out = String::from(
+        "/// Malformed lines of the embedded database files, skipped by the build:\n\
+         /// `(file, line number, line)`.\n\
+         const SKIPPED_LINES: &[(&str, usize, &str)] = &[\n",
+    );
+    for (file, (line, raw)) in skipped {
+        out.push_str(&format!("    ({file:?}, {line}, {raw:?}),\n"));
+    }
+    out.push_str(
+        "];\n\n\
+         /// Lines of the embedded database files dropped for sharing a key with a\n\
+         /// kept line: `(file, (line number, line) kept, (line number, line) dropped)`.\n\
+         #[allow(clippy::type_complexity)]\n\
+         const DROPPED_LINES: &[(&str, (usize, &str), (usize, &str))] = &[\n",
+    );
+    for (file, (kept, lost)) in dropped {
+        out.push_str(&format!(
+            "    ({file:?}, ({}, {:?}), ({}, {:?})),\n",
+            kept.line, kept.raw, lost.line, lost.raw
+        ));
+    }
+    out.push_str("];\n");
+    fs::write(Path::new(out_dir).join("load_report.rs"), out)
+        .expect("failed to write load_report.rs");
+}
+
+fn push_table(out: &mut String, doc: &str, name: &str, rows: &[Row]) {
+    out.push_str(&format!("/// {doc}\npub const {name}: &[Entry] = &[\n"));
+    for row in rows {
+        let [extension, content_type, encoding] = row.fields;
+        out.push_str(&format!(
+            "    Entry {{ extension: {extension:?}, content_type: {content_type:?}, encoding: {encoding:?}, weight: {}, id: {}, tags: &{:?}, extensions: &{:?} }},\n",
+            row.weight, row.id, row.tags, row.extensions
+        ));
+    }
+    out.push_str("];\n\n");
+}
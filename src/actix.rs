@@ -0,0 +1,127 @@
+//! Integration with the actix-web framework.
+//!
+//! Enabled by the `actix` feature. [`mime_guard`] routes requests by their
+//! `Content-Type` against a pattern such as `image/*`, and
+//! [`ResponderExt::serve_with_mime`] sets a response's `Content-Type` from
+//! the name of the file it serves, looked up in the global database:
+//!
+//! ```
+//! use actix_web::{web, App, Responder};
+//! use minimime::actix::{mime_guard, ResponderExt};
+//!
+//! async fn upload_image(body: web::Bytes) -> impl Responder {
+//!     format!("{} bytes", body.len())
+//! }
+//!
+//! async fn report() -> impl Responder {
+//!     "month,total\n".serve_with_mime("report.csv")
+//! }
+//!
+//! let app = App::new()
+//!     .route("/upload", web::post().guard(mime_guard("image/*")).to(upload_image))
+//!     .route("/report", web::get().to(report));
+//! ```
+
+use std::path::Path;
+
+use actix_web::{
+    guard::{Guard, GuardContext},
+    http::header::{HeaderValue, CONTENT_TYPE},
+    CustomizeResponder, Responder,
+};
+
+use crate::content_type_matches;
+
+/// Creates a guard that admits requests whose `Content-Type` matches
+/// `pattern`.
+///
+/// The pattern takes the forms [`content_type_matches`] accepts: an exact
+/// `type/subtype`, `type/*`, `*/*` or a suffix pattern such as
+/// `application/*+json`. Requests without a `Content-Type` header are not
+/// admitted.
+pub fn mime_guard(pattern: impl Into<String>) -> MimeGuard {
+    MimeGuard {
+        pattern: pattern.into(),
+    }
+}
+
+/// Guard returned by [`mime_guard`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimeGuard {
+    pattern: String,
+}
+
+impl Guard for MimeGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        ctx.head()
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| content_type_matches(&self.pattern, value))
+    }
+}
+
+/// Extension methods for actix-web responders.
+pub trait ResponderExt: Responder + Sized {
+    /// Serves the response with the `Content-Type` of a file name.
+    ///
+    /// The type is looked up from the path's file name in the global
+    /// database; unknown names are served as `application/octet-stream`. The
+    /// path is not opened, so the body can come from anywhere.
+    fn serve_with_mime(self, path: impl AsRef<Path>) -> CustomizeResponder<Self> {
+        let content_type = crate::lookup_by_path(path)
+            .and_then(|info| HeaderValue::from_str(&info.content_type).ok())
+            .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+        self.customize().insert_header((CONTENT_TYPE, content_type))
+    }
+}
+
+impl<R: Responder> ResponderExt for R {}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{guard::Guard, test::TestRequest};
+
+    use super::*;
+
+    #[test]
+    fn test_mime_guard() {
+        let check = |pattern: &str, content_type: Option<&str>| {
+            let mut request = TestRequest::default();
+            if let Some(content_type) = content_type {
+                request = request.insert_header((CONTENT_TYPE, content_type));
+            }
+            mime_guard(pattern).check(&request.to_srv_request().guard_ctx())
+        };
+        assert!(check("image/*", Some("image/png")));
+        assert!(check(
+            "application/*+json",
+            Some("application/ld+json; charset=utf-8")
+        ));
+        assert!(!check("image/*", Some("text/plain")));
+        assert!(!check("image/*", None));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_serve_with_mime() {
+        use actix_web::Responder;
+
+        let request = TestRequest::default().to_http_request();
+        let content_type = |path: &str| {
+            "body"
+                .serve_with_mime(path)
+                .respond_to(&request)
+                .headers()
+                .get(CONTENT_TYPE)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(content_type("static/app.CSS"), "text/css");
+        assert_eq!(
+            content_type("downloads/archive.tar.gz"),
+            "application/x-gtar"
+        );
+        assert_eq!(content_type("no-extension"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,301 @@
+//! Classification of archive members.
+//!
+//! Quarantine, antivirus and ingestion tools need to know what an uploaded
+//! archive contains before unpacking it. The functions here walk a ZIP or
+//! tar archive (optionally gzip-compressed) and classify every regular file
+//! in it by name and, optionally, by the magic bytes at the start of its
+//! data. Nested archives are reported as members but not descended into.
+//!
+//! Requires the `archive` feature.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::{sniff::PREFIX_LEN, Db, Info};
+
+/// One regular file inside an archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member<'a> {
+    /// Path of the member inside the archive, as stored
+    pub name: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Entry for the member's file name
+    pub by_name: Option<&'a Info>,
+    /// Entry for the content type sniffed from the member's leading bytes;
+    /// always `None` unless sniffing was requested
+    pub sniffed: Option<&'a Info>,
+    /// Whether the name contradicts the sniffed entry
+    mismatch: bool,
+}
+
+impl<'a> Member<'a> {
+    /// Returns the best available entry for the member: the sniffed one, or
+    /// else the one for its name.
+    pub fn detected(&self) -> Option<&'a Info> {
+        self.sniffed.or(self.by_name)
+    }
+
+    /// Checks whether the member's content was sniffed as a different type
+    /// than its name suggests, such as an executable named `invoice.pdf`.
+    ///
+    /// Names are judged as by [`Db::detect`]: `clip.webm` agrees with WebM
+    /// video, and `report.docx` with the ZIP container it is built on.
+    pub fn mismatches(&self) -> bool {
+        self.mismatch
+    }
+}
+
+/// Classifies the members of a ZIP archive.
+///
+/// # Arguments
+///
+/// * `db` - The database to classify members with
+/// * `reader` - The archive
+/// * `sniff` - Whether to also decompress the first bytes of every member
+///   and sniff them
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read or is not a valid ZIP
+/// archive.
+pub fn classify_zip<'a, R: Read + Seek>(
+    db: &'a Db,
+    reader: R,
+    sniff: bool,
+) -> io::Result<Vec<Member<'a>>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut members = Vec::with_capacity(archive.len());
+    let mut prefix = Vec::with_capacity(PREFIX_LEN);
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        if !file.is_file() {
+            continue;
+        }
+        let name = file.name().to_string();
+        let size = file.size();
+        members.push(classify(db, name, size, file, sniff, &mut prefix)?);
+    }
+    Ok(members)
+}
+
+/// Classifies the members of a tar archive.
+///
+/// The archive is read sequentially, so it can come straight from a network
+/// stream; wrap a gzip-compressed one in a decoder first, or use
+/// [`classify_path`].
+///
+/// # Arguments
+///
+/// * `db` - The database to classify members with
+/// * `reader` - The archive
+/// * `sniff` - Whether to also read the first bytes of every member and
+///   sniff them
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read or is malformed.
+pub fn classify_tar<R: Read>(db: &Db, reader: R, sniff: bool) -> io::Result<Vec<Member<'_>>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    let mut prefix = Vec::with_capacity(PREFIX_LEN);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let size = entry.header().size()?;
+        members.push(classify(db, name, size, entry, sniff, &mut prefix)?);
+    }
+    Ok(members)
+}
+
+/// Classifies the members of an archive file.
+///
+/// The format is recognized from the file's content: ZIP, tar, or
+/// gzip-compressed tar.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, is none of the supported
+/// formats (with [`io::ErrorKind::InvalidData`]), or is malformed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use minimime::{archive, Db};
+///
+/// let db = Db::new().unwrap();
+/// for member in archive::classify_path(&db, "upload.zip", true)? {
+///     if member.detected().is_some_and(|info| info.has_tag("executable")) {
+///         println!("quarantine: upload.zip contains {}", member.name);
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn classify_path(db: &Db, path: impl AsRef<Path>, sniff: bool) -> io::Result<Vec<Member<'_>>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut header = [0; 512];
+    let len = read_up_to(&mut file, &mut header)?;
+    file.seek(SeekFrom::Start(0))?;
+    let header = &header[..len];
+
+    if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+        classify_zip(db, file, sniff)
+    } else if header.starts_with(b"\x1F\x8B") {
+        classify_tar(db, flate2::read::GzDecoder::new(file), sniff)
+    } else if header.get(257..262) == Some(b"ustar") {
+        classify_tar(db, file, sniff)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a ZIP or tar archive",
+        ))
+    }
+}
+
+/// Builds the [`Member`] for one archive entry, reading its first bytes into
+/// `prefix` if sniffing.
+fn classify<'a>(
+    db: &'a Db,
+    name: String,
+    size: u64,
+    data: impl Read,
+    sniff: bool,
+    prefix: &mut Vec<u8>,
+) -> io::Result<Member<'a>> {
+    let sniffed = if sniff {
+        prefix.clear();
+        data.take(PREFIX_LEN as u64).read_to_end(prefix)?;
+        db.lookup_by_magic(prefix)
+    } else {
+        None
+    };
+    let by_name = db.lookup_by_filename(&name);
+    Ok(Member {
+        mismatch: db.detect_entries(by_name, sniffed, None).mismatch,
+        by_name,
+        name,
+        size,
+        sniffed,
+    })
+}
+
+/// Fills as much of `buf` as the reader allows, returning the length read.
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(all(test, feature = "ext-db", feature = "content-type-db"))]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+
+    const WEBP: &[u8] = b"RIFF\x24\0\0\0WEBPVP8 ";
+    const PEM: &[u8] = b"-----BEGIN CERTIFICATE-----\nMIIB\n";
+
+    fn zip_bytes() -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("docs/", options).unwrap();
+        zip.start_file("docs/photo.webp", options).unwrap();
+        zip.write_all(WEBP).unwrap();
+        zip.start_file("invoice.pdf", options).unwrap();
+        zip.write_all(PEM).unwrap();
+        zip.start_file("report.docx", options).unwrap();
+        zip.write_all(b"PK\x03\x04\x14\0\0\0").unwrap();
+        zip.start_file("README", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar_bytes() -> Vec<u8> {
+        let mut tar = tar::Builder::new(Vec::new());
+        for (name, data) in [("photo.webp", WEBP), ("key.pdf", PEM)] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            tar.append_data(&mut header, name, data).unwrap();
+        }
+        tar.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_classify_zip() {
+        let db = Db::new().unwrap();
+        let members = classify_zip(&db, Cursor::new(zip_bytes()), true).unwrap();
+        let names: Vec<&str> = members.iter().map(|m| &*m.name).collect();
+        assert_eq!(
+            names,
+            ["docs/photo.webp", "invoice.pdf", "report.docx", "README"]
+        );
+
+        assert_eq!(members[0].size, WEBP.len() as u64);
+        assert_eq!(members[0].sniffed, db.lookup_by_extension("webp"));
+        assert!(!members[0].mismatches());
+
+        assert_eq!(members[1].by_name, db.lookup_by_extension("pdf"));
+        assert_eq!(members[1].sniffed, db.lookup_by_extension("pem"));
+        assert!(members[1].mismatches());
+
+        assert_eq!(members[2].by_name, db.lookup_by_extension("docx"));
+        assert_eq!(members[2].sniffed, db.lookup_by_extension("zip"));
+        assert!(!members[2].mismatches(), "a docx is a ZIP container");
+
+        assert_eq!(members[3].detected(), None);
+
+        let unsniffed = classify_zip(&db, Cursor::new(zip_bytes()), false).unwrap();
+        assert!(unsniffed.iter().all(|m| m.sniffed.is_none()));
+        assert_eq!(unsniffed[1].detected(), db.lookup_by_extension("pdf"));
+    }
+
+    #[test]
+    fn test_classify_tar() {
+        let db = Db::new().unwrap();
+        let members = classify_tar(&db, &tar_bytes()[..], true).unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].name, "photo.webp");
+        assert_eq!(members[0].sniffed, db.lookup_by_extension("webp"));
+        assert!(members[1].mismatches());
+    }
+
+    #[test]
+    fn test_classify_path() {
+        let db = Db::new().unwrap();
+        let dir = std::env::temp_dir().join(format!("minimime-archive-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(&tar_bytes()).unwrap();
+        for (name, bytes) in [
+            ("a.zip", zip_bytes()),
+            ("a.tar", tar_bytes()),
+            ("a.tgz", gz.finish().unwrap()),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let members = classify_path(&db, &path, true).unwrap();
+            assert!(members.iter().any(|m| m.mismatches()), "{name}");
+        }
+
+        let path = dir.join("a.txt");
+        std::fs::write(&path, "not an archive").unwrap();
+        let err = classify_path(&db, &path, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
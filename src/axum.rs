@@ -0,0 +1,189 @@
+//! Integration with the axum web framework.
+//!
+//! Enabled by the `axum` feature. [`TypedContentType`] extracts a request's
+//! `Content-Type` header, rejecting requests whose type is missing,
+//! malformed or not in the global database, and [`ServeWithMime`] sets a
+//! response's `Content-Type` from the path of the file it serves:
+//!
+//! ```
+//! use axum::{routing::post, Router};
+//! use minimime::axum::{ServeWithMime, TypedContentType};
+//!
+//! async fn upload(content_type: TypedContentType, body: String) -> String {
+//!     format!("{} bytes of {}", body.len(), content_type.info.extension)
+//! }
+//!
+//! async fn report() -> ServeWithMime<&'static str> {
+//!     ServeWithMime::new("report.csv", "month,total\n")
+//! }
+//!
+//! let app: Router = Router::new()
+//!     .route("/upload", post(upload))
+//!     .route("/report", axum::routing::get(report));
+//! ```
+
+use std::{fmt, path::PathBuf};
+
+use ::axum::{
+    extract::FromRequestParts,
+    http::{header::CONTENT_TYPE, request::Parts, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::{Info, MediaType};
+
+/// Extractor for a request's `Content-Type`, resolved through the global
+/// database.
+///
+/// Requests without the header, with a malformed one or with a type the
+/// database doesn't know are rejected with a [`ContentTypeRejection`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedContentType {
+    /// The header value as parsed, parameters included
+    pub media_type: MediaType,
+    /// Entry for the header's type
+    pub info: Info,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for TypedContentType {
+    type Rejection = ContentTypeRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let value = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .ok_or(ContentTypeRejection::Missing)?;
+        let media_type = value
+            .to_str()
+            .ok()
+            .and_then(|value| MediaType::parse(value).ok())
+            .ok_or(ContentTypeRejection::Malformed)?;
+        let info = crate::lookup_by_content_type(media_type.essence())
+            .ok_or(ContentTypeRejection::Unknown)?;
+        Ok(TypedContentType { media_type, info })
+    }
+}
+
+/// Why a request was rejected by [`TypedContentType`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentTypeRejection {
+    /// The request has no `Content-Type` header
+    Missing,
+    /// The header is not a valid media type
+    Malformed,
+    /// The database doesn't know the header's type
+    Unknown,
+}
+
+impl fmt::Display for ContentTypeRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ContentTypeRejection::Missing => "missing Content-Type header",
+            ContentTypeRejection::Malformed => "malformed Content-Type header",
+            ContentTypeRejection::Unknown => "unsupported Content-Type",
+        })
+    }
+}
+
+impl std::error::Error for ContentTypeRejection {}
+
+/// Answers `400 Bad Request` for a malformed header and `415 Unsupported
+/// Media Type` otherwise.
+impl IntoResponse for ContentTypeRejection {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ContentTypeRejection::Malformed => StatusCode::BAD_REQUEST,
+            ContentTypeRejection::Missing | ContentTypeRejection::Unknown => {
+                StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// A response body served with the `Content-Type` of a file path.
+///
+/// The type is looked up from the path's file name in the global database;
+/// unknown names are served as `application/octet-stream`. The path is not
+/// opened, so the body can come from anywhere.
+#[derive(Debug, Clone)]
+pub struct ServeWithMime<B> {
+    path: PathBuf,
+    body: B,
+}
+
+impl<B> ServeWithMime<B> {
+    /// Wraps a body served as the file at `path`.
+    pub fn new(path: impl Into<PathBuf>, body: B) -> Self {
+        ServeWithMime {
+            path: path.into(),
+            body,
+        }
+    }
+}
+
+impl<B: IntoResponse> IntoResponse for ServeWithMime<B> {
+    fn into_response(self) -> Response {
+        let content_type = crate::lookup_by_path(&self.path)
+            .and_then(|info| HeaderValue::from_str(&info.content_type).ok())
+            .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+        let mut response = self.body.into_response();
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+        response
+    }
+}
+
+#[cfg(all(test, feature = "ext-db", feature = "content-type-db"))]
+mod tests {
+    use ::axum::http::Request;
+
+    use super::*;
+
+    async fn extract(content_type: Option<&str>) -> Result<TypedContentType, ContentTypeRejection> {
+        let mut request = Request::builder();
+        if let Some(content_type) = content_type {
+            request = request.header(CONTENT_TYPE, content_type);
+        }
+        let (mut parts, ()) = request.body(()).unwrap().into_parts();
+        TypedContentType::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_extractor() {
+        let typed = extract(Some("Application/JSON; charset=utf-8"))
+            .await
+            .unwrap();
+        assert_eq!(&*typed.info.extension, "json");
+        assert_eq!(typed.media_type.param("charset"), Some("utf-8"));
+
+        assert_eq!(extract(None).await, Err(ContentTypeRejection::Missing));
+        assert_eq!(
+            extract(Some("json")).await,
+            Err(ContentTypeRejection::Malformed)
+        );
+        assert_eq!(
+            extract(Some("application/x-unknown")).await,
+            Err(ContentTypeRejection::Unknown)
+        );
+        assert_eq!(
+            ContentTypeRejection::Malformed.into_response().status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            ContentTypeRejection::Unknown.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+
+    #[test]
+    fn test_serve_with_mime() {
+        let content_type =
+            |path| ServeWithMime::new(path, "body").into_response().headers()[CONTENT_TYPE].clone();
+        assert_eq!(content_type("static/app.CSS"), "text/css");
+        assert_eq!(
+            content_type("downloads/archive.tar.gz"),
+            "application/x-gtar"
+        );
+        assert_eq!(content_type("no-extension"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,53 @@
+//! Internals of the lookup hot path, for benchmarking alternative backends.
+//!
+//! A lookup normalizes its query (finding the extension of a file name,
+//! folding its case) and then searches a table. The functions here are the
+//! ones [`Db`](crate::Db) and the global lookups use for the first step, so
+//! a benchmark of another table (a perfect hash, a trie, a different
+//! hasher) can put the same work in front of it, and compare it with
+//! [`TableHasher`] maps and the sorted [`tables`](crate::tables).
+//!
+//! Requires the `bench-support` feature. This module exists for the
+//! crate's benchmarks and is not covered by semver: it changes with the
+//! internals it exposes.
+
+use std::borrow::Cow;
+
+use crate::normalize;
+
+/// Length of the stack buffer the lookups fold case into; longer queries
+/// allocate.
+pub const FOLD_BUFFER_LEN: usize = normalize::MAX_FOLDED_LEN;
+
+/// The hasher of the [`Db`](crate::Db) tables, as chosen by the `fxhash`
+/// and `ahash` features.
+pub type TableHasher = crate::db::BuildHasher;
+
+/// Returns the extension of a file name, as the lookups find it.
+pub fn extension(filename: &str) -> Option<&str> {
+    normalize::extension(filename)
+}
+
+/// Returns the compound extension of a file name (`tar.gz`), which the
+/// lookups try before [`extension`].
+pub fn compound_extension(filename: &str) -> Option<&str> {
+    normalize::compound_extension(filename)
+}
+
+/// Lowercases the ASCII letters of a query into `buf`, as the lookups do,
+/// allocating only for queries longer than [`FOLD_BUFFER_LEN`].
+pub fn fold_ascii_lowercase<'a>(
+    input: &'a str,
+    buf: &'a mut [u8; FOLD_BUFFER_LEN],
+) -> Cow<'a, str> {
+    normalize::fold_ascii_lowercase(input, buf)
+}
+
+/// Checks whether the global lookups still search the static tables rather
+/// than the database, so a benchmark knows which path it measures. The
+/// database takes over once it is modified or loaded from files named in
+/// the environment.
+#[cfg(feature = "global")]
+pub fn global_uses_static_tables() -> bool {
+    crate::global::tables_current()
+}
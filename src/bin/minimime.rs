@@ -0,0 +1,260 @@
+//! Command-line lookups against the embedded database.
+//!
+//! ```text
+//! minimime ext pdf                  # application/pdf
+//! minimime file path/to/doc.docx    # by name, and by content if the file exists
+//! minimime type application/json   # json
+//! minimime --json ext pdf           # the whole entry as JSON
+//! ```
+//!
+//! Exits with 1 when nothing matches and 2 on usage errors, so shell scripts
+//! can branch on the status; an unknown extension also prints the closest
+//! known ones to stderr. Requires the `cli` feature.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use minimime::{sniff::PREFIX_LEN, Db, Info};
+
+const USAGE: &str = "\
+usage: minimime [--json] <command> <argument>
+
+commands:
+  ext <extension>        content type for a file extension
+  file <path>            content type for a file, from its name and, if it
+                         exists, its leading bytes
+  type <content-type>    preferred extension for a content type
+
+options:
+  --json                 print the whole entry as JSON
+  -h, --help             print this help";
+
+/// A parsed command line.
+#[derive(Debug, PartialEq)]
+struct Command {
+    kind: Kind,
+    argument: String,
+    json: bool,
+}
+
+/// What a [`Command`] looks up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Extension,
+    File,
+    ContentType,
+}
+
+/// Parses the arguments after the program name.
+///
+/// Returns `Ok(None)` when help was asked for.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Command>, String> {
+    let mut json = false;
+    let mut positional = Vec::new();
+    let mut options_done = false;
+    for arg in args {
+        match arg.as_str() {
+            "--" if !options_done => options_done = true,
+            "--json" if !options_done => json = true,
+            "-h" | "--help" if !options_done => return Ok(None),
+            option if !options_done && option.starts_with('-') && option.len() > 1 => {
+                return Err(format!("unknown option: {option}"));
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let [command, argument]: [String; 2] = positional
+        .try_into()
+        .map_err(|_| "expected a command and one argument".to_string())?;
+    let kind = match command.as_str() {
+        "ext" => Kind::Extension,
+        "file" => Kind::File,
+        "type" => Kind::ContentType,
+        _ => return Err(format!("unknown command: {command}")),
+    };
+    Ok(Some(Command {
+        kind,
+        argument,
+        json,
+    }))
+}
+
+/// Runs a lookup, reading the file for [`Kind::File`] if it exists.
+fn lookup<'a>(db: &'a Db, command: &Command) -> io::Result<Option<&'a Info>> {
+    let argument = command.argument.as_str();
+    Ok(match command.kind {
+        Kind::Extension => db.lookup_by_extension(argument),
+        Kind::ContentType => db.lookup_by_content_type(argument),
+        Kind::File => match File::open(argument) {
+            Ok(file) => {
+                let mut prefix = Vec::with_capacity(PREFIX_LEN);
+                file.take(PREFIX_LEN as u64).read_to_end(&mut prefix)?;
+                db.lookup_by_filename_and_magic(argument, &prefix)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => db.lookup_by_filename(argument),
+            Err(err) => return Err(err),
+        },
+    })
+}
+
+/// Formats a found entry: the content type, or the extension for a content
+/// type lookup, or the whole entry as JSON.
+fn render(info: &Info, command: &Command) -> String {
+    if command.json {
+        serde_json::to_string(info).expect("entries serialize to JSON")
+    } else if command.kind == Kind::ContentType {
+        info.extension.to_string()
+    } else {
+        info.content_type.to_string()
+    }
+}
+
+/// Builds a "did you mean" hint for an unknown extension, if any known
+/// extension is close.
+fn hint(db: &Db, command: &Command) -> Option<String> {
+    if command.kind != Kind::Extension {
+        return None;
+    }
+    let suggestions: Vec<String> = db
+        .suggest_extensions(&command.argument)
+        .into_iter()
+        .map(|extension| format!(".{extension}"))
+        .collect();
+    let (last, rest) = suggestions.split_last()?;
+    Some(match rest {
+        [] => format!("did you mean {last}?"),
+        rest => format!("did you mean {} or {last}?", rest.join(", ")),
+    })
+}
+
+fn main() -> ExitCode {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(command)) => command,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("minimime: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let db = match Db::new() {
+        Ok(db) => db,
+        Err(err) => {
+            eprintln!("minimime: cannot load the database: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    match lookup(&db, &command) {
+        Ok(Some(info)) => {
+            println!("{}", render(info, &command));
+            ExitCode::SUCCESS
+        }
+        Ok(None) => {
+            if let Some(hint) = hint(&db, &command) {
+                eprintln!("minimime: unknown extension {}; {hint}", command.argument);
+            }
+            ExitCode::from(1)
+        }
+        Err(err) => {
+            eprintln!("minimime: {}: {err}", command.argument);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Command>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn run(db: &Db, args: &[&str]) -> Option<String> {
+        let command = parse(args).unwrap().unwrap();
+        lookup(db, &command)
+            .unwrap()
+            .map(|info| render(info, &command))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse(&["ext", "pdf", "--json"]),
+            Ok(Some(Command {
+                kind: Kind::Extension,
+                argument: "pdf".to_string(),
+                json: true,
+            }))
+        );
+        assert_eq!(
+            parse(&["file", "--", "-odd.txt"])
+                .unwrap()
+                .unwrap()
+                .argument,
+            "-odd.txt"
+        );
+        assert_eq!(parse(&["--help"]), Ok(None));
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["ext"]).is_err());
+        assert!(parse(&["ext", "pdf", "txt"]).is_err());
+        assert!(parse(&["magic", "pdf"]).is_err());
+        assert!(parse(&["--yaml", "ext", "pdf"]).is_err());
+    }
+
+    #[test]
+    fn test_lookups() {
+        let db = Db::new().unwrap();
+        assert_eq!(
+            run(&db, &["ext", "pdf"]).as_deref(),
+            Some("application/pdf")
+        );
+        assert_eq!(
+            run(&db, &["type", "application/json"]).as_deref(),
+            Some("json")
+        );
+        assert_eq!(run(&db, &["ext", "frog"]), None);
+
+        let json = run(&db, &["--json", "ext", "pdf"]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["content_type"], "application/pdf");
+        assert_eq!(value["extension"], "pdf");
+    }
+
+    #[test]
+    fn test_hint() {
+        let db = Db::new().unwrap();
+        let hint = |args: &[&str]| hint(&db, &parse(args).unwrap().unwrap());
+        assert_eq!(
+            hint(&["ext", "xlxs"]).as_deref(),
+            Some("did you mean .xlsx or .xls?")
+        );
+        assert_eq!(
+            hint(&["ext", "tar.gzz"]).as_deref(),
+            Some("did you mean .tar.gz, .tar.bz2 or .tar.xz?")
+        );
+        assert_eq!(hint(&["ext", "qqqqqqqq"]), None);
+        assert_eq!(hint(&["type", "image/jpg"]), None);
+    }
+
+    #[test]
+    fn test_file() {
+        let db = Db::new().unwrap();
+        // A name that does not exist is typed by name alone.
+        assert_eq!(
+            run(&db, &["file", "missing/archive.tar.gz"]).as_deref(),
+            Some("application/x-gtar")
+        );
+
+        let path = std::env::temp_dir().join(format!("minimime-cli-{}.pdf", std::process::id()));
+        std::fs::write(&path, b"RIFF\x24\0\0\0WEBPVP8 ").unwrap();
+        let sniffed = run(&db, &["file", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(sniffed.as_deref(), Some("image/webp"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Coarse categories of content types.
+//!
+//! Upload validators usually want to know "is this an image?" rather than
+//! compare content type strings. [`Category`] sorts a content type into one
+//! of a few such buckets, from its top-level media type plus curated lists
+//! of archive and font types, which don't have a top-level type of their
+//! own (fonts only do since RFC 8081, and many are still served as
+//! `application/*`).
+
+/// The broad kind of a content type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Category {
+    /// `image/*`
+    Image,
+    /// `audio/*`
+    Audio,
+    /// `video/*`
+    Video,
+    /// Text as [`extraction::is_text`](crate::extraction::is_text) sees it:
+    /// `text/*`, JSON, XML, JavaScript, ...
+    Text,
+    /// `font/*` and legacy font types such as `application/x-font-ttf`
+    Font,
+    /// Archives and compressed files such as ZIP, tar, gzip or 7z
+    Archive,
+    /// Anything else, such as PDFs, executables or office documents
+    Other,
+}
+
+/// Font types outside `font/*`; a trailing `*` matches any rest.
+const FONT_TYPES: &[&str] = &[
+    "application/font-*",
+    "application/vnd.ms-fontobject",
+    "application/x-font-*",
+];
+
+/// Archive and compression types; a trailing `*` matches any rest.
+const ARCHIVE_TYPES: &[&str] = &[
+    "application/gzip",
+    "application/java-archive",
+    "application/vnd.rar",
+    "application/x-7z-compressed",
+    "application/x-bzip2",
+    "application/x-gtar",
+    "application/x-gzip",
+    "application/x-rar-compressed",
+    "application/x-tar",
+    "application/x-xz",
+    "application/zip",
+    "application/zstd",
+];
+
+/// Sorts a content type into its [`Category`].
+///
+/// Fonts and archives come first, then the top-level media types `image`,
+/// `audio` and `video`, then text. Parameters are ignored and the comparison
+/// is ASCII case-insensitive. Prefer [`Info::category`](crate::Info::category)
+/// where an entry is at hand: it also counts the entry's `font` and
+/// `archive` tags.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::category::{self, Category};
+///
+/// assert_eq!(category::of("Image/PNG"), Category::Image);
+/// assert_eq!(category::of("application/x-font-ttf"), Category::Font);
+/// assert_eq!(category::of("application/zip"), Category::Archive);
+/// assert_eq!(category::of("application/json; charset=utf-8"), Category::Text);
+/// assert_eq!(category::of("application/pdf"), Category::Other);
+/// ```
+pub fn of(content_type: &str) -> Category {
+    classify(content_type, false, false)
+}
+
+/// Sorts a content type into its [`Category`], counting it as a font or an
+/// archive if its entry is tagged as one.
+pub(crate) fn classify(content_type: &str, is_font: bool, is_archive: bool) -> Category {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    let ty = essence.split_once('/').map_or("", |(ty, _)| ty);
+    if is_font || ty.eq_ignore_ascii_case("font") || matches_any(essence, FONT_TYPES) {
+        Category::Font
+    } else if is_archive || matches_any(essence, ARCHIVE_TYPES) {
+        Category::Archive
+    } else if ty.eq_ignore_ascii_case("image") {
+        Category::Image
+    } else if ty.eq_ignore_ascii_case("audio") {
+        Category::Audio
+    } else if ty.eq_ignore_ascii_case("video") {
+        Category::Video
+    } else if crate::extraction::is_text(essence) {
+        Category::Text
+    } else {
+        Category::Other
+    }
+}
+
+/// Checks an essence against patterns where a trailing `*` matches any rest.
+fn matches_any(essence: &str, patterns: &[&str]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| crate::media_type::matches_prefix_pattern(pattern, essence))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_of() {
+        for (content_type, category) in [
+            ("image/webp", Category::Image),
+            ("IMAGE/svg+xml", Category::Image),
+            ("audio/mpeg", Category::Audio),
+            ("video/mp4; codecs=avc1", Category::Video),
+            ("text/plain", Category::Text),
+            ("application/xml", Category::Text),
+            ("font/woff2", Category::Font),
+            ("application/font-woff", Category::Font),
+            ("application/vnd.ms-fontobject", Category::Font),
+            ("application/x-7z-compressed", Category::Archive),
+            ("application/x-gtar", Category::Archive),
+            ("application/octet-stream", Category::Other),
+            ("", Category::Other),
+        ] {
+            assert_eq!(of(content_type), category, "{content_type}");
+        }
+    }
+
+    #[test]
+    fn test_tags_win() {
+        assert_eq!(
+            classify("application/octet-stream", true, false),
+            Category::Font
+        );
+        assert_eq!(
+            classify("application/x-iso9660-image", false, true),
+            Category::Archive
+        );
+    }
+}
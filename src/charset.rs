@@ -0,0 +1,250 @@
+//! Character encoding detection for text content.
+//!
+//! Knowing that a file is `text/plain` is not enough to emit a correct
+//! `Content-Type: text/plain; charset=...` header or to decode it. [`detect`]
+//! guesses the encoding of a text buffer from a byte order mark, UTF-16 byte
+//! patterns and UTF-8 validity, falling back to Latin-1. Like the sniffer, it
+//! only needs a prefix of the file and never allocates. With `std`,
+//! [`content_type_header`] appends the guess to a content type as a
+//! ready-to-use header value.
+
+use core::fmt;
+
+/// A character encoding, as told apart by [`detect`].
+///
+/// With the `serde` feature it serializes as its IANA name (see
+/// [`Charset::as_str`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Charset {
+    /// UTF-8, including plain ASCII
+    #[cfg_attr(feature = "serde", serde(rename = "utf-8"))]
+    Utf8,
+    /// UTF-16, little-endian
+    #[cfg_attr(feature = "serde", serde(rename = "utf-16le"))]
+    Utf16Le,
+    /// UTF-16, big-endian
+    #[cfg_attr(feature = "serde", serde(rename = "utf-16be"))]
+    Utf16Be,
+    /// ISO-8859-1, the fallback for 8-bit text that is not UTF-8
+    #[cfg_attr(feature = "serde", serde(rename = "iso-8859-1"))]
+    Latin1,
+}
+
+impl Charset {
+    /// Returns the IANA charset name, for a `charset=` parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::charset::Charset;
+    ///
+    /// assert_eq!(Charset::Utf16Le.as_str(), "utf-16le");
+    /// ```
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Charset::Utf8 => "utf-8",
+            Charset::Utf16Le => "utf-16le",
+            Charset::Utf16Be => "utf-16be",
+            Charset::Latin1 => "iso-8859-1",
+        }
+    }
+
+    /// Returns the byte order mark for this charset, if it has one.
+    pub const fn bom(self) -> Option<&'static [u8]> {
+        match self {
+            Charset::Utf8 => Some(b"\xEF\xBB\xBF"),
+            Charset::Utf16Le => Some(b"\xFF\xFE"),
+            Charset::Utf16Be => Some(b"\xFE\xFF"),
+            Charset::Latin1 => None,
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Detects the character encoding of text.
+///
+/// In order:
+///
+/// 1. A UTF-8 or UTF-16 byte order mark decides.
+/// 2. Without one, text where at least half the byte pairs have a zero byte
+///    on the same side, and none on the other, is UTF-16 (mostly-ASCII
+///    UTF-16 looks like that).
+/// 3. Other content with NUL bytes or control characters besides tab, line
+///    breaks, form feed and escape is not text, and yields `None`.
+/// 4. Valid UTF-8 is UTF-8. A multi-byte sequence cut off at the end of the
+///    buffer is tolerated, so a prefix of the file is enough.
+/// 5. Anything else is Latin-1.
+///
+/// Empty input is UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::charset::{self, Charset};
+///
+/// assert_eq!(charset::detect("naïve".as_bytes()), Some(Charset::Utf8));
+/// assert_eq!(charset::detect(b"na\xEFve"), Some(Charset::Latin1));
+/// assert_eq!(charset::detect(b"h\0i\0"), Some(Charset::Utf16Le));
+/// assert_eq!(charset::detect(b"\x89PNG\r\n\x1a\n\0\0"), None);
+/// ```
+pub fn detect(bytes: &[u8]) -> Option<Charset> {
+    for charset in [Charset::Utf8, Charset::Utf16Le, Charset::Utf16Be] {
+        if charset.bom().is_some_and(|bom| bytes.starts_with(bom)) {
+            return Some(charset);
+        }
+    }
+    if let Some(charset) = utf16_pattern(bytes) {
+        return Some(charset);
+    }
+    if bytes
+        .iter()
+        .any(|&b| (b < 0x20 && !b"\t\n\x0C\r\x1B".contains(&b)) || b == 0x7F)
+    {
+        return None;
+    }
+    match core::str::from_utf8(bytes) {
+        Ok(_) => Some(Charset::Utf8),
+        Err(err) if err.error_len().is_none() => Some(Charset::Utf8),
+        Err(_) => Some(Charset::Latin1),
+    }
+}
+
+/// Builds a `Content-Type` header value with the detected charset of the
+/// content.
+///
+/// A `charset=` parameter is appended for textual types (see
+/// [`extraction::is_text`](crate::extraction::is_text)) whose content
+/// [`detect`] recognizes as text. The content type is returned as it is if it
+/// already has a charset, is not textual or is malformed, or if the content
+/// is binary.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::charset;
+///
+/// assert_eq!(
+///     charset::content_type_header("text/csv", "naïve,1".as_bytes()),
+///     "text/csv; charset=utf-8"
+/// );
+/// assert_eq!(
+///     charset::content_type_header("text/plain", b"\xFF\xFEh\0i\0"),
+///     "text/plain; charset=utf-16le"
+/// );
+/// assert_eq!(charset::content_type_header("image/png", b"\x89PNG"), "image/png");
+/// ```
+#[cfg(feature = "std")]
+pub fn content_type_header(content_type: &str, bytes: &[u8]) -> String {
+    let declared = match crate::MediaType::parse(content_type) {
+        Ok(media_type) => media_type.param("charset").is_some(),
+        Err(_) => true,
+    };
+    match detect(bytes) {
+        Some(charset) if !declared && crate::extraction::is_text(content_type) => {
+            format!("{}; charset={charset}", content_type.trim())
+        }
+        _ => content_type.to_string(),
+    }
+}
+
+/// Recognizes BOM-less UTF-16 from where its zero bytes fall.
+fn utf16_pattern(bytes: &[u8]) -> Option<Charset> {
+    let pairs = bytes.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for pair in bytes.chunks_exact(2) {
+        even_zeros += usize::from(pair[0] == 0);
+        odd_zeros += usize::from(pair[1] == 0);
+    }
+    if odd_zeros * 2 >= pairs && even_zeros == 0 {
+        Some(Charset::Utf16Le)
+    } else if even_zeros * 2 >= pairs && odd_zeros == 0 {
+        Some(Charset::Utf16Be)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bom() {
+        assert_eq!(detect(b"\xEF\xBB\xBFhello"), Some(Charset::Utf8));
+        assert_eq!(detect(b"\xFF\xFEh\0"), Some(Charset::Utf16Le));
+        assert_eq!(detect(b"\xFE\xFF\0h"), Some(Charset::Utf16Be));
+    }
+
+    #[test]
+    fn test_utf16_pattern() {
+        assert_eq!(detect(b"h\0e\0l\0l\0o\0\xE9\0"), Some(Charset::Utf16Le));
+        // "hel" and a CJK character.
+        assert_eq!(detect(b"\0h\0e\0l\x4E\x2D"), Some(Charset::Utf16Be));
+        // Too few zeros on one side to call it UTF-16.
+        assert_eq!(detect(b"\0\x01\x02\x03\x04\x05"), None);
+    }
+
+    #[test]
+    fn test_utf8_and_latin1() {
+        assert_eq!(detect(b""), Some(Charset::Utf8));
+        assert_eq!(detect(b"plain ascii\r\n\tline"), Some(Charset::Utf8));
+        assert_eq!(detect("日本語".as_bytes()), Some(Charset::Utf8));
+        // A sequence cut off by the end of the prefix.
+        assert_eq!(detect(&"日本".as_bytes()[..4]), Some(Charset::Utf8));
+        assert_eq!(detect(b"caf\xE9 cr\xE8me"), Some(Charset::Latin1));
+        assert_eq!(detect(b"\xC3\x28"), Some(Charset::Latin1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_content_type_header() {
+        assert_eq!(
+            content_type_header("text/html", b"<p>caf\xE9</p>"),
+            "text/html; charset=iso-8859-1"
+        );
+        assert_eq!(
+            content_type_header("application/json", b"{}"),
+            "application/json; charset=utf-8"
+        );
+        assert_eq!(
+            content_type_header("text/plain; charset=us-ascii", b"hi"),
+            "text/plain; charset=us-ascii"
+        );
+        assert_eq!(
+            content_type_header("text/plain", b"\0\x01\x02\x03\x04\x05"),
+            "text/plain"
+        );
+        assert_eq!(content_type_header("text", b"hi"), "text");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_uses_iana_names() {
+        for charset in [
+            Charset::Utf8,
+            Charset::Utf16Le,
+            Charset::Utf16Be,
+            Charset::Latin1,
+        ] {
+            let json = serde_json::to_string(&charset).unwrap();
+            assert_eq!(json, format!("\"{charset}\""));
+            assert_eq!(serde_json::from_str::<Charset>(&json).unwrap(), charset);
+        }
+    }
+
+    #[test]
+    fn test_binary() {
+        assert_eq!(detect(b"text\0with a nul"), None);
+        assert_eq!(detect(b"bell\x07"), None);
+        assert_eq!(detect(b"\x7FELF"), None);
+    }
+}
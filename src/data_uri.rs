@@ -0,0 +1,125 @@
+//! `data:` URIs for inlining files.
+
+use std::fmt::Write;
+
+use crate::{charset, Db, TransferEncoding};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl Db {
+    /// Builds a `data:` URI (RFC 2397) that inlines a file, for small assets
+    /// in HTML, CSS or emails.
+    ///
+    /// The content type is looked up from `filename`, with the detected
+    /// charset for textual types (see
+    /// [`charset::content_type_header`](crate::charset::content_type_header)).
+    /// Types whose encoding is `base64` are base64-encoded; the others, text
+    /// and markup, are percent-encoded, which keeps them readable. Unknown
+    /// file names are inlined as base64 `application/octet-stream`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// assert_eq!(db.to_data_uri("dot.png", b"\x89PNG"), "data:image/png;base64,iVBORw==");
+    /// assert_eq!(
+    ///     db.to_data_uri("hello.txt", b"hi there!"),
+    ///     "data:text/plain;charset=utf-8,hi%20there!"
+    /// );
+    /// ```
+    pub fn to_data_uri(&self, filename: &str, bytes: &[u8]) -> String {
+        let info = self.lookup_by_filename(filename);
+        let content_type = info.map_or("application/octet-stream", |info| &info.content_type);
+        let base64 = info.is_none_or(|info| info.encoding == TransferEncoding::Base64);
+        let header = charset::content_type_header(content_type, bytes).replace("; ", ";");
+
+        let mut uri = format!("data:{header}");
+        if base64 {
+            uri.reserve(8 + bytes.len().div_ceil(3) * 4);
+            uri.push_str(";base64,");
+            encode_base64(bytes, &mut uri);
+        } else {
+            uri.reserve(1 + bytes.len());
+            uri.push(',');
+            encode_percent(bytes, &mut uri);
+        }
+        uri
+    }
+}
+
+/// Appends standard, padded base64 (RFC 4648) of `bytes`.
+pub(crate) fn encode_base64(bytes: &[u8], out: &mut String) {
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(group >> (18 - 6 * i)) as usize & 0x3F] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+}
+
+/// Appends `bytes` with everything but unreserved characters and safe
+/// delimiters percent-encoded.
+fn encode_percent(bytes: &[u8], out: &mut String) {
+    for &byte in bytes {
+        if byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/?".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base64(bytes: &[u8]) -> String {
+        let mut out = String::new();
+        encode_base64(bytes, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_base64() {
+        // RFC 4648, section 10.
+        for (bytes, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(bytes.as_bytes()), encoded);
+        }
+        assert_eq!(base64(&[0xFF, 0xEF]), "/+8=");
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_to_data_uri() {
+        let db = Db::new().unwrap();
+        assert_eq!(
+            db.to_data_uri("icon.svg", b"<svg a=\"1\"/>#"),
+            "data:image/svg+xml;charset=utf-8,%3Csvg%20a=%221%22/%3E%23"
+        );
+        assert_eq!(
+            db.to_data_uri("page.html", "ü%".as_bytes()),
+            "data:text/html;charset=utf-8,%C3%BC%25"
+        );
+        assert_eq!(
+            db.to_data_uri("unknown", b"\0\x01"),
+            "data:application/octet-stream;base64,AAE="
+        );
+        assert_eq!(db.to_data_uri("empty.gif", b""), "data:image/gif;base64,");
+    }
+}
@@ -0,0 +1,2429 @@
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    fmt,
+    sync::Arc,
+};
+
+use crate::{
+    glob, media_type, normalize, sniff, tables, telemetry, Error, Info, LoadReport, LookupOptions,
+    Origin, TransferEncoding,
+};
+
+/// Hasher for the lookup tables, chosen with the `fxhash` or `ahash` feature.
+///
+/// The tables are static and read-only, so the HashDoS resistance of the
+/// default SipHash buys nothing, while it dominates lookup cost. If both
+/// features are enabled, `fxhash` is used.
+#[cfg(feature = "fxhash")]
+pub(crate) type BuildHasher = rustc_hash::FxBuildHasher;
+#[cfg(all(feature = "ahash", not(feature = "fxhash")))]
+pub(crate) type BuildHasher = ahash::RandomState;
+#[cfg(not(any(feature = "fxhash", feature = "ahash")))]
+pub(crate) type BuildHasher = std::collections::hash_map::RandomState;
+
+/// A lookup table keyed by extension or content type.
+///
+/// Keys of the embedded entries borrow the `'static` strings of the
+/// generated tables, so [`Db::new`] allocates only the tables themselves.
+pub(crate) type Map<V> = HashMap<Cow<'static, str>, V, BuildHasher>;
+
+/// Internal database for MIME type lookups.
+///
+/// This struct manages the hash maps used for fast MIME type lookups
+/// by file extension and content type.
+///
+/// With the `serde` feature enabled, a whole `Db` (including any custom
+/// entries) can be serialized and later restored without re-parsing the
+/// text database files.
+///
+/// The tables are reference-counted, so cloning a `Db` is cheap and clones
+/// share their entries until one of them is modified (see [`Db::merge`]).
+/// `Db` is `Send + Sync` and can be stored directly in the application state
+/// of a web framework.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Db {
+    pub(crate) ext_db: Arc<Map<Info>>,
+    pub(crate) content_type_db: Arc<Map<Info>>,
+    /// Glob patterns on file names, in registration order
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) filename_rules: Arc<Vec<(String, Info)>>,
+    /// Whether extension and content type lookups match case exactly (see
+    /// [`DbBuilder::case_sensitive`])
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) case_sensitive: bool,
+    /// Signatures of the shared-mime-info database, by descending priority
+    #[cfg(feature = "xdg")]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) magic_rules: Arc<Vec<crate::xdg::MagicRule>>,
+}
+
+// `Db` must stay shareable across threads.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Db>();
+};
+
+impl Db {
+    /// Creates a new database instance and loads the embedded data files.
+    pub fn new() -> Result<Self, Error> {
+        let mut ext_db = Map::default();
+        let mut content_type_db = Map::default();
+
+        // Load extension database
+        Self::load_ext_db(&mut ext_db)?;
+        // Load content type database
+        Self::load_content_type_db(&mut content_type_db)?;
+
+        Ok(Db {
+            ext_db: Arc::new(ext_db),
+            content_type_db: Arc::new(content_type_db),
+            filename_rules: Arc::default(),
+            case_sensitive: false,
+            #[cfg(feature = "xdg")]
+            magic_rules: Arc::default(),
+        })
+    }
+
+    /// Creates a new database instance like [`Db::new`], along with a
+    /// [`LoadReport`] of the lines of the embedded data files that were
+    /// skipped as malformed or dropped as duplicates and conflicts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (db, report) = minimime::Db::new_with_report().unwrap();
+    /// assert!(report.is_clean(), "{report}");
+    /// assert!(db.lookup_by_extension("pdf").is_some());
+    /// ```
+    pub fn new_with_report() -> Result<(Self, LoadReport), Error> {
+        Ok((Self::new()?, LoadReport::embedded()))
+    }
+
+    /// Returns a [`DbBuilder`] for assembling a database from the embedded
+    /// data, extra entries and files.
+    pub fn builder() -> DbBuilder {
+        DbBuilder::new()
+    }
+
+    /// Parses a database in the text format of the embedded database files.
+    ///
+    /// Each line holds `extension content_type encoding [weight [tags]]`,
+    /// separated by whitespace. Blank lines are ignored, and `#` starts a comment that
+    /// runs to the end of the line, so hand-maintained override files can be
+    /// annotated. When several lines share an extension (or a content type),
+    /// the highest weight wins, then the first line, as in the Ruby
+    /// `mini_mime` gem; see [`Db::parse_with`] to let the last line win
+    /// instead. See the README for the full format.
+    ///
+    /// The result holds only the parsed entries; [`Db::merge`] it into a
+    /// [`Db::new`] database to override or extend the embedded one.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Db)` with the parsed entries
+    /// * `Err(ParseError)` for the first malformed line
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Db, MergeStrategy};
+    ///
+    /// let overrides = Db::parse(
+    ///     "# Internal report formats\n\
+    ///      rpt  application/vnd.acme.report  base64\n\
+    ///      \n\
+    ///      csv  text/csv  quoted-printable  # served as text\n",
+    /// )
+    /// .unwrap();
+    /// let mut db = Db::new().unwrap();
+    /// db.merge(overrides, MergeStrategy::PreferOther).unwrap();
+    /// assert!(db.lookup_by_extension("rpt").is_some());
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        Self::parse_with(text, InsertionPolicy::FirstWins)
+    }
+
+    /// Parses a database like [`Db::parse`], resolving lines that share a
+    /// key and a weight as `policy` says.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Db, InsertionPolicy};
+    ///
+    /// let text = "log text/plain 8bit\nlog text/x-log 8bit\n";
+    /// let first = Db::parse_with(text, InsertionPolicy::FirstWins).unwrap();
+    /// assert_eq!(&*first.lookup_by_extension("log").unwrap().content_type, "text/plain");
+    /// let last = Db::parse_with(text, InsertionPolicy::LastWins).unwrap();
+    /// assert_eq!(&*last.lookup_by_extension("log").unwrap().content_type, "text/x-log");
+    /// ```
+    pub fn parse_with(text: &str, policy: InsertionPolicy) -> Result<Self, ParseError> {
+        let mut ext_db: Map<Info> = Map::default();
+        let mut content_type_db: Map<Info> = Map::default();
+        for (index, raw) in text.lines().enumerate() {
+            if let Some(info) = Self::parse_line(raw, index + 1)? {
+                Self::insert_parsed(&mut ext_db, &mut content_type_db, info, policy);
+            }
+        }
+        Ok(Db {
+            ext_db: Arc::new(ext_db),
+            content_type_db: Arc::new(content_type_db),
+            filename_rules: Arc::default(),
+            case_sensitive: false,
+            #[cfg(feature = "xdg")]
+            magic_rules: Arc::default(),
+        })
+    }
+
+    /// Adds a parsed entry to the tables being built by [`Db::parse_with`],
+    /// keeping the heavier entry for each key, or else the one `policy`
+    /// picks.
+    fn insert_parsed(
+        ext_db: &mut Map<Info>,
+        content_type_db: &mut Map<Info>,
+        info: Info,
+        policy: InsertionPolicy,
+    ) {
+        let replaces = |existing: &Info| match policy {
+            InsertionPolicy::FirstWins => info.weight > existing.weight,
+            InsertionPolicy::LastWins => info.weight >= existing.weight,
+        };
+        for (map, key) in [
+            (ext_db, extension_key(info.extension.clone())),
+            (content_type_db, lowercase_key(info.content_type.clone())),
+        ] {
+            match map.entry(key) {
+                Entry::Occupied(mut entry) if replaces(entry.get()) => {
+                    entry.insert(info.clone());
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(entry) => {
+                    entry.insert(info.clone());
+                }
+            }
+        }
+    }
+
+    /// Parses one database line, returning `None` for a blank or comment-only
+    /// line.
+    fn parse_line(raw: &str, line_number: usize) -> Result<Option<Info>, ParseError> {
+        let line = raw.split('#').next().unwrap_or_default();
+        if line.trim().is_empty() {
+            return Ok(None);
+        }
+        Info::new(line)
+            .filter(|_| line.split_whitespace().count() <= 5)
+            .map(Some)
+            .ok_or_else(|| ParseError {
+                line: line_number,
+                content: raw.to_string(),
+            })
+    }
+
+    /// Reads and parses a database file; see [`Db::parse`] for the format.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Db)` with the parsed entries
+    /// * `Err` if the file cannot be read or holds a malformed line; the
+    ///   error carries the path
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        Self::from_file_with(path.as_ref(), InsertionPolicy::FirstWins)
+    }
+
+    /// Reads and parses a database file as [`Db::parse_with`] does.
+    fn from_file_with(path: &std::path::Path, policy: InsertionPolicy) -> Result<Self, Error> {
+        let load = || Ok(Self::parse_with(&std::fs::read_to_string(path)?, policy)?);
+        load().map_err(|err: Error| err.in_file(path))
+    }
+
+    /// Reads a database file and merges its entries into this database,
+    /// overriding existing entries for the same extensions and content
+    /// types.
+    ///
+    /// See [`Db::load_extra_from_reader`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` once the entries are merged
+    /// * `Err` if the file cannot be read or holds a malformed line; the
+    ///   error carries the path, and the database is left unchanged
+    pub fn load_extra_from_path(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path).map_err(|err| Error::from(err).in_file(path))?;
+        self.load_extra_from_reader(std::io::BufReader::new(file))
+            .map_err(|err| err.in_file(path))
+    }
+
+    /// Reads a database in the format of [`Db::parse`] line by line and
+    /// merges its entries into this database.
+    ///
+    /// Loaded entries override existing ones for the same extensions and
+    /// content types, so each load takes precedence over the embedded data
+    /// and over earlier loads. Within one source, lines sharing a key are
+    /// resolved as in [`Db::parse`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` once the entries are merged
+    /// * `Err` if reading fails ([`Error::Io`]) or a line is malformed
+    ///   ([`Error::Parse`]); the database is left unchanged
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let mut db = Db::new().unwrap();
+    /// db.load_extra_from_reader("csv text/x-acme-csv quoted-printable\n".as_bytes())
+    ///     .unwrap();
+    /// assert_eq!(&*db.lookup_by_extension("csv").unwrap().content_type, "text/x-acme-csv");
+    /// ```
+    pub fn load_extra_from_reader(&mut self, reader: impl std::io::BufRead) -> Result<(), Error> {
+        let mut ext_db = Map::default();
+        let mut content_type_db = Map::default();
+        for (index, raw) in reader.lines().enumerate() {
+            if let Some(info) = Self::parse_line(&raw?, index + 1)? {
+                Self::insert_parsed(
+                    &mut ext_db,
+                    &mut content_type_db,
+                    info,
+                    InsertionPolicy::FirstWins,
+                );
+            }
+        }
+        let extra = Db {
+            ext_db: Arc::new(ext_db),
+            content_type_db: Arc::new(content_type_db),
+            filename_rules: Arc::default(),
+            case_sensitive: false,
+            #[cfg(feature = "xdg")]
+            magic_rules: Arc::default(),
+        };
+        Ok(self.merge(extra, MergeStrategy::PreferOther)?)
+    }
+
+    /// Loads the file extension to MIME type database.
+    ///
+    /// This method copies the build-time generated [`tables::BY_EXTENSION`]
+    /// table (from `ext_mime.db`) into the extension lookup hash map.
+    fn load_ext_db(ext_db: &mut Map<Info>) -> Result<(), Error> {
+        ext_db.reserve(tables::BY_EXTENSION.len());
+        for entry in tables::BY_EXTENSION {
+            ext_db.insert(Cow::Borrowed(entry.extension), entry.to_info());
+        }
+        Ok(())
+    }
+
+    /// Loads the content type to MIME type database.
+    ///
+    /// This method copies the build-time generated [`tables::BY_CONTENT_TYPE`]
+    /// table (from `content_type_mime.db`) into the content type lookup hash map.
+    fn load_content_type_db(content_type_db: &mut Map<Info>) -> Result<(), Error> {
+        content_type_db.reserve(tables::BY_CONTENT_TYPE.len());
+        for entry in tables::BY_CONTENT_TYPE {
+            let info = entry.to_info();
+            content_type_db.insert(lowercase_key(info.content_type.clone()), info);
+        }
+        Ok(())
+    }
+
+    /// Looks up MIME information by file extension.
+    ///
+    /// The lookup is case-insensitive: extensions are stored lowercase, and
+    /// the query is lowercased on the stack, so it doesn't allocate unless
+    /// the extension has non-ASCII letters that change case, like `É`.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - File extension (with or without leading dot)
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Info)` if the extension is found
+    /// * `None` if the extension is not recognized
+    pub fn lookup_by_extension(&self, extension: &str) -> Option<&Info> {
+        telemetry::time_lookup("extension", || self.find_extension(extension))
+    }
+
+    /// Finds an extension by its lowercase form.
+    fn find_extension(&self, extension: &str) -> Option<&Info> {
+        let mut buf = [0; normalize::MAX_FOLDED_LEN];
+        let found = self
+            .ext_db
+            .get(&*normalize::lowercase(extension, &mut buf))?;
+        // A case-sensitive database only finds extensions as registered.
+        (!self.case_sensitive || *found.extension == *extension).then_some(found)
+    }
+
+    /// Looks up MIME information by content type.
+    ///
+    /// The lookup is ASCII case-insensitive, and a `Content-Type` header
+    /// value works too: parameters and surrounding whitespace are ignored,
+    /// so `Text/HTML; charset=utf-8` finds `text/html`. Legacy spellings
+    /// without an entry of their own, such as `image/jpg` or
+    /// `application/x-gzip`, find the entry of their canonical type (see
+    /// [`canonicalize`](crate::canonicalize)).
+    ///
+    /// # Arguments
+    ///
+    /// * `content_type` - MIME content type (e.g., "text/plain")
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Info)` if the content type is found
+    /// * `None` if the content type is not recognized
+    pub fn lookup_by_content_type(&self, content_type: &str) -> Option<&Info> {
+        telemetry::time_lookup("content_type", || self.find_content_type(content_type))
+    }
+
+    /// Looks up MIME information by content type, treating unknown types
+    /// with a structured syntax suffix like their base type.
+    ///
+    /// Vendor types such as `application/vnd.acme.order+json` are rarely in
+    /// a database, yet what matters for handling them is that they are JSON.
+    /// A type the database knows is returned as by
+    /// [`Db::lookup_by_content_type`]. An unknown type whose suffix names a
+    /// known `application/{suffix}` type (`json`, `xml`, `zip`, `gzip`, ...)
+    /// gets an entry with its own content type and the extension and tags
+    /// of the base type; [`Info::structured_suffix`] tells which. Text
+    /// suffixes make a text entry, so `is_binary()` is false and a
+    /// `charset=utf-8` is in order.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Info)` with the known or derived entry
+    /// * `None` if the type is unknown and has no known suffix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let order = db.lookup_by_content_type_or_suffix("application/vnd.acme.order+json").unwrap();
+    /// assert_eq!(&*order.content_type, "application/vnd.acme.order+json");
+    /// assert_eq!(&*order.extension, "json");
+    /// assert!(!order.is_binary());
+    /// ```
+    pub fn lookup_by_content_type_or_suffix(&self, content_type: &str) -> Option<Info> {
+        if let Some(info) = self.lookup_by_content_type(content_type) {
+            return Some(info.clone());
+        }
+        let essence = media_type::essence(content_type)?.to_ascii_lowercase();
+        let (_, suffix) = essence.rsplit_once('+')?;
+        let base = self.lookup_by_content_type(&format!("application/{suffix}"))?;
+        let encoding = if crate::extraction::is_text(&essence) {
+            TransferEncoding::QuotedPrintable
+        } else {
+            base.encoding.clone()
+        };
+        Some(Info {
+            extension: base.extension.clone(),
+            content_type: Cow::Owned(essence),
+            encoding,
+            weight: 0,
+            tags: base.tags.clone(),
+            origin: base.origin,
+        })
+    }
+
+    /// Looks up MIME information by a [`mime::Mime`] from the `mime` crate.
+    ///
+    /// Parameters such as `charset` are ignored, as in
+    /// [`Db::lookup_by_content_type`]. Requires the `mime` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let info = db.lookup_by_mime(&mime::TEXT_HTML_UTF_8).unwrap();
+    /// assert_eq!(&*info.extension, "html");
+    /// ```
+    #[cfg(feature = "mime")]
+    pub fn lookup_by_mime(&self, mime: &mime::Mime) -> Option<&Info> {
+        self.lookup_by_content_type(mime.essence_str())
+    }
+
+    /// Finds a content type: as given if it is already in normal form (the
+    /// common case), otherwise by its lowercase essence.
+    pub(crate) fn find_content_type(&self, content_type: &str) -> Option<&Info> {
+        let found = self.content_type_db.get(content_type).or_else(|| {
+            let essence = media_type::essence(content_type)?;
+            let mut buf = [0; normalize::MAX_FOLDED_LEN];
+            self.content_type_db
+                .get(&*normalize::fold_ascii_lowercase(essence, &mut buf))
+                .or_else(|| {
+                    self.content_type_db
+                        .get(media_type::resolve_alias(essence)?)
+                })
+        })?;
+        if self.case_sensitive {
+            // Aliases resolve only when spelled in lowercase, as listed.
+            let essence = media_type::essence(content_type)?;
+            let exact = *found.content_type == *essence
+                || !essence.bytes().any(|b| b.is_ascii_uppercase())
+                    && media_type::resolve_alias(essence) == Some(&*found.content_type);
+            return exact.then_some(found);
+        }
+        Some(found)
+    }
+
+    /// Returns every extension entry for a content type.
+    ///
+    /// [`Db::lookup_by_content_type`] only returns the preferred entry; many
+    /// types have several extensions (`image/jpeg` has `jpeg`, `jpg`, `jpe`
+    /// and `jfif`). The preferred one comes first, then the others sorted by
+    /// extension. The content type is compared case-insensitively.
+    ///
+    /// This scans the whole extension table, so cache the result on hot
+    /// paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let extensions: Vec<&str> = db
+    ///     .extensions_for("image/jpeg")
+    ///     .iter()
+    ///     .map(|info| &*info.extension)
+    ///     .collect();
+    /// assert_eq!(extensions, ["jpeg", "jfif", "jpe", "jpg"]);
+    /// ```
+    pub fn extensions_for(&self, content_type: &str) -> Vec<&Info> {
+        let mut infos: Vec<&Info> = self
+            .ext_db
+            .values()
+            .filter(|info| info.content_type.eq_ignore_ascii_case(content_type))
+            .collect();
+        // Matched with the stored spelling of the content type.
+        let preferred = infos
+            .first()
+            .and_then(|info| self.lookup_by_content_type(&info.content_type))
+            .map(|info| &*info.extension);
+        infos.sort_unstable_by_key(|info| (Some(&*info.extension) != preferred, &info.extension));
+        infos
+    }
+
+    /// Returns the extension to name a file of a content type with.
+    ///
+    /// This is the extension of the entry [`Db::lookup_by_content_type`]
+    /// finds: the canonical one the content type table lists for the type,
+    /// such as `jpeg` for `image/jpeg`. A `Content-Type` header value works
+    /// too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// assert_eq!(db.preferred_extension("application/pdf; name=x"), Some("pdf"));
+    /// assert_eq!(db.preferred_extension("application/x-unknown"), None);
+    /// ```
+    pub fn preferred_extension(&self, content_type: &str) -> Option<&str> {
+        self.lookup_by_content_type(content_type)
+            .map(|info| &*info.extension)
+    }
+
+    /// Returns every extension of a content type, in a fixed order.
+    ///
+    /// The [preferred extension](Db::preferred_extension) comes first, then
+    /// the other extensions mapped to the type, in alphabetical order: `jpeg`,
+    /// `jfif`, `jpe`, `jpg` for `image/jpeg`. A `Content-Type` header value
+    /// works too. Unlike [`Db::extensions_for`] this includes the preferred
+    /// extension even where the extension table maps it to another type.
+    ///
+    /// This scans the whole extension table, so cache the result on hot
+    /// paths.
+    pub fn all_extensions(&self, content_type: &str) -> Vec<&str> {
+        let mut extensions: Vec<&str> =
+            self.preferred_extension(content_type).into_iter().collect();
+        let essence = media_type::essence(content_type).unwrap_or(content_type);
+        for info in self.extensions_for(essence) {
+            if !extensions.contains(&&*info.extension) {
+                extensions.push(&info.extension);
+            }
+        }
+        extensions
+    }
+
+    /// Looks up MIME information by filename.
+    ///
+    /// Filename rules registered with [`Db::add_filename_rule`] are checked
+    /// first, since they refine extensions the table knows; otherwise the
+    /// file extension is extracted and looked up. A
+    /// known compound extension spanning the last two segments wins over the
+    /// last one alone, so `backup.tar.gz` is a `tar.gz` (`application/x-gtar`)
+    /// rather than a `gz`. The lookup is case-insensitive.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - Full filename or path
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Info)` if the file extension is recognized
+    /// * `None` if the file has no extension or the extension is not recognized
+    pub fn lookup_by_filename(&self, filename: &str) -> Option<&Info> {
+        telemetry::time_lookup("filename", || {
+            self.find_filename_rule(filename)
+                .or_else(|| {
+                    normalize::compound_extension(filename).and_then(|ext| self.find_extension(ext))
+                })
+                .or_else(|| normalize::extension(filename).and_then(|ext| self.find_extension(ext)))
+        })
+    }
+
+    /// Looks up MIME information by the file name in a URL.
+    ///
+    /// The last segment of the URL's path is percent-decoded and looked up
+    /// like [`Db::lookup_by_filename`]; the scheme, host, query string and
+    /// fragment are ignored. Relative URLs and bare paths work too.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Info)` if the file extension is recognized
+    /// * `None` if the path ends with `/` or has no extension, or the
+    ///   extension is not recognized
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let url = "https://cdn.example.com/a/b/image%20name.PNG?sig=abc";
+    /// assert_eq!(&*db.lookup_by_url(url).unwrap().content_type, "image/png");
+    /// assert!(db.lookup_by_url("https://example.com").is_none());
+    /// ```
+    pub fn lookup_by_url(&self, url: &str) -> Option<&Info> {
+        self.lookup_by_filename(&normalize::url_file_name(url))
+    }
+
+    /// Looks up MIME information by filename, reading it as `options` say.
+    ///
+    /// With [`LookupOptions::lenient`], URL paths with a query string,
+    /// backup copies and bare extensions such as `.pdf` find an entry too;
+    /// with [`LookupOptions::strict`] this is [`Db::lookup_by_filename`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Db, LookupOptions};
+    ///
+    /// let db = Db::new().unwrap();
+    /// let options = LookupOptions::strict().backup_suffixes(true);
+    /// let info = db.lookup_by_filename_with("draft.md~", &options).unwrap();
+    /// assert_eq!(&*info.content_type, "text/markdown");
+    /// ```
+    pub fn lookup_by_filename_with(
+        &self,
+        filename: &str,
+        options: &LookupOptions,
+    ) -> Option<&Info> {
+        let name = options.file_name(filename);
+        self.lookup_by_filename(name).or_else(|| {
+            options
+                .bare_extension(name)
+                .and_then(|ext| self.lookup_by_extension(ext))
+        })
+    }
+
+    /// Looks up MIME information by the file name of a path.
+    ///
+    /// Like [`Db::lookup_by_filename`], but for [`Path`](std::path::Path)s
+    /// and [`OsStr`](std::ffi::OsStr)s that need not be valid UTF-8. Bytes
+    /// that are not UTF-8 can't be part of a known extension, so a name such
+    /// as `b"r\xE9sum\xE9.PDF"` still finds `pdf`. Only the name is used:
+    /// the file is not opened (with the `tokio` feature, `lookup_by_path_async`
+    /// also sniffs its content).
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Info)` if the file extension is recognized
+    /// * `None` if the path has no file name, or its extension is missing or
+    ///   not recognized
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let path = PathBuf::from("exports").join("Q3.tar.gz");
+    /// assert_eq!(&*db.lookup_by_path(&path).unwrap().extension, "tar.gz");
+    /// ```
+    pub fn lookup_by_path(&self, path: impl AsRef<std::path::Path>) -> Option<&Info> {
+        let name = path.as_ref().file_name()?;
+        self.lookup_by_filename(&name.to_string_lossy())
+    }
+
+    /// Iterates over the extension entries carrying a tag, in no particular
+    /// order.
+    ///
+    /// The embedded entries are tagged when the database is generated
+    /// (`archive`, `document`, `executable`, `font`, `media`, `office`,
+    /// `web`; see [`tables::TAGS`]); entries loaded with [`Db::parse`] carry
+    /// the tags of their optional fifth column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let office: Vec<&str> = db.by_tag("office").map(|info| &*info.extension).collect();
+    /// assert!(office.contains(&"docx"));
+    /// ```
+    pub fn by_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Info> + 'a {
+        self.ext_db.values().filter(move |info| info.has_tag(tag))
+    }
+
+    /// Iterates over the extension entries of a top-level media type, such
+    /// as `image`, `audio`, `video` or `font`, in no particular order.
+    ///
+    /// The top-level type is compared case-insensitively. Each extension is
+    /// its own entry, so a content type with several extensions appears
+    /// once per extension: file picker filters and lists of accepted upload
+    /// formats need them all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let mut images: Vec<&str> = db.by_media_type("image").map(|info| &*info.extension).collect();
+    /// images.sort_unstable();
+    /// assert!(images.contains(&"png") && images.contains(&"jpg"));
+    /// ```
+    pub fn by_media_type<'a>(&'a self, top_level: &'a str) -> impl Iterator<Item = &'a Info> + 'a {
+        self.ext_db.values().filter(move |info| {
+            info.content_type
+                .split_once('/')
+                .is_some_and(|(ty, _)| ty.eq_ignore_ascii_case(top_level))
+        })
+    }
+
+    /// Returns whether an extension has an entry, compared as by
+    /// [`Db::lookup_by_extension`].
+    ///
+    /// Unlike a lookup, this records no metrics.
+    pub fn contains_extension(&self, extension: &str) -> bool {
+        self.find_extension(extension).is_some()
+    }
+
+    /// Returns whether a content type has an entry, compared as by
+    /// [`Db::lookup_by_content_type`], aliases included.
+    ///
+    /// Unlike a lookup, this records no metrics.
+    pub fn contains_content_type(&self, content_type: &str) -> bool {
+        self.find_content_type(content_type).is_some()
+    }
+
+    /// Counts the entries of the database, e.g. for a health check or to
+    /// assert that a custom database is complete after merges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Db, Info};
+    ///
+    /// let mut db = Db::new().unwrap();
+    /// let before = db.stats();
+    /// db.register(Info::new("rpt application/vnd.acme.report base64").unwrap());
+    /// let after = db.stats();
+    /// assert_eq!(after.extensions, before.extensions + 1);
+    /// assert_eq!(after.by_top_level["application"], before.by_top_level["application"] + 1);
+    /// ```
+    pub fn stats(&self) -> DbStats {
+        let mut by_top_level = BTreeMap::new();
+        let mut alternate_extensions = 0;
+        for (extension, info) in self.ext_db.iter() {
+            let top_level = info
+                .content_type
+                .split_once('/')
+                .map_or("", |(ty, _)| ty)
+                .to_ascii_lowercase();
+            *by_top_level.entry(top_level).or_insert(0) += 1;
+            let preferred = self
+                .find_content_type(&info.content_type)
+                .is_some_and(|preferred| preferred.extension.eq_ignore_ascii_case(extension));
+            if !preferred {
+                alternate_extensions += 1;
+            }
+        }
+        DbStats {
+            extensions: self.ext_db.len(),
+            content_types: self.content_type_db.len(),
+            filename_rules: self.filename_rules.len(),
+            alternate_extensions,
+            by_top_level,
+        }
+    }
+
+    /// Registers a glob pattern mapping matching file names to an entry.
+    ///
+    /// Some naming conventions can't be expressed as a single extension:
+    /// `*.blade.php` templates, `CMakeLists.txt`, `*.conf.j2`. Patterns are
+    /// matched against the file name (not the directories), ignoring ASCII
+    /// case; `*` matches any run of characters and `?` a single one.
+    ///
+    /// [`Db::lookup_by_filename`] checks the rules, in registration order,
+    /// before the extension table. They have to come first: names such as
+    /// these end in extensions the table already knows (`php`, `txt`), so a
+    /// rule checked after the table would never apply to them. Rules only refine what
+    /// the extension alone would say; [`Db::lookup_by_extension`] is not
+    /// affected. Matching allocates nothing, and a rule whose literal tail
+    /// (`.blade.php`) the name doesn't end with is skipped at once.
+    /// Registering a pattern again replaces its entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern for the file name
+    /// * `info` - The entry returned for matching file names
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Db, Info};
+    ///
+    /// let mut db = Db::new().unwrap();
+    /// let cmake = Info::new("txt text/x-cmake quoted-printable").unwrap();
+    /// db.add_filename_rule("CMakeLists.txt", cmake);
+    /// assert_eq!(&*db.lookup_by_filename("src/CMakeLists.txt").unwrap().content_type, "text/x-cmake");
+    /// assert_eq!(&*db.lookup_by_filename("notes.txt").unwrap().content_type, "text/plain");
+    /// ```
+    pub fn add_filename_rule(&mut self, pattern: &str, info: Info) {
+        let rules = Arc::make_mut(&mut self.filename_rules);
+        match rules.iter_mut().find(|(existing, _)| existing == pattern) {
+            Some((_, existing)) => *existing = info,
+            None => rules.push((pattern.to_string(), info)),
+        }
+    }
+
+    /// Registers an entry, e.g. for a proprietary file format.
+    ///
+    /// The entry replaces any existing one for its extension (compared
+    /// case-insensitively). It also becomes the entry for its content type,
+    /// unless that type already has one: registering `jfif` as another
+    /// `image/jpeg` extension keeps `jpeg` as the preferred extension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Db, Info};
+    ///
+    /// let mut db = Db::new().unwrap();
+    /// db.register(Info::new("rpt application/vnd.acme.report base64").unwrap());
+    /// assert_eq!(&*db.lookup_by_filename("q3.RPT").unwrap().content_type, "application/vnd.acme.report");
+    /// assert_eq!(&*db.lookup_by_content_type("application/vnd.acme.report").unwrap().extension, "rpt");
+    /// ```
+    pub fn register(&mut self, info: Info) {
+        Arc::make_mut(&mut self.content_type_db)
+            .entry(lowercase_key(info.content_type.clone()))
+            .or_insert_with(|| info.clone());
+        let key = extension_key(info.extension.clone());
+        Arc::make_mut(&mut self.ext_db).insert(key, info);
+    }
+
+    /// Registers an entry, resolving a collision with the existing entry
+    /// for its extension as `strategy` says.
+    ///
+    /// [`MergeStrategy::PreferOther`] replaces the existing entry, as
+    /// [`Db::register`] does; [`MergeStrategy::PreferSelf`] keeps it and
+    /// drops `info`; [`MergeStrategy::ErrorOnConflict`] returns the
+    /// collision and leaves the database unchanged. Registering an entry
+    /// equal to the existing one, origin included, is not a collision.
+    /// [`Db::overrides`] lists the embedded entries replaced this way.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` once the entry is registered or, with `PreferSelf`,
+    ///   dropped
+    /// * `Err(MergeConflict)` with `ErrorOnConflict` if the extension
+    ///   already has another entry
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Db, Info, MergeStrategy};
+    ///
+    /// let mut db = Db::new().unwrap();
+    /// let vendor = Info::new("csv application/vnd.acme.table+csv quoted-printable").unwrap();
+    /// assert!(db.register_with(vendor.clone(), MergeStrategy::ErrorOnConflict).is_err());
+    /// db.register_with(vendor.clone(), MergeStrategy::PreferSelf).unwrap();
+    /// assert_eq!(&*db.lookup_by_extension("csv").unwrap().content_type, "text/csv");
+    ///
+    /// db.register_with(vendor, MergeStrategy::PreferOther).unwrap();
+    /// let (embedded, current) = &db.overrides()[0];
+    /// assert_eq!(&*embedded.content_type, "text/csv");
+    /// assert_eq!(&*current.content_type, "application/vnd.acme.table+csv");
+    /// ```
+    pub fn register_with(
+        &mut self,
+        info: Info,
+        strategy: MergeStrategy,
+    ) -> Result<(), MergeConflict> {
+        let existing = self
+            .ext_db
+            .get(&*info.extension.to_lowercase())
+            .filter(|existing| **existing != info);
+        match (existing, strategy) {
+            (Some(_), MergeStrategy::PreferSelf) => return Ok(()),
+            (Some(existing), MergeStrategy::ErrorOnConflict) => {
+                return Err(MergeConflict {
+                    existing: Box::new(existing.clone()),
+                    incoming: Box::new(info),
+                })
+            }
+            _ => {}
+        }
+        self.register(info);
+        Ok(())
+    }
+
+    /// Lists the embedded entries shadowed by others: for every extension
+    /// of the embedded database whose entry has another [`Origin`] now,
+    /// such as one registered with [`Db::register`] or loaded from the
+    /// system database, the embedded entry and the one that replaced it,
+    /// sorted by extension.
+    ///
+    /// Only extension entries count; filename rules and content type
+    /// entries are left out.
+    pub fn overrides(&self) -> Vec<(Info, &Info)> {
+        let mut overrides: Vec<_> = self
+            .ext_db
+            .iter()
+            .filter(|(_, info)| info.origin != Origin::Embedded)
+            .filter_map(|(key, info)| Some((tables::find_by_extension(key)?.to_info(), info)))
+            .collect();
+        overrides.sort_by(|(a, _), (b, _)| a.extension.cmp(&b.extension));
+        overrides
+    }
+
+    /// Parses a database line and registers its entry; see [`Db::parse`]
+    /// for the format and [`Db::register`] for how it is added.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` once the entry is registered
+    /// * `Err(ParseError)` if the line is malformed, blank or only a comment
+    pub fn register_line(&mut self, line: &str) -> Result<(), ParseError> {
+        let info = Self::parse_line(line, 1)?.ok_or_else(|| ParseError {
+            line: 1,
+            content: line.to_string(),
+        })?;
+        self.register(info);
+        Ok(())
+    }
+
+    /// Removes the entry for an extension, returning it.
+    ///
+    /// If the entry was also the one for its content type, another extension
+    /// of that type takes its place (the first in alphabetical order), or
+    /// the content type is removed when none is left.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Info)` with the removed entry
+    /// * `None` if the extension is not registered
+    pub fn unregister_extension(&mut self, extension: &str) -> Option<Info> {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        let removed = Arc::make_mut(&mut self.ext_db).remove(&*extension)?;
+        let content_type = &*removed.content_type;
+        let key = lowercase_key(removed.content_type.clone());
+        if self
+            .content_type_db
+            .get(&key)
+            .is_some_and(|info| info.extension.eq_ignore_ascii_case(&extension))
+        {
+            let successor = self
+                .ext_db
+                .values()
+                .filter(|info| info.content_type.eq_ignore_ascii_case(content_type))
+                .min_by(|a, b| a.extension.cmp(&b.extension))
+                .cloned();
+            let content_type_db = Arc::make_mut(&mut self.content_type_db);
+            match successor {
+                Some(info) => content_type_db.insert(key, info),
+                None => content_type_db.remove(&*key),
+            };
+        }
+        Some(removed)
+    }
+
+    /// Builds an entry for a type named by an outside database (the
+    /// shared-mime-info globs or the Windows registry), borrowing the
+    /// encoding and tags of the entry for the same type. Unknown types get
+    /// `quoted-printable` if textual and `base64` otherwise.
+    #[cfg(any(feature = "xdg", feature = "windows-registry"))]
+    pub(crate) fn derived_info(&self, extension: &str, content_type: &str, weight: u16) -> Info {
+        let (encoding, tags) = match self.find_content_type(content_type) {
+            Some(known) => (known.encoding.clone(), known.tags.clone()),
+            None if crate::extraction::is_text(content_type) => {
+                (TransferEncoding::QuotedPrintable, Cow::default())
+            }
+            None => (TransferEncoding::Base64, Cow::default()),
+        };
+        Info {
+            extension: Cow::Owned(extension.to_string()),
+            content_type: Cow::Owned(content_type.to_string()),
+            encoding,
+            weight,
+            tags,
+            origin: Origin::System,
+        }
+    }
+
+    /// Finds the first filename rule matching the file name of a path.
+    fn find_filename_rule(&self, filename: &str) -> Option<&Info> {
+        if self.filename_rules.is_empty() {
+            return None;
+        }
+        let name = normalize::file_name(filename);
+        self.filename_rules
+            .iter()
+            .find(|(pattern, _)| glob::matches(pattern, name))
+            .map(|(_, info)| info)
+    }
+
+    /// Looks up MIME information by the magic bytes at the start of a file.
+    ///
+    /// See the [`sniff`] module for the recognized signatures. A database
+    /// loaded with `Db::with_system_mime_info` (`xdg` feature) tries the
+    /// system's signatures first.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The first bytes of the file
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Info)` if a known signature matches
+    /// * `None` if the content is not recognized
+    pub fn lookup_by_magic(&self, bytes: &[u8]) -> Option<&Info> {
+        telemetry::time_lookup("magic", || {
+            #[cfg(feature = "xdg")]
+            if let Some(info) = self.lookup_by_system_magic(bytes) {
+                return Some(info);
+            }
+            sniff::content_type(bytes).and_then(|content_type| self.find_content_type(content_type))
+        })
+    }
+
+    /// Looks up MIME information from both a file name and the magic bytes
+    /// at the start of the file.
+    ///
+    /// The sniffed type wins, since the content can't lie about itself,
+    /// except when it is only a generic container (ZIP, gzip, ...) and the
+    /// file name says what is inside: a `.docx` or `.epub` is a ZIP archive
+    /// and a `.tar.gz` a gzip stream, but the name is more specific.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - Full filename or path
+    /// * `bytes` - The first bytes of the file; see
+    ///   [`PREFIX_LEN`](crate::sniff::PREFIX_LEN) for how many are useful
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let zip = b"PK\x03\x04\x14\0\0\0";
+    /// let docx = db.lookup_by_filename_and_magic("report.docx", zip).unwrap();
+    /// assert_eq!(&*docx.extension, "docx");
+    /// let webp = db.lookup_by_filename_and_magic("photo.png", b"RIFF\x24\0\0\0WEBPVP8 ").unwrap();
+    /// assert_eq!(&*webp.content_type, "image/webp");
+    /// ```
+    pub fn lookup_by_filename_and_magic(&self, filename: &str, bytes: &[u8]) -> Option<&Info> {
+        combine(
+            self.lookup_by_filename(filename),
+            self.lookup_by_magic(bytes),
+        )
+    }
+
+    /// Looks up MIME information for a file from its path and content,
+    /// reading the first [`PREFIX_LEN`](crate::sniff::PREFIX_LEN) bytes
+    /// without blocking the async runtime.
+    ///
+    /// See [`Db::lookup_by_filename_and_magic`] for how the two are
+    /// combined. Requires the `tokio` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or read.
+    #[cfg(feature = "tokio")]
+    pub async fn lookup_by_path_async(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> std::io::Result<Option<&Info>> {
+        let path = path.as_ref();
+        let prefix = read_prefix(path).await?;
+        Ok(self.lookup_by_filename_and_magic(&path.to_string_lossy(), &prefix))
+    }
+
+    /// Detects the types of many files in parallel on the rayon thread pool,
+    /// from their paths and their first
+    /// [`PREFIX_LEN`](crate::sniff::PREFIX_LEN) bytes.
+    ///
+    /// Each file is detected as by [`Db::detect`] without a declared type.
+    /// Results come in the order of `paths`, one per path: a file that
+    /// can't be opened or read gets an error without failing the others.
+    /// Requires the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let paths = ["library/a.jpg", "library/b.mp4"];
+    /// for (path, detection) in paths.iter().zip(db.par_detect_files(&paths)) {
+    ///     match detection {
+    ///         Ok(detection) if detection.mismatch => println!("{path}: misnamed"),
+    ///         Ok(_) => {}
+    ///         Err(err) => eprintln!("{path}: {err}"),
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_detect_files<P: AsRef<std::path::Path> + Sync>(
+        &self,
+        paths: &[P],
+    ) -> Vec<std::io::Result<crate::detection::Detection>> {
+        use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+        paths
+            .par_iter()
+            .map(|path| {
+                let path = path.as_ref();
+                let prefix = read_file_prefix(path)?;
+                Ok(self.detect(Some(&path.to_string_lossy()), Some(&prefix), None))
+            })
+            .collect()
+    }
+
+    /// Suggests a file name for content of a given type.
+    ///
+    /// Path separators and control characters in `base` become `_`, trailing
+    /// dots and spaces are trimmed, and an empty result becomes `file`. The
+    /// preferred extension of `content_type` is then appended, unless `base`
+    /// already has an extension of that type: `photo.jpeg` stays as it is for
+    /// `image/jpeg` rather than becoming `photo.jpeg.jpg`. Parameters on the
+    /// content type are ignored, and an unknown content type adds nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The desired name, with or without an extension
+    /// * `content_type` - The content type of the file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// assert_eq!(db.suggest_filename("report", "application/pdf"), "report.pdf");
+    /// assert_eq!(db.suggest_filename("photo.JPG", "image/jpeg"), "photo.JPG");
+    /// assert_eq!(db.suggest_filename("../etc/passwd", "text/plain"), ".._etc_passwd.txt");
+    /// ```
+    pub fn suggest_filename(&self, base: &str, content_type: &str) -> String {
+        let mut name: String = base
+            .chars()
+            .map(|c| {
+                if matches!(c, '/' | '\\') || c.is_control() {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        name.truncate(name.trim_end_matches(['.', ' ']).len());
+        if name.is_empty() {
+            name.push_str("file");
+        }
+
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        let Some(info) = self.lookup_by_content_type(essence) else {
+            return name;
+        };
+        let has_extension = normalize::extension(&name)
+            .and_then(|ext| self.lookup_by_extension(ext))
+            .is_some_and(|own| own.content_type == info.content_type);
+        if !has_extension {
+            name.push('.');
+            name.push_str(&info.extension);
+        }
+        name
+    }
+
+    /// Merges the entries of another database into this one.
+    ///
+    /// Entries are keyed by extension and by content type, as in the lookup
+    /// tables. When both databases hold different entries for the same key,
+    /// `strategy` decides which one is kept. This makes layered
+    /// configurations (base, organization-wide, per-tenant) predictable.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The database whose entries are merged in
+    /// * `strategy` - How to resolve conflicting entries
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` once the entries are merged
+    /// * `Err(MergeConflict)` for the first conflict found with
+    ///   [`MergeStrategy::ErrorOnConflict`]; `self` is left unchanged
+    ///
+    /// Tables shared with clones of this database are copied before being
+    /// modified, so the clones are not affected.
+    pub fn merge(&mut self, other: Db, strategy: MergeStrategy) -> Result<(), MergeConflict> {
+        if strategy == MergeStrategy::ErrorOnConflict {
+            let conflict = Self::find_conflict(&self.ext_db, &other.ext_db)
+                .or_else(|| Self::find_conflict(&self.content_type_db, &other.content_type_db))
+                .or_else(|| {
+                    other.filename_rules.iter().find_map(|(pattern, incoming)| {
+                        self.filename_rules
+                            .iter()
+                            .find(|(existing, info)| existing == pattern && info != incoming)
+                            .map(|(_, existing)| (existing, incoming))
+                    })
+                });
+            if let Some((existing, incoming)) = conflict {
+                return Err(MergeConflict {
+                    existing: Box::new(existing.clone()),
+                    incoming: Box::new(incoming.clone()),
+                });
+            }
+        }
+
+        let prefer_other = strategy == MergeStrategy::PreferOther;
+        for (ours, theirs) in [
+            (&mut self.ext_db, other.ext_db),
+            (&mut self.content_type_db, other.content_type_db),
+        ] {
+            let ours = Arc::make_mut(ours);
+            let theirs = Arc::try_unwrap(theirs).unwrap_or_else(|shared| (*shared).clone());
+            for (key, info) in theirs {
+                match ours.entry(key) {
+                    Entry::Occupied(mut entry) if prefer_other => {
+                        entry.insert(info);
+                    }
+                    Entry::Occupied(_) => {}
+                    Entry::Vacant(entry) => {
+                        entry.insert(info);
+                    }
+                }
+            }
+        }
+        for (pattern, info) in other.filename_rules.iter() {
+            let exists = self
+                .filename_rules
+                .iter()
+                .any(|(existing, _)| existing == pattern);
+            if !exists || prefer_other {
+                self.add_filename_rule(pattern, info.clone());
+            }
+        }
+        #[cfg(feature = "xdg")]
+        if !other.magic_rules.is_empty() {
+            let rules = Arc::make_mut(&mut self.magic_rules);
+            rules.extend(other.magic_rules.iter().cloned());
+            rules.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+        }
+        Ok(())
+    }
+
+    /// Finds the first key present in both tables with differing entries.
+    fn find_conflict<'a>(
+        ours: &'a Map<Info>,
+        theirs: &'a Map<Info>,
+    ) -> Option<(&'a Info, &'a Info)> {
+        theirs.iter().find_map(|(key, incoming)| {
+            ours.get(key)
+                .filter(|existing| *existing != incoming)
+                .map(|existing| (existing, incoming))
+        })
+    }
+}
+
+/// Builder for a [`Db`] assembled from the embedded data, extra entries
+/// and files.
+///
+/// Sources are applied in the order they are added, each merged into the
+/// database built so far with the [precedence](DbBuilder::precedence)
+/// strategy. Files are only read, and text only parsed, in
+/// [`build`](DbBuilder::build). The result is an independent `Db`, not tied
+/// to the global database.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{Db, Info, MergeStrategy};
+///
+/// let db = Db::builder()
+///     .embedded(false)
+///     .text("rpt application/vnd.acme.report base64\ncsv text/csv quoted-printable")
+///     .entry(Info::new("csv text/x-acme-csv quoted-printable").unwrap())
+///     .precedence(MergeStrategy::PreferSelf)
+///     .build()
+///     .unwrap();
+/// assert!(db.lookup_by_extension("pdf").is_none());
+/// assert_eq!(&*db.lookup_by_extension("csv").unwrap().content_type, "text/csv");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DbBuilder {
+    embedded: bool,
+    sources: Vec<Source>,
+    strategy: MergeStrategy,
+    policy: InsertionPolicy,
+    case_sensitive: bool,
+}
+
+/// One source of entries for a [`DbBuilder`].
+#[derive(Debug, Clone)]
+enum Source {
+    Text(String),
+    File(std::path::PathBuf),
+    Entry(Info),
+}
+
+impl DbBuilder {
+    /// Creates a builder that loads the embedded data, lets later sources
+    /// override earlier ones, and looks up case-insensitively.
+    pub fn new() -> Self {
+        DbBuilder {
+            embedded: true,
+            sources: Vec::new(),
+            strategy: MergeStrategy::PreferOther,
+            policy: InsertionPolicy::FirstWins,
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether to start from the embedded database (the default) or
+    /// from an empty one.
+    pub fn embedded(mut self, embedded: bool) -> Self {
+        self.embedded = embedded;
+        self
+    }
+
+    /// Adds entries in the text format of [`Db::parse`].
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.sources.push(Source::Text(text.into()));
+        self
+    }
+
+    /// Adds the entries of a database file; see [`Db::from_file`].
+    pub fn file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.sources.push(Source::File(path.into()));
+        self
+    }
+
+    /// Adds a single entry.
+    pub fn entry(mut self, info: Info) -> Self {
+        self.sources.push(Source::Entry(info));
+        self
+    }
+
+    /// Sets how an added source's entries are merged with the ones before
+    /// it; see [`Db::merge`]. Defaults to [`MergeStrategy::PreferOther`], so
+    /// later sources override earlier ones and the embedded data.
+    pub fn precedence(mut self, strategy: MergeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets which line wins when lines of one text or file source share a
+    /// key and a weight; see [`Db::parse_with`]. Defaults to
+    /// [`InsertionPolicy::FirstWins`].
+    pub fn insertion_policy(mut self, policy: InsertionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets whether extension and content type lookups match case exactly.
+    ///
+    /// By default `PDF` finds `pdf` and `Text/HTML` finds `text/html`; a
+    /// case-sensitive database only finds them as stored. Parameters of
+    /// content types are still ignored, and filename rules still ignore
+    /// case.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Builds the database.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Db)` with the entries of all sources
+    /// * `Err` if a file cannot be read ([`Error::Io`]), a source holds a
+    ///   malformed line ([`Error::Parse`]), or a source conflicts with the
+    ///   entries before it under [`MergeStrategy::ErrorOnConflict`]
+    ///   ([`Error::Conflict`])
+    pub fn build(self) -> Result<Db, Error> {
+        let mut db = if self.embedded {
+            Db::new()?
+        } else {
+            Db::parse("")?
+        };
+        for source in self.sources {
+            let layer = match source {
+                Source::Text(text) => Db::parse_with(&text, self.policy)?,
+                Source::File(path) => Db::from_file_with(&path, self.policy)?,
+                Source::Entry(info) => {
+                    let mut layer = Db::parse("")?;
+                    layer.register(info);
+                    layer
+                }
+            };
+            db.merge(layer, self.strategy)?;
+        }
+        db.case_sensitive = self.case_sensitive;
+        Ok(db)
+    }
+}
+
+impl Default for DbBuilder {
+    fn default() -> Self {
+        DbBuilder::new()
+    }
+}
+
+/// Returns an extension as a map key: lowercase, and borrowing the
+/// `'static` string of an embedded entry where that already is.
+fn extension_key(extension: Cow<'static, str>) -> Cow<'static, str> {
+    match extension.to_lowercase() {
+        lowercase if lowercase == *extension => extension,
+        lowercase => Cow::Owned(lowercase),
+    }
+}
+
+/// Returns a content type as a map key: ASCII lowercase, and borrowing the
+/// `'static` string of an embedded entry where that already is.
+fn lowercase_key(content_type: Cow<'static, str>) -> Cow<'static, str> {
+    if content_type.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(content_type.to_ascii_lowercase())
+    } else {
+        content_type
+    }
+}
+
+/// Content types of formats that other formats are built on.
+const CONTAINERS: &[&str] = &[
+    "application/gzip",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/zip",
+    "application/zstd",
+];
+
+/// Checks whether the sniffed entry is only a generic container that the
+/// file name's entry says more about.
+pub(crate) fn refines_container(by_filename: &Info, sniffed: &Info) -> bool {
+    CONTAINERS.contains(&&*sniffed.content_type) && by_filename.content_type != sniffed.content_type
+}
+
+/// Picks between the entries for a file name and for sniffed bytes, as
+/// [`Db::lookup_by_filename_and_magic`] describes.
+pub(crate) fn combine<'a>(
+    by_filename: Option<&'a Info>,
+    sniffed: Option<&'a Info>,
+) -> Option<&'a Info> {
+    match (by_filename, sniffed) {
+        (Some(by_filename), Some(sniffed)) if refines_container(by_filename, sniffed) => {
+            Some(by_filename)
+        }
+        (by_filename, sniffed) => sniffed.or(by_filename),
+    }
+}
+
+/// Reads up to [`PREFIX_LEN`](sniff::PREFIX_LEN) bytes from the start of a
+/// file, for sniffing.
+#[cfg(any(feature = "walk", feature = "rayon"))]
+pub(crate) fn read_file_prefix(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(path)?;
+    let mut prefix = Vec::with_capacity(sniff::PREFIX_LEN);
+    file.take(sniff::PREFIX_LEN as u64)
+        .read_to_end(&mut prefix)?;
+    Ok(prefix)
+}
+
+/// Reads up to [`PREFIX_LEN`](sniff::PREFIX_LEN) bytes from the start of a
+/// file, for sniffing.
+#[cfg(feature = "tokio")]
+pub(crate) async fn read_prefix(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let file = tokio::fs::File::open(path).await?;
+    let mut prefix = Vec::with_capacity(sniff::PREFIX_LEN);
+    file.take(sniff::PREFIX_LEN as u64)
+        .read_to_end(&mut prefix)
+        .await?;
+    Ok(prefix)
+}
+
+/// Strategy for resolving conflicting entries in [`Db::merge`] and
+/// [`Db::register_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the entry already present in the database being merged into
+    PreferSelf,
+    /// Replace the existing entry with the incoming one
+    PreferOther,
+    /// Abort the merge without changes if any entry conflicts
+    ErrorOnConflict,
+}
+
+/// Which of the lines sharing a key and a weight a parsed database keeps,
+/// for [`Db::parse_with`] and [`DbBuilder::insertion_policy`].
+///
+/// A heavier line always wins, whatever the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InsertionPolicy {
+    /// Keep the first line, as the Ruby `mini_mime` gem does by searching
+    /// its sorted files; the embedded database is built this way
+    #[default]
+    FirstWins,
+    /// Keep the last line, so lines appended to a file override earlier
+    /// ones
+    LastWins,
+}
+
+/// Entry counts of a [`Db`], returned by [`Db::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DbStats {
+    /// Extension entries
+    pub extensions: usize,
+    /// Content type entries, aliases registered by a system database
+    /// included
+    pub content_types: usize,
+    /// Filename rules (see [`Db::add_filename_rule`])
+    pub filename_rules: usize,
+    /// Extensions that are not the preferred extension of their content
+    /// type, such as `jpg` next to `jpeg`
+    pub alternate_extensions: usize,
+    /// Extension entries per lowercase top-level type (`image`, `text`,
+    /// ...)
+    pub by_top_level: BTreeMap<String, usize>,
+}
+
+/// Error returned by [`Db::merge`] and [`Db::register_with`] when
+/// [`MergeStrategy::ErrorOnConflict`] finds two different entries for the
+/// same extension or content type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// The entry already present in the database
+    pub existing: Box<Info>,
+    /// The conflicting entry from the merged database
+    pub incoming: Box<Info>,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "conflicting MIME entries: {} {} ({}) vs {} {} ({})",
+            self.existing.extension,
+            self.existing.content_type,
+            self.existing.encoding,
+            self.incoming.extension,
+            self.incoming.content_type,
+            self.incoming.encoding,
+        )
+    }
+}
+
+impl std::error::Error for MergeConflict {}
+
+/// Error returned by [`Db::parse`] for a malformed database line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// The offending line
+    pub content: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "malformed MIME database line {}: {:?}",
+            self.line, self.content
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "serde", feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_db_serde_round_trip() {
+        let db = Db::new().unwrap();
+        let json = serde_json::to_string(&db).unwrap();
+        let restored: Db = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.ext_db, db.ext_db);
+        assert_eq!(restored.content_type_db, db.content_type_db);
+        assert_eq!(
+            &*restored.lookup_by_extension("pdf").unwrap().content_type,
+            "application/pdf"
+        );
+    }
+
+    /// Builds a database from inline `extension content_type encoding` lines.
+    fn db_from_lines(lines: &[&str]) -> Db {
+        let mut ext_db = Map::default();
+        let mut content_type_db = Map::default();
+        for info in lines.iter().filter_map(|line| Info::new(line)) {
+            ext_db.insert(info.extension.clone(), info.clone());
+            content_type_db.insert(lowercase_key(info.content_type.clone()), info);
+        }
+        Db {
+            ext_db: Arc::new(ext_db),
+            content_type_db: Arc::new(content_type_db),
+            filename_rules: Arc::default(),
+            case_sensitive: false,
+            #[cfg(feature = "xdg")]
+            magic_rules: Arc::default(),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut db = db_from_lines(&[
+            "jpeg image/jpeg base64",
+            "png image/png base64",
+            "csv text/csv 8bit",
+        ]);
+        db.register(Info::new("jpg image/jpeg base64").unwrap());
+        let stats = db.stats();
+        assert_eq!(stats.extensions, 4);
+        assert_eq!(stats.content_types, 3);
+        assert_eq!(stats.filename_rules, 0);
+        assert_eq!(stats.alternate_extensions, 1);
+        assert_eq!(
+            stats.by_top_level,
+            BTreeMap::from([("image".to_string(), 3), ("text".to_string(), 1)])
+        );
+        assert_eq!(Db::parse("").unwrap().stats(), DbStats::default());
+
+        assert!(db.contains_extension("JPG"));
+        assert!(!db.contains_extension("gif"));
+        let embedded = Db::new().unwrap();
+        for extension in ["pdf", ".pdf"] {
+            assert_eq!(
+                embedded.contains_extension(extension),
+                embedded.lookup_by_extension(extension).is_some(),
+                "{extension}"
+            );
+        }
+        assert!(db.contains_content_type("Image/PNG; q=1"));
+        assert!(!db.contains_content_type("image/gif"));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_by_media_type() {
+        let db = Db::new().unwrap();
+        let fonts: Vec<&Info> = db.by_media_type("FONT").collect();
+        assert!(fonts.iter().any(|info| &*info.extension == "woff2"));
+        assert!(fonts
+            .iter()
+            .all(|info| info.content_type.starts_with("font/")));
+        assert!(db
+            .by_media_type("video")
+            .any(|info| &*info.extension == "ts"));
+        assert_eq!(db.by_media_type("imag").count(), 0);
+        assert_eq!(db.by_media_type("").count(), 0);
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_lookup_by_content_type_or_suffix() {
+        let db = Db::new().unwrap();
+        let known = db
+            .lookup_by_content_type_or_suffix("image/svg+xml")
+            .unwrap();
+        assert_eq!(&*known.extension, "svg");
+
+        let feed = db
+            .lookup_by_content_type_or_suffix("Application/VND.Acme.Feed+XML; charset=utf-8")
+            .unwrap();
+        assert_eq!(&*feed.content_type, "application/vnd.acme.feed+xml");
+        assert_eq!(&*feed.extension, "xml");
+        assert_eq!(feed.structured_suffix(), Some("xml"));
+        assert!(!feed.is_binary());
+
+        let bundle = db
+            .lookup_by_content_type_or_suffix("application/vnd.acme.bundle+zip")
+            .unwrap();
+        assert_eq!(&*bundle.extension, "zip");
+        assert!(bundle.is_binary());
+
+        assert!(db
+            .lookup_by_content_type_or_suffix("application/vnd.acme+unknown")
+            .is_none());
+        assert!(db
+            .lookup_by_content_type_or_suffix("application/vnd.acme")
+            .is_none());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_content_type_aliases() {
+        let db = Db::new().unwrap();
+        let extension = |db: &Db, content_type| {
+            db.lookup_by_content_type(content_type)
+                .map(|info| info.extension.to_string())
+        };
+        assert_eq!(extension(&db, "image/jpg").as_deref(), Some("jpeg"));
+        assert_eq!(
+            extension(&db, "Application/X-GZIP; q=1").as_deref(),
+            Some("gz")
+        );
+        // Types with entries of their own keep them.
+        assert_eq!(
+            &*db.lookup_by_content_type("application/javascript")
+                .unwrap()
+                .content_type,
+            "application/javascript"
+        );
+
+        let strict = Db::builder().case_sensitive(true).build().unwrap();
+        assert_eq!(extension(&strict, "image/jpg").as_deref(), Some("jpeg"));
+        assert_eq!(extension(&strict, "image/JPG"), None);
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let base = || db_from_lines(&["csv text/csv 8bit", "pdf application/pdf base64"]);
+        let overlay = || {
+            db_from_lines(&[
+                "csv application/vnd.acme.csv 8bit",
+                "rpt application/vnd.acme.report base64",
+            ])
+        };
+
+        let mut db = base();
+        db.merge(overlay(), MergeStrategy::PreferSelf).unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("csv").unwrap().content_type,
+            "text/csv"
+        );
+        assert!(db.lookup_by_extension("rpt").is_some());
+
+        let mut db = base();
+        db.merge(overlay(), MergeStrategy::PreferOther).unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("csv").unwrap().content_type,
+            "application/vnd.acme.csv"
+        );
+        assert!(db.lookup_by_content_type("text/csv").is_some());
+
+        let mut db = base();
+        let err = db
+            .merge(overlay(), MergeStrategy::ErrorOnConflict)
+            .unwrap_err();
+        assert_eq!(&*err.existing.content_type, "text/csv");
+        assert_eq!(&*err.incoming.content_type, "application/vnd.acme.csv");
+        assert!(db.lookup_by_extension("rpt").is_none());
+
+        // Identical entries are not conflicts.
+        let mut db = base();
+        db.merge(base(), MergeStrategy::ErrorOnConflict).unwrap();
+        assert_eq!(db.ext_db.len(), 2);
+    }
+
+    #[test]
+    fn test_parse() {
+        let db = Db::parse(
+            "# Overrides for the reporting service\n\
+             \n\
+             rpt   application/vnd.acme.report  base64\n\
+             \t  # indented comment\n\
+             csv   text/csv                     quoted-printable  # trailing\n\
+             csv   application/vnd.acme.csv     8bit\n\
+             tsv   text/csv                     8bit  5\n",
+        )
+        .unwrap();
+        assert_eq!(db.ext_db.len(), 3);
+        assert_eq!(
+            &*db.lookup_by_extension("csv").unwrap().content_type,
+            "text/csv"
+        );
+        assert_eq!(
+            &*db.lookup_by_content_type("text/csv").unwrap().extension,
+            "tsv"
+        );
+        assert!(db
+            .lookup_by_content_type("application/vnd.acme.csv")
+            .is_some());
+
+        let err = Db::parse("# header\nrpt application/x-report\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.content, "rpt application/x-report");
+        let tagged = Db::parse("rpt application/x-report base64 1 office,internal").unwrap();
+        let info = tagged.lookup_by_extension("rpt").unwrap();
+        assert!(info.has_tag("office") && info.has_tag("internal"));
+        assert_eq!(tagged.by_tag("internal").count(), 1);
+        assert!(Db::parse("rpt application/x-report base64 1 office extra").is_err());
+        assert!(Db::parse("rpt application/x-report base64 heavy").is_err());
+        assert!(Db::parse("").unwrap().ext_db.is_empty());
+
+        let text = "csv text/csv 8bit\n\
+                    csv text/x-csv 8bit\n\
+                    tsv text/tab-separated-values 8bit 1\n\
+                    tsv text/x-tsv 8bit\n";
+        let last = Db::parse_with(text, InsertionPolicy::LastWins).unwrap();
+        assert_eq!(
+            &*last.lookup_by_extension("csv").unwrap().content_type,
+            "text/x-csv"
+        );
+        assert_eq!(
+            &*last.lookup_by_extension("tsv").unwrap().content_type,
+            "text/tab-separated-values"
+        );
+        assert_eq!(
+            Db::parse_with(text, InsertionPolicy::default())
+                .unwrap()
+                .ext_db,
+            Db::parse(text).unwrap().ext_db
+        );
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_embedded_files_parse() {
+        // Tags are generated at build time, so they are left out here, and
+        // parsed entries are custom ones.
+        let untagged = |map: &Map<Info>| -> Map<Info> {
+            map.iter()
+                .map(|(key, info)| {
+                    let tags = Default::default();
+                    (
+                        key.clone(),
+                        Info {
+                            tags,
+                            origin: Origin::Custom,
+                            ..info.clone()
+                        },
+                    )
+                })
+                .collect()
+        };
+        let db = Db::new().unwrap();
+        let ext_db = Db::parse(include_str!("db/ext_mime.db")).unwrap();
+        assert_eq!(*ext_db.ext_db, untagged(&db.ext_db));
+        let content_type_db = Db::parse(include_str!("db/content_type_mime.db")).unwrap();
+        assert_eq!(
+            *content_type_db.content_type_db,
+            untagged(&db.content_type_db)
+        );
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_compound_extensions() {
+        let db = Db::new().unwrap();
+        let cases = [
+            ("backup.tar.gz", "tar.gz", "application/x-gtar"),
+            ("dir/backup.TAR.BZ2", "tar.bz2", "application/x-gtar"),
+            ("logo.svg.gz", "svg.gz", "image/svg+xml"),
+            ("dark-mode.user.js", "user.js", "text/javascript"),
+            ("jquery.min.js", "js", "text/javascript"),
+            ("notes.2024.gz", "gz", "application/gzip"),
+            (".tar.gz", "gz", "application/gzip"),
+        ];
+        for (filename, extension, content_type) in cases {
+            let info = db.lookup_by_filename(filename).unwrap();
+            assert_eq!(&*info.extension, extension, "{filename}");
+            assert_eq!(&*info.content_type, content_type, "{filename}");
+        }
+        assert!(db.lookup_by_extension("tar.gz").unwrap().has_tag("archive"));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_preferred_and_all_extensions() {
+        let db = Db::new().unwrap();
+        assert_eq!(db.preferred_extension("image/jpeg"), Some("jpeg"));
+        assert_eq!(db.preferred_extension("IMAGE/JPEG; q=1"), Some("jpeg"));
+        assert_eq!(
+            db.all_extensions("Image/JPEG; q=1"),
+            ["jpeg", "jfif", "jpe", "jpg"]
+        );
+        // Listed by the content type table only.
+        assert_eq!(db.all_extensions("application/x-executable"), ["elf"]);
+        assert!(db.all_extensions("application/x-unknown").is_empty());
+        for info in db.content_type_db.values() {
+            let extensions = db.all_extensions(&info.content_type);
+            assert_eq!(extensions[0], &*info.extension);
+            assert!(extensions[1..].windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_lookup_by_path() {
+        use std::path::{Path, PathBuf};
+
+        let db = Db::new().unwrap();
+        let extension = |path: &Path| db.lookup_by_path(path).map(|info| &*info.extension);
+        assert_eq!(extension(Path::new("docs/Report.PDF")), Some("pdf"));
+        assert_eq!(
+            extension(&PathBuf::from("a").join("b.tar.gz")),
+            Some("tar.gz")
+        );
+        assert_eq!(extension(Path::new("docs.d/")), None);
+        assert_eq!(extension(Path::new("..")), None);
+        assert_eq!(extension(Path::new("")), None);
+
+        #[cfg(unix)]
+        {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+            let name = |bytes| Path::new(OsStr::from_bytes(bytes));
+            assert_eq!(extension(name(b"dir/r\xE9sum\xE9.PDF")), Some("pdf"));
+            assert_eq!(extension(name(b"\xFF\xFE.tar.GZ")), Some("tar.gz"));
+            assert_eq!(extension(name(b"photo.jp\xE9g")), None);
+        }
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_lookup_by_filename_with() {
+        let mut db = Db::new().unwrap();
+        db.add_filename_rule(".pdf", Info::new("pdfrc text/plain 8bit").unwrap());
+        let lenient = LookupOptions::lenient();
+        let extension = |filename, options: &LookupOptions| {
+            db.lookup_by_filename_with(filename, options)
+                .map(|info| &*info.extension)
+        };
+        for filename in ["photo.jpg?w=200", "notes.txt~", ".PDF", "a.tar.gz#x"] {
+            assert_eq!(
+                extension(filename, &LookupOptions::strict()),
+                db.lookup_by_filename(filename).map(|info| &*info.extension),
+                "{filename:?}"
+            );
+        }
+        assert_eq!(extension("/img/photo.JPG?w=200", &lenient), Some("jpg"));
+        assert_eq!(extension("backup.tar.gz.bak", &lenient), Some("tar.gz"));
+        assert_eq!(extension("notes.txt~", &lenient), Some("txt"));
+        assert_eq!(extension(".PNG", &lenient), Some("png"));
+        // Filename rules see the cleaned name and win over a bare extension.
+        assert_eq!(extension("~/.pdf?x", &lenient), Some("pdfrc"));
+        assert_eq!(extension("https://example.com/?a.pdf", &lenient), None);
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_lookup_by_filename_and_magic() {
+        let db = Db::new().unwrap();
+        let detect = |filename, bytes: &[u8]| {
+            db.lookup_by_filename_and_magic(filename, bytes)
+                .map(|info| &*info.extension)
+        };
+        let zip = b"PK\x03\x04";
+        assert_eq!(detect("book.epub", zip), Some("epub"));
+        assert_eq!(detect("upload", zip), Some("zip"));
+        assert_eq!(detect("backup.tar.gz", b"\x1F\x8B\x08"), Some("tar.gz"));
+        // A real signature beats a misleading name.
+        assert_eq!(detect("invoice.pdf", b"\x7FELF\x02\x01"), Some("elf"));
+        assert_eq!(detect("notes.txt", b"plain text"), Some("txt"));
+        assert_eq!(detect("unknown", b"plain text"), None);
+    }
+
+    #[cfg(all(feature = "tokio", feature = "ext-db", feature = "content-type-db"))]
+    #[tokio::test]
+    async fn test_lookup_by_path_async() {
+        let dir = std::env::temp_dir().join(format!("minimime-async-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("avatar.jpg");
+        std::fs::write(&path, b"RIFF\x24\0\0\0WEBPVP8 ").unwrap();
+
+        let db = Db::new().unwrap();
+        let info = db.lookup_by_path_async(&path).await.unwrap().unwrap();
+        assert_eq!(&*info.content_type, "image/webp");
+        let err = db
+            .lookup_by_path_async(dir.join("missing"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(all(feature = "rayon", feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_par_detect_files() {
+        let dir = std::env::temp_dir().join(format!("minimime-rayon-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut paths = Vec::new();
+        for i in 0..50 {
+            let (name, bytes): (_, &[u8]) = match i % 3 {
+                0 => ("png", b"\x89PNG\r\n\x1a\n"),
+                1 => ("pdf", b"%PDF-1.7\n"),
+                _ => ("png", b"%PDF-1.7\n"),
+            };
+            let path = dir.join(format!("{i}.{name}"));
+            std::fs::write(&path, bytes).unwrap();
+            paths.push(path);
+        }
+        paths.insert(7, dir.join("missing.png"));
+
+        let db = Db::new().unwrap();
+        let results = db.par_detect_files(&paths);
+        assert_eq!(results.len(), paths.len());
+        for (path, result) in paths.iter().zip(&results) {
+            let Ok(detection) = result else {
+                assert!(path.ends_with("missing.png"));
+                continue;
+            };
+            let expected = std::fs::read(path).unwrap();
+            let expected = db.lookup_by_magic(&expected).unwrap();
+            assert_eq!(
+                detection.info.as_ref(),
+                Some(expected),
+                "{}",
+                path.display()
+            );
+        }
+        assert_eq!(
+            results[7].as_ref().unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        assert!(results[2].as_ref().unwrap().mismatch);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_lookup_header_value() {
+        let db = Db::new().unwrap();
+        let html = db.lookup_by_content_type("text/html");
+        assert!(html.is_some());
+        assert_eq!(
+            db.lookup_by_content_type(" text/html ; charset=utf-8"),
+            html
+        );
+        assert_eq!(db.lookup_by_content_type("text/html;"), html);
+        assert_eq!(
+            db.lookup_by_content_type("text/html; charset=utf-8 x"),
+            html
+        );
+        assert_eq!(db.lookup_by_content_type("TEXT/Html; Charset=UTF-8"), html);
+        let mp2t = db.lookup_by_content_type("video/mp2t").unwrap();
+        assert_eq!(&*mp2t.content_type, "video/MP2T");
+        assert_eq!(db.lookup_by_content_type("video/MP2T"), Some(mp2t));
+        assert_eq!(db.lookup_by_content_type("text; charset=utf-8"), None);
+    }
+
+    #[test]
+    fn test_embedded_keys_are_borrowed() {
+        let db = Db::new().unwrap();
+        assert!(db.ext_db.keys().all(|key| matches!(key, Cow::Borrowed(_))));
+        for (key, info) in db.content_type_db.iter() {
+            let lowercase = !info.content_type.bytes().any(|b| b.is_ascii_uppercase());
+            assert_eq!(matches!(key, Cow::Borrowed(_)), lowercase, "{key}");
+        }
+    }
+
+    #[test]
+    fn test_load_extra() {
+        let mut db = Db::new().unwrap();
+        db.load_extra_from_reader(
+            "# first load\nrpt application/vnd.acme.report base64\npdf application/x-acme-pdf base64\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        db.load_extra_from_reader("rpt application/vnd.acme.report2 base64".as_bytes())
+            .unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("rpt").unwrap().content_type,
+            "application/vnd.acme.report2"
+        );
+        assert_eq!(
+            &*db.lookup_by_extension("pdf").unwrap().content_type,
+            "application/x-acme-pdf"
+        );
+
+        let before = db.clone();
+        let err = db
+            .load_extra_from_reader("svg image/x-acme-svg 8bit\nbroken line\n".as_bytes())
+            .unwrap_err();
+        assert!(matches!(err, Error::Parse { path: None, ref source } if source.line == 2));
+        assert_eq!(db.ext_db, before.ext_db);
+
+        let path = std::env::temp_dir().join(format!("minimime-extra-{}.db", std::process::id()));
+        std::fs::write(&path, "png image/x-acme-png base64\n").unwrap();
+        db.load_extra_from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("png").unwrap().content_type,
+            "image/x-acme-png"
+        );
+        let err = db.load_extra_from_path(&path).unwrap_err();
+        assert!(matches!(err, Error::Io { .. }), "{err}");
+        assert_eq!(err.path(), Some(path.as_path()));
+
+        std::fs::write(&path, "png\n").unwrap();
+        let err = db.load_extra_from_path(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, Error::Parse { .. }), "{err}");
+        assert!(err
+            .to_string()
+            .starts_with(&format!("{}: ", path.display())));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_builder() {
+        let dir = std::env::temp_dir().join(format!("minimime-builder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("overrides.db");
+        std::fs::write(&path, "pdf application/x-acme-pdf base64\n").unwrap();
+
+        let db = Db::builder()
+            .text("rpt application/vnd.acme.report base64")
+            .file(&path)
+            .build()
+            .unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("pdf").unwrap().content_type,
+            "application/x-acme-pdf"
+        );
+        assert!(db.lookup_by_extension("rpt").is_some());
+        assert!(db.lookup_by_extension("png").is_some());
+
+        let db = Db::builder()
+            .file(&path)
+            .precedence(MergeStrategy::PreferSelf)
+            .build()
+            .unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("pdf").unwrap().content_type,
+            "application/pdf"
+        );
+
+        let err = Db::builder()
+            .file(&path)
+            .precedence(MergeStrategy::ErrorOnConflict)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::Conflict(_)), "{err}");
+        assert_eq!(err.path(), None);
+        let err = Db::builder().text("pdf").build().unwrap_err();
+        assert!(matches!(err, Error::Parse { path: None, .. }), "{err}");
+        let missing = dir.join("missing.db");
+        let err = Db::builder().file(&missing).build().unwrap_err();
+        assert_eq!(err.path(), Some(missing.as_path()));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let db = Db::builder()
+            .embedded(false)
+            .entry(Info::new("rpt application/vnd.acme.report base64").unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(db.ext_db.len(), 1);
+        assert!(db.lookup_by_filename("q3.RPT").is_some());
+
+        let db = Db::builder()
+            .text("pdf application/x-acme-pdf base64\npdf application/x-acme-pdf2 base64")
+            .insertion_policy(InsertionPolicy::LastWins)
+            .build()
+            .unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("pdf").unwrap().content_type,
+            "application/x-acme-pdf2"
+        );
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_builder_case_sensitive() {
+        let db = Db::builder().case_sensitive(true).build().unwrap();
+        assert!(db.lookup_by_extension("pdf").is_some());
+        assert!(db.lookup_by_extension("PDF").is_none());
+        assert!(db.lookup_by_filename("REPORT.PDF").is_none());
+        assert!(db
+            .lookup_by_content_type("text/html; charset=utf-8")
+            .is_some());
+        assert!(db.lookup_by_content_type("Text/HTML").is_none());
+        assert!(db.lookup_by_content_type("video/MP2T").is_some());
+        assert!(db.lookup_by_content_type("video/mp2t").is_none());
+        assert!(db.clone().lookup_by_extension("PDF").is_none());
+
+        // Keys are lowercase, but entries are only found as registered.
+        let db = Db::builder()
+            .case_sensitive(true)
+            .text("RPT application/vnd.acme.report base64")
+            .build()
+            .unwrap();
+        assert!(db.lookup_by_extension("RPT").is_some());
+        assert!(db.lookup_by_extension("rpt").is_none());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_extension_keys_are_lowercase() {
+        let db = Db::parse("RPT application/vnd.acme.report base64\nÉTÉ text/x-ete 8bit").unwrap();
+        assert!(db.ext_db.keys().all(|key| *key == key.to_lowercase()));
+        for extension in ["rpt", "Rpt", "RPT", "été", "Été", "ÉTÉ"] {
+            assert!(db.lookup_by_extension(extension).is_some(), "{extension}");
+        }
+        // The Kelvin sign lowercases to an ASCII `k`.
+        let db = Db::new().unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("\u{212A}ML").unwrap().extension,
+            "kml"
+        );
+        let long = "X".repeat(normalize::MAX_FOLDED_LEN + 1);
+        assert!(db.lookup_by_extension(&long).is_none());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_register() {
+        let mut db = Db::new().unwrap();
+        let original = db.clone();
+        db.register_line("RPT application/vnd.acme.report base64 0 office")
+            .unwrap();
+        let info = db.lookup_by_extension("rpt").unwrap();
+        assert!(info.has_tag("office"));
+        assert_eq!(
+            db.lookup_by_content_type("application/vnd.acme.report"),
+            Some(info)
+        );
+        assert!(original.lookup_by_extension("rpt").is_none());
+
+        // An extra extension for a known type does not take it over.
+        db.register(Info::new("jpgx image/jpeg base64").unwrap());
+        assert_eq!(
+            &*db.lookup_by_extension("jpgx").unwrap().content_type,
+            "image/jpeg"
+        );
+        assert_eq!(
+            &*db.lookup_by_content_type("image/jpeg").unwrap().extension,
+            "jpeg"
+        );
+
+        // Re-registering an extension replaces it.
+        db.register_line("txt text/x-acme quoted-printable")
+            .unwrap();
+        assert_eq!(
+            &*db.lookup_by_extension("txt").unwrap().content_type,
+            "text/x-acme"
+        );
+
+        // Collisions follow the strategy.
+        let vendor = Info::new("csv application/vnd.acme.table+csv quoted-printable").unwrap();
+        let embedded = db.lookup_by_extension("csv").unwrap().clone();
+        assert_eq!(
+            db.register_with(vendor.clone(), MergeStrategy::ErrorOnConflict),
+            Err(MergeConflict {
+                existing: Box::new(embedded.clone()),
+                incoming: Box::new(vendor.clone()),
+            })
+        );
+        db.register_with(vendor.clone(), MergeStrategy::PreferSelf)
+            .unwrap();
+        assert_eq!(db.lookup_by_extension("csv"), Some(&embedded));
+        db.register_with(vendor.clone(), MergeStrategy::PreferOther)
+            .unwrap();
+        db.register_with(vendor.clone(), MergeStrategy::ErrorOnConflict)
+            .unwrap();
+        db.register_with(
+            Info::new("csvx text/csv 8bit").unwrap(),
+            MergeStrategy::ErrorOnConflict,
+        )
+        .unwrap();
+        let overrides: Vec<_> = db
+            .overrides()
+            .into_iter()
+            .map(|(embedded, current)| (embedded.extension, &*current.content_type))
+            .collect();
+        assert_eq!(
+            overrides,
+            [
+                ("csv".into(), "application/vnd.acme.table+csv"),
+                ("txt".into(), "text/x-acme"),
+            ]
+        );
+        assert!(original.overrides().is_empty());
+
+        for line in ["", "# comment", "rpt application/x-report", "a b c d e f"] {
+            assert_eq!(
+                db.register_line(line),
+                Err(ParseError {
+                    line: 1,
+                    content: line.to_string()
+                })
+            );
+        }
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_unregister_extension() {
+        let mut db = Db::new().unwrap();
+        assert_eq!(
+            db.unregister_extension(".jpeg").unwrap().extension.as_ref(),
+            "jpeg"
+        );
+        assert!(db.lookup_by_extension("jpeg").is_none());
+        // Another image/jpeg extension becomes the preferred one.
+        assert_eq!(
+            &*db.lookup_by_content_type("image/jpeg").unwrap().extension,
+            "jfif"
+        );
+        assert!(db.lookup_by_extension("jpg").is_some());
+
+        db.unregister_extension("wasm").unwrap();
+        assert!(db.lookup_by_content_type("application/wasm").is_none());
+        // Unregistering the non-preferred extension leaves the content type.
+        db.unregister_extension("jpg").unwrap();
+        assert_eq!(
+            &*db.lookup_by_content_type("image/jpeg").unwrap().extension,
+            "jfif"
+        );
+        assert_eq!(db.unregister_extension("frog"), None);
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_extensions_for() {
+        let db = Db::new().unwrap();
+        let extensions = |content_type| {
+            db.extensions_for(content_type)
+                .iter()
+                .map(|info| info.extension.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(extensions("image/jpeg"), ["jpeg", "jfif", "jpe", "jpg"]);
+        assert_eq!(extensions("IMAGE/JPEG"), ["jpeg", "jfif", "jpe", "jpg"]);
+        assert_eq!(extensions("application/pdf"), ["pdf", "ai"]);
+        assert!(extensions("application/x-unknown").is_empty());
+        for info in db.extensions_for("text/html") {
+            assert_eq!(&*info.content_type, "text/html");
+        }
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_suggest_filename() {
+        let db = Db::new().unwrap();
+        let suggest = |base, content_type| db.suggest_filename(base, content_type);
+        assert_eq!(suggest("photo", "image/jpeg"), "photo.jpeg");
+        assert_eq!(suggest("photo.jpg", "image/jpeg"), "photo.jpg");
+        assert_eq!(suggest("photo.jpg", "image/png"), "photo.jpg.png");
+        assert_eq!(
+            suggest("data", "application/json; charset=utf-8"),
+            "data.json"
+        );
+        assert_eq!(suggest("a\\b/c\nd", "text/plain"), "a_b_c_d.txt");
+        assert_eq!(suggest("notes. . ", "text/plain"), "notes.txt");
+        assert_eq!(suggest("", "application/pdf"), "file.pdf");
+        assert_eq!(suggest("..", "application/pdf"), "file.pdf");
+        assert_eq!(suggest("blob", "application/x-unknown"), "blob");
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_filename_rules() {
+        let mut db = Db::new().unwrap();
+        let blade = Info::new("php text/x-blade quoted-printable").unwrap();
+        let jinja = Info::new("j2 text/x-jinja quoted-printable").unwrap();
+        db.add_filename_rule("*.blade.php", blade.clone());
+        db.add_filename_rule("*.j2", jinja.clone());
+        db.add_filename_rule("CMakeLists.txt", jinja.clone());
+        db.add_filename_rule("CMakeLists.txt", blade.clone());
+
+        assert_eq!(
+            db.lookup_by_filename("views/welcome.blade.php"),
+            Some(&blade)
+        );
+        assert_eq!(db.lookup_by_filename("nginx.conf.J2"), Some(&jinja));
+        assert_eq!(db.lookup_by_filename("CMakeLists.txt"), Some(&blade));
+        assert_eq!(
+            &*db.lookup_by_filename("index.php").unwrap().extension,
+            "php"
+        );
+        assert_eq!(
+            &*db.lookup_by_filename("notes.txt").unwrap().content_type,
+            "text/plain"
+        );
+        assert_eq!(&*db.lookup_by_extension("php").unwrap().extension, "php");
+        assert_eq!(db.filename_rules.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_filename_rules() {
+        let rule = |line| {
+            let mut db = db_from_lines(&[]);
+            db.add_filename_rule("*.tpl", Info::new(line).unwrap());
+            db
+        };
+        let ours = rule("tpl text/x-smarty quoted-printable");
+        let theirs = || rule("tpl text/x-template quoted-printable");
+
+        let mut db = ours.clone();
+        db.merge(theirs(), MergeStrategy::PreferSelf).unwrap();
+        assert_eq!(
+            &*db.lookup_by_filename("a.tpl").unwrap().content_type,
+            "text/x-smarty"
+        );
+
+        let mut db = ours.clone();
+        db.merge(theirs(), MergeStrategy::PreferOther).unwrap();
+        assert_eq!(
+            &*db.lookup_by_filename("a.tpl").unwrap().content_type,
+            "text/x-template"
+        );
+
+        let mut db = ours.clone();
+        assert!(db.merge(theirs(), MergeStrategy::ErrorOnConflict).is_err());
+        assert_eq!(db.filename_rules, ours.filename_rules);
+    }
+
+    #[test]
+    fn test_clone_shares_tables() {
+        let db = db_from_lines(&["csv text/csv 8bit"]);
+        let mut clone = db.clone();
+        assert!(Arc::ptr_eq(&db.ext_db, &clone.ext_db));
+
+        let overlay = db_from_lines(&["rpt application/vnd.acme.report base64"]);
+        clone.merge(overlay, MergeStrategy::PreferSelf).unwrap();
+        assert!(clone.lookup_by_extension("rpt").is_some());
+        assert!(db.lookup_by_extension("rpt").is_none());
+    }
+
+    #[test]
+    fn test_db_is_send_sync() {
+        let db = Db::new().unwrap();
+        let shared = Arc::new(db.clone());
+        let handle = std::thread::spawn(move || shared.lookup_by_extension("pdf").cloned());
+        assert_eq!(
+            handle.join().unwrap().as_ref(),
+            db.lookup_by_extension("pdf")
+        );
+    }
+}
@@ -0,0 +1,476 @@
+//! Type detection from every signal an upload carries.
+//!
+//! An upload pipeline usually knows up to three things about a file: its
+//! name, its leading bytes and the `Content-Type` the client declared.
+//! [`Db::detect`] weighs them against each other and returns one
+//! [`Detection`]: the best entry, how far the signals back it up, and
+//! whether any of them contradicts it.
+//!
+//! Upload handlers that accept files by extension can also check that the
+//! content is what the extension claims with [`Db::verify`], which catches,
+//! say, a script uploaded as `avatar.png`.
+
+use crate::{
+    db::{combine, refines_container},
+    media_type::validate_content_type,
+    sniff, Db, Info,
+};
+
+/// How well the signals given to [`Db::detect`] back up its result.
+///
+/// Levels are ordered, so a pipeline can require a minimum with
+/// `detection.confidence >= Confidence::Medium`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Confidence {
+    /// Only the file name or only the declared type names the result, or
+    /// nothing was found at all
+    Low,
+    /// The content was sniffed but nothing else agrees with it, or the file
+    /// name and the declared type agree without the content to confirm them
+    Medium,
+    /// The content was sniffed and the file name or the declared type agrees
+    /// with it
+    High,
+}
+
+/// Which signal a [`Detection`] took its entry from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Source {
+    /// The magic bytes at the start of the content
+    Content,
+    /// The file name's extension or filename rule
+    Filename,
+    /// The `Content-Type` declared by the client
+    Declared,
+}
+
+/// The outcome of [`Db::detect`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Detection {
+    /// The best entry for the file, if any signal led to one
+    pub info: Option<Info>,
+    /// Which signal `info` came from; `info.origin` tells which database
+    pub source: Option<Source>,
+    /// How well the signals back up `info`
+    pub confidence: Confidence,
+    /// Whether a signal contradicts `info`, such as a `.png` name on a PDF.
+    /// A declared `application/octet-stream` makes no claim.
+    pub mismatch: bool,
+}
+
+/// The outcome of [`Db::verify`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum VerifyResult {
+    /// The content's signature agrees with the extension's type
+    Match(Info),
+    /// The content's signature is that of another type than the extension's
+    Mismatch {
+        /// Entry for the file name's extension
+        expected: Info,
+        /// Entry for the sniffed content
+        actual: Info,
+    },
+    /// The extension's type has a signature, but the content doesn't start
+    /// with it, such as a script named `.png`
+    MissingSignature {
+        /// Entry for the file name's extension
+        expected: Info,
+    },
+    /// Nothing to check against: the extension is unknown, or neither its
+    /// type nor the content has a signature, as with plain text
+    Unverifiable {
+        /// Entry for the sniffed content, if any
+        actual: Option<Info>,
+    },
+}
+
+impl VerifyResult {
+    /// Whether the content was confirmed to be what the extension says.
+    pub fn is_match(&self) -> bool {
+        matches!(self, VerifyResult::Match(_))
+    }
+
+    /// Whether the content contradicts the extension; upload handlers
+    /// usually reject these files.
+    pub fn is_suspicious(&self) -> bool {
+        matches!(
+            self,
+            VerifyResult::Mismatch { .. } | VerifyResult::MissingSignature { .. }
+        )
+    }
+}
+
+impl Db {
+    /// Detects the type of a file from its name, its leading bytes and the
+    /// content type declared for it, any of which may be missing.
+    ///
+    /// The sniffed content wins, except that a generic container (ZIP,
+    /// gzip, ...) defers to a file name that says what is inside, as in
+    /// [`Db::lookup_by_filename_and_magic`]. The declared type is only used
+    /// when neither of the others finds an entry, since clients make it up
+    /// freely; it still counts towards the confidence and the mismatch flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - Full filename or path
+    /// * `bytes` - The first bytes of the file; see
+    ///   [`PREFIX_LEN`](crate::sniff::PREFIX_LEN) for how many are useful
+    /// * `declared_type` - The `Content-Type` sent along with the file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{detection::Confidence, Db};
+    ///
+    /// let db = Db::new().unwrap();
+    /// let pdf = b"%PDF-1.7\n";
+    ///
+    /// let detection = db.detect(Some("report.pdf"), Some(pdf), Some("application/pdf"));
+    /// assert_eq!(&*detection.info.unwrap().content_type, "application/pdf");
+    /// assert_eq!(detection.confidence, Confidence::High);
+    /// assert!(!detection.mismatch);
+    ///
+    /// let detection = db.detect(Some("avatar.png"), Some(pdf), Some("image/png"));
+    /// assert_eq!(&*detection.info.unwrap().content_type, "application/pdf");
+    /// assert!(detection.mismatch);
+    /// ```
+    pub fn detect(
+        &self,
+        filename: Option<&str>,
+        bytes: Option<&[u8]>,
+        declared_type: Option<&str>,
+    ) -> Detection {
+        let by_filename = filename.and_then(|filename| self.lookup_by_filename(filename));
+        let sniffed = bytes.and_then(|bytes| self.lookup_by_magic(bytes));
+        self.detect_entries(by_filename, sniffed, declared_type)
+    }
+
+    /// Detects the type of a file as [`Db::detect`] does, from the entries
+    /// already looked up for its name and its leading bytes.
+    pub(crate) fn detect_entries(
+        &self,
+        by_filename: Option<&Info>,
+        sniffed: Option<&Info>,
+        declared_type: Option<&str>,
+    ) -> Detection {
+        let declared_type = declared_type.filter(|declared| {
+            !declared.trim().is_empty()
+                && !validate_content_type(declared, "application/octet-stream")
+        });
+
+        let (info, source) = match combine(by_filename, sniffed) {
+            Some(info) if sniffed == Some(info) => (info, Source::Content),
+            Some(info) => (info, Source::Filename),
+            None => {
+                match declared_type.and_then(|declared| self.lookup_by_content_type(declared)) {
+                    Some(info) => (info, Source::Declared),
+                    None => {
+                        return Detection {
+                            info: None,
+                            source: None,
+                            confidence: Confidence::Low,
+                            mismatch: false,
+                        }
+                    }
+                }
+            }
+        };
+
+        let filename_agrees = by_filename.map(|by_filename| {
+            by_filename.content_type == info.content_type || self.same_format(by_filename, info)
+        });
+        let declared_agrees =
+            declared_type.map(|declared| validate_content_type(declared, &info.content_type));
+        // A container the file name refines confirms the name's entry.
+        let content_agrees = sniffed.map(|sniffed| {
+            self.same_format(info, sniffed)
+                || by_filename.is_some_and(|by_filename| refines_container(by_filename, sniffed))
+        });
+        let mismatch = [filename_agrees, declared_agrees, content_agrees].contains(&Some(false));
+
+        let confidence = match source {
+            Source::Content if filename_agrees == Some(true) || declared_agrees == Some(true) => {
+                Confidence::High
+            }
+            Source::Filename if content_agrees == Some(true) => Confidence::High,
+            Source::Content => Confidence::Medium,
+            Source::Filename if declared_agrees == Some(true) => Confidence::Medium,
+            Source::Filename | Source::Declared => Confidence::Low,
+        };
+
+        Detection {
+            info: Some(info.clone()),
+            source: Some(source),
+            confidence,
+            mismatch,
+        }
+    }
+
+    /// Checks whether a file's content is what its extension says.
+    ///
+    /// The content's signature is sniffed from `bytes` and compared with the
+    /// type of the file name's extension. A generic container such as ZIP
+    /// satisfies an extension for an archive, document, executable or
+    /// office format without a signature of its own, such as `.docx` or
+    /// `.jar`. An extension whose type has a signature the content lacks is
+    /// reported too, since scripts and markup have no signature to
+    /// contradict it with.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - Full filename or path
+    /// * `bytes` - The first bytes of the file; see
+    ///   [`PREFIX_LEN`](crate::sniff::PREFIX_LEN) for how many are useful
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{detection::VerifyResult, Db};
+    ///
+    /// let db = Db::new().unwrap();
+    /// assert!(db.verify("photo.png", b"\x89PNG\r\n\x1a\n").is_match());
+    ///
+    /// let result = db.verify("avatar.png", b"<?php system($_GET['c']); ?>");
+    /// assert!(matches!(result, VerifyResult::MissingSignature { .. }));
+    /// assert!(result.is_suspicious());
+    ///
+    /// let result = db.verify("notes.txt", b"just some notes");
+    /// assert!(matches!(result, VerifyResult::Unverifiable { actual: None }));
+    /// ```
+    pub fn verify(&self, filename: &str, bytes: &[u8]) -> VerifyResult {
+        /// Tags of formats that may be built on a generic container.
+        const CONTAINED: &[&str] = &["archive", "document", "executable", "office"];
+
+        let by_filename = self.lookup_by_filename(filename);
+        let sniffed = self.lookup_by_magic(bytes);
+        let Some(expected) = by_filename else {
+            return VerifyResult::Unverifiable {
+                actual: sniffed.cloned(),
+            };
+        };
+        let signed = self.has_signature(expected);
+        match sniffed {
+            Some(actual) if self.same_format(expected, actual) => {
+                VerifyResult::Match(expected.clone())
+            }
+            Some(actual)
+                if !signed
+                    && refines_container(expected, actual)
+                    && CONTAINED.iter().any(|tag| expected.has_tag(tag)) =>
+            {
+                VerifyResult::Match(expected.clone())
+            }
+            Some(actual) => VerifyResult::Mismatch {
+                expected: expected.clone(),
+                actual: actual.clone(),
+            },
+            None if signed => VerifyResult::MissingSignature {
+                expected: expected.clone(),
+            },
+            None => VerifyResult::Unverifiable { actual: None },
+        }
+    }
+
+    /// Checks whether two entries are the same format: the same content
+    /// type, or an extension whose type differs from the one sniffing
+    /// reports for it, such as `webm`, which the extension table maps to
+    /// `audio/webm` and sniffing to `video/webm`.
+    fn same_format(&self, by_filename: &Info, sniffed: &Info) -> bool {
+        by_filename.content_type == sniffed.content_type
+            || self.preferred_extension(&sniffed.content_type) == Some(&*by_filename.extension)
+    }
+
+    /// Checks whether an entry's format has a signature sniffing knows,
+    /// under its own content type or, like `webm`, another one.
+    fn has_signature(&self, info: &Info) -> bool {
+        sniff::has_signature(&info.content_type)
+            || sniff::SIGNED
+                .iter()
+                .any(|signed| self.preferred_extension(signed) == Some(&*info.extension))
+    }
+}
+
+#[cfg(all(test, feature = "ext-db", feature = "content-type-db"))]
+mod tests {
+    use super::*;
+    use crate::Origin;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const ZIP: &[u8] = b"PK\x03\x04\x14\0\0\0";
+
+    fn content_type(detection: &Detection) -> Option<&str> {
+        detection.info.as_ref().map(|info| &*info.content_type)
+    }
+
+    #[test]
+    fn test_sources() {
+        let db = Db::new().unwrap();
+        let detection = db.detect(Some("a.txt"), Some(PNG), None);
+        assert_eq!(content_type(&detection), Some("image/png"));
+        assert_eq!(detection.source, Some(Source::Content));
+
+        let detection = db.detect(Some("a.docx"), Some(ZIP), Some("application/zip"));
+        assert_eq!(&*detection.info.as_ref().unwrap().extension, "docx");
+        assert_eq!(detection.source, Some(Source::Filename));
+        assert_eq!(detection.confidence, Confidence::High);
+        assert!(detection.mismatch, "the declared type names the container");
+
+        let detection = db.detect(None, Some(b"plain words"), Some("text/csv; charset=utf-8"));
+        assert_eq!(content_type(&detection), Some("text/csv"));
+        assert_eq!(detection.source, Some(Source::Declared));
+        assert_eq!(detection.confidence, Confidence::Low);
+
+        let detection = db.detect(Some("noext"), None, Some("application/x-unknown"));
+        assert_eq!(detection.info, None);
+        assert_eq!(detection.source, None);
+        assert!(!detection.mismatch);
+    }
+
+    #[test]
+    fn test_origin() {
+        let mut db = Db::new().unwrap();
+        assert_eq!(
+            db.lookup_by_extension("png").unwrap().origin,
+            Origin::Embedded
+        );
+        db.register_line("png image/x-custom-png base64").unwrap();
+        let detection = db.detect(Some("a.png"), None, None);
+        assert_eq!(content_type(&detection), Some("image/x-custom-png"));
+        assert_eq!(detection.info.unwrap().origin, Origin::Custom);
+        // The sniffed entry of the same type is still the embedded one.
+        let detection = db.detect(Some("a.png"), Some(PNG), None);
+        assert_eq!(detection.source, Some(Source::Content));
+        assert_eq!(detection.info.unwrap().origin, Origin::Embedded);
+        assert_eq!(
+            Info::new("x application/x-x base64").unwrap().origin,
+            Origin::Custom
+        );
+    }
+
+    #[test]
+    fn test_confidence() {
+        let db = Db::new().unwrap();
+        let confidence =
+            |filename, bytes, declared| db.detect(filename, bytes, declared).confidence;
+        assert_eq!(confidence(Some("a.png"), Some(PNG), None), Confidence::High);
+        assert_eq!(
+            confidence(None, Some(PNG), Some("Image/PNG")),
+            Confidence::High
+        );
+        assert_eq!(confidence(None, Some(PNG), None), Confidence::Medium);
+        assert_eq!(
+            confidence(Some("a.gif"), Some(PNG), None),
+            Confidence::Medium
+        );
+        assert_eq!(
+            confidence(Some("a.png"), None, Some("image/png")),
+            Confidence::Medium
+        );
+        assert_eq!(confidence(Some("a.png"), None, None), Confidence::Low);
+        assert_eq!(
+            confidence(Some("a.png"), Some(b"text"), None),
+            Confidence::Low
+        );
+        assert!(Confidence::High > Confidence::Medium);
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let db = Db::new().unwrap();
+        let mismatch = |filename, bytes, declared| db.detect(filename, bytes, declared).mismatch;
+        assert!(!mismatch(Some("a.png"), Some(PNG), Some("image/png")));
+        assert!(!mismatch(
+            Some("a.png"),
+            Some(PNG),
+            Some("application/octet-stream")
+        ));
+        assert!(!mismatch(Some("a.tar.gz"), Some(b"\x1f\x8b\x08\0"), None));
+        assert!(mismatch(Some("a.php"), Some(PNG), None));
+        assert!(mismatch(None, Some(PNG), Some("text/html")));
+        assert!(mismatch(Some("a.png"), None, Some("text/html")));
+        // The extension table maps `webm` to `audio/webm`.
+        assert!(!mismatch(
+            Some("a.webm"),
+            Some(b"\x1A\x45\xDF\xA3\x9F\x42\x82\x84webm"),
+            None
+        ));
+    }
+
+    #[test]
+    fn test_prose_starting_with_from() {
+        let db = Db::new().unwrap();
+        let memo = b"From the desk of the CEO\nDear all,\nthe office is closed on Friday.\n";
+        let detection = db.detect(Some("notes.txt"), Some(memo), None);
+        assert_eq!(content_type(&detection), Some("text/plain"));
+        assert!(!detection.mismatch);
+        assert_eq!(
+            db.lookup_by_filename_and_magic("notes.txt", memo)
+                .map(|info| &*info.content_type),
+            Some("text/plain")
+        );
+        assert!(!db.verify("notes.txt", memo).is_suspicious());
+    }
+
+    #[test]
+    fn test_verify() {
+        let db = Db::new().unwrap();
+        let webm = b"\x1A\x45\xDF\xA3\x9F\x42\x82\x84webm";
+        assert_eq!(
+            db.verify("a.PNG", PNG),
+            VerifyResult::Match(db.lookup_by_extension("png").unwrap().clone())
+        );
+        assert!(db.verify("a.webm", webm).is_match());
+        assert!(db.verify("a.docx", ZIP).is_match());
+        assert!(db.verify("a.tar.gz", b"\x1f\x8b\x08\0").is_match());
+
+        let result = db.verify("a.jpg", PNG);
+        assert!(result.is_suspicious());
+        match result {
+            VerifyResult::Mismatch { expected, actual } => {
+                assert_eq!(&*expected.content_type, "image/jpeg");
+                assert_eq!(&*actual.content_type, "image/png");
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(
+            db.verify("a.png", ZIP).is_suspicious(),
+            "PNG has a signature of its own"
+        );
+        assert!(
+            db.verify("a.php", ZIP).is_suspicious(),
+            "scripts are not containers"
+        );
+        assert!(db.verify("a.txt", PNG).is_suspicious());
+        assert!(matches!(
+            db.verify("a.webm", b"<?php"),
+            VerifyResult::MissingSignature { .. }
+        ));
+
+        let result = db.verify("a.unknown-ext", PNG);
+        assert!(matches!(
+            result,
+            VerifyResult::Unverifiable { actual: Some(_) }
+        ));
+        assert!(!result.is_match() && !result.is_suspicious());
+        assert_eq!(
+            db.verify("a.csv", b"a,b\n"),
+            VerifyResult::Unverifiable { actual: None }
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! `Content-Disposition` headers for file downloads.
+//!
+//! A download endpoint names the file with a `Content-Disposition` header.
+//! Non-ASCII names need the `filename*` parameter of RFC 6266 with RFC 5987
+//! encoding, plus a plain `filename` fallback for old clients, and quotes
+//! or path separators in a name must not leak into the header. [`attachment`]
+//! and [`inline`] build the value; [`download_headers`] pairs it with the
+//! `Content-Type` looked up for the name.
+
+use std::fmt::Write;
+
+use crate::Db;
+
+/// `Content-Type` and `Content-Disposition` values for a download, built by
+/// [`download_headers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadHeaders {
+    /// The type looked up for the file name, or `application/octet-stream`
+    pub content_type: String,
+    /// An `attachment` disposition naming the file
+    pub content_disposition: String,
+}
+
+/// Builds a `Content-Disposition` value that makes clients save the
+/// response under `filename`.
+///
+/// Only the last path component of `filename` is used. The `filename`
+/// parameter is a quoted ASCII fallback, with other characters replaced by
+/// `_`; names that need it also get a `filename*` parameter with the full
+/// name in UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::disposition;
+///
+/// assert_eq!(
+///     disposition::attachment("report.pdf"),
+///     "attachment; filename=\"report.pdf\""
+/// );
+/// assert_eq!(
+///     disposition::attachment("../Résumé 2026.pdf"),
+///     "attachment; filename=\"R_sum_ 2026.pdf\"; filename*=UTF-8''R%C3%A9sum%C3%A9%202026.pdf"
+/// );
+/// ```
+pub fn attachment(filename: &str) -> String {
+    build("attachment", filename)
+}
+
+/// Builds a `Content-Disposition` value that lets clients display the
+/// response, naming it `filename` should it be saved.
+///
+/// The name is encoded as for [`attachment`].
+pub fn inline(filename: &str) -> String {
+    build("inline", filename)
+}
+
+/// Builds the `Content-Type` and `Content-Disposition` values for serving
+/// a file as a download.
+///
+/// The content type is looked up from the file name; unknown names are
+/// served as `application/octet-stream`.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{disposition, Db};
+///
+/// let db = Db::new().unwrap();
+/// let headers = disposition::download_headers(&db, "export.csv");
+/// assert_eq!(headers.content_type, "text/csv");
+/// assert_eq!(headers.content_disposition, "attachment; filename=\"export.csv\"");
+/// ```
+pub fn download_headers(db: &Db, filename: &str) -> DownloadHeaders {
+    DownloadHeaders {
+        content_type: db
+            .lookup_by_filename(base_name(filename))
+            .map_or("application/octet-stream", |info| &info.content_type)
+            .to_string(),
+        content_disposition: attachment(filename),
+    }
+}
+
+/// Returns the last path component, splitting at `/` and `\`.
+fn base_name(filename: &str) -> &str {
+    filename.rsplit(['/', '\\']).next().unwrap_or_default()
+}
+
+fn build(kind: &str, filename: &str) -> String {
+    let name = base_name(filename);
+    let mut value = format!("{kind}; filename=\"");
+    let mut plain = true;
+    for c in name.chars() {
+        match c {
+            // Backslashes were path separators, so only quotes need escaping.
+            '"' => value.push_str("\\\""),
+            ' '..='~' => value.push(c),
+            _ => {
+                value.push('_');
+                plain = false;
+            }
+        }
+    }
+    value.push('"');
+    if !plain {
+        value.push_str("; filename*=UTF-8''");
+        for &byte in name.as_bytes() {
+            // `attr-char` of RFC 5987.
+            if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                value.push(byte as char);
+            } else {
+                let _ = write!(value, "%{byte:02X}");
+            }
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_escapes() {
+        assert_eq!(
+            attachment("say \"hi\".txt"),
+            "attachment; filename=\"say \\\"hi\\\".txt\""
+        );
+        assert_eq!(inline("C:\\temp\\a.png"), "inline; filename=\"a.png\"");
+        assert_eq!(
+            attachment("line\nbreak.txt"),
+            "attachment; filename=\"line_break.txt\"; filename*=UTF-8''line%0Abreak.txt"
+        );
+        assert_eq!(
+            attachment("报告.pdf"),
+            "attachment; filename=\"__.pdf\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A.pdf"
+        );
+        assert_eq!(attachment("dir/"), "attachment; filename=\"\"");
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_download_headers() {
+        let db = Db::new().unwrap();
+        let headers = download_headers(&db, "backups/site.tar.gz");
+        assert_eq!(headers.content_type, "application/x-gtar");
+        assert_eq!(
+            headers.content_disposition,
+            "attachment; filename=\"site.tar.gz\""
+        );
+        assert_eq!(
+            download_headers(&db, "blob").content_type,
+            "application/octet-stream"
+        );
+    }
+}
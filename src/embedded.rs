@@ -0,0 +1,119 @@
+//! Heap-free lookups for embedded and `no_std` targets.
+//!
+//! Enabled by the `embedded` feature. Lookups binary-search the sorted
+//! [`BY_EXTENSION`] and [`BY_CONTENT_TYPE`] tables, so no `HashMap` is built
+//! and nothing is allocated, ever: case folding is done byte by byte during
+//! comparison. Combined with `default-features = false` the
+//! crate builds as `#![no_std]` without `alloc`, which suits
+//! microcontrollers and allocator-free environments. Enable the `ext-db`
+//! and `content-type-db` features for the tables you need; a firmware that
+//! only maps extensions to content types can leave out the other.
+//!
+//! # Examples
+//!
+//! ```
+//! use minimime::embedded;
+//!
+//! let entry = embedded::lookup_by_filename("photo.JPG").unwrap();
+//! assert_eq!(entry.content_type, "image/jpeg");
+//! assert_eq!(embedded::lookup_by_content_type("image/jpeg").unwrap().extension, "jpeg");
+//! ```
+
+use crate::{
+    sniff,
+    tables::{self, Entry},
+};
+
+pub use crate::tables::{BY_CONTENT_TYPE, BY_EXTENSION};
+
+/// Looks up an entry by file extension.
+///
+/// The lookup is ASCII case-insensitive.
+///
+/// # Returns
+///
+/// * `Some(&Entry)` if the extension is found
+/// * `None` if the extension is not recognized
+pub const fn lookup_by_extension(extension: &str) -> Option<&'static Entry> {
+    tables::find_by_extension(extension)
+}
+
+/// Looks up the preferred entry for a content type.
+///
+/// The lookup is ASCII case-insensitive.
+///
+/// # Returns
+///
+/// * `Some(&Entry)` if the content type is found
+/// * `None` if the content type is not recognized
+pub const fn lookup_by_content_type(content_type: &str) -> Option<&'static Entry> {
+    tables::find_by_content_type(content_type)
+}
+
+/// Looks up an entry by filename or path.
+///
+/// Both `/` and `\` are treated as path separators. A leading dot starts a
+/// hidden file name rather than an extension, as with [`std::path::Path`].
+/// A known compound extension such as `tar.gz` wins over the last segment
+/// alone.
+///
+/// # Returns
+///
+/// * `Some(&Entry)` if the file extension is recognized
+/// * `None` if the file has no extension or the extension is not recognized
+pub fn lookup_by_filename(filename: &str) -> Option<&'static Entry> {
+    let name = filename.rsplit(['/', '\\']).next()?;
+    let dot = name.rfind('.').filter(|&dot| dot > 0)?;
+    let compound = match name[..dot].rfind('.') {
+        Some(inner) if inner > 0 => lookup_by_extension(&name[inner + 1..]),
+        _ => None,
+    };
+    compound.or_else(|| lookup_by_extension(&name[dot + 1..]))
+}
+
+/// Looks up an entry by the magic bytes at the start of a file.
+///
+/// See the [`sniff`] module for the recognized signatures.
+pub fn lookup_by_magic(bytes: &[u8]) -> Option<&'static Entry> {
+    sniff::content_type(bytes).and_then(lookup_by_content_type)
+}
+
+#[cfg(all(test, feature = "ext-db", feature = "content-type-db"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookups() {
+        assert_eq!(
+            lookup_by_extension("pdf").unwrap().content_type,
+            "application/pdf"
+        );
+        assert_eq!(
+            lookup_by_extension("ZiP").unwrap().content_type,
+            "application/zip"
+        );
+        assert!(lookup_by_extension("frog").is_none());
+        assert!(lookup_by_extension("").is_none());
+
+        assert_eq!(
+            lookup_by_content_type("text/plain").unwrap().extension,
+            "txt"
+        );
+        assert!(lookup_by_content_type("something-fake").is_none());
+
+        assert_eq!(
+            lookup_by_filename("a/b/c.tar.GZ").unwrap().extension,
+            "tar.gz"
+        );
+        assert_eq!(lookup_by_filename("a/b/c.min.js").unwrap().extension, "js");
+        assert_eq!(
+            lookup_by_filename("C:\\docs\\a.csv").unwrap().extension,
+            "csv"
+        );
+        assert!(lookup_by_filename(".pdf").is_none());
+        assert!(lookup_by_filename("dir.pdf/readme").is_none());
+        assert!(lookup_by_filename("a.").is_none());
+
+        assert_eq!(lookup_by_magic(b"WEBVTT\n").unwrap().extension, "vtt");
+    }
+}
@@ -0,0 +1,158 @@
+//! The transfer encoding column of database entries.
+
+use std::{convert::Infallible, fmt, ops::Deref, str::FromStr};
+
+/// A `Content-Transfer-Encoding` (RFC 2045): the encoding column of a
+/// database entry, [`Info::encoding`](crate::Info::encoding).
+///
+/// It converts from and to strings, and derefs to its token, so code
+/// written against the former string field, such as
+/// `&*info.encoding == "base64"`, keeps working. With the `serde` feature
+/// it serializes as its token.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "String", into = "String")
+)]
+pub enum TransferEncoding {
+    /// Short lines of US-ASCII, sent as they are
+    SevenBit,
+    /// Short lines of any bytes but NUL, sent as they are
+    EightBit,
+    /// Mostly ASCII text, with other bytes escaped as `=XX`
+    QuotedPrintable,
+    /// Binary data, in base64 lines
+    Base64,
+    /// Any other token, as written in the database
+    Other(String),
+}
+
+impl TransferEncoding {
+    /// The known encodings.
+    const KNOWN: [TransferEncoding; 4] = [
+        TransferEncoding::SevenBit,
+        TransferEncoding::EightBit,
+        TransferEncoding::QuotedPrintable,
+        TransferEncoding::Base64,
+    ];
+
+    /// Parses a `Content-Transfer-Encoding` token, ignoring ASCII case.
+    ///
+    /// Returns `None` for tokens other than the four RFC 2045 ones; use
+    /// [`TransferEncoding::from`] to keep those as
+    /// [`TransferEncoding::Other`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::TransferEncoding;
+    ///
+    /// assert_eq!(TransferEncoding::parse("Base64"), Some(TransferEncoding::Base64));
+    /// assert_eq!(TransferEncoding::parse("x-uuencode"), None);
+    /// ```
+    pub fn parse(token: &str) -> Option<Self> {
+        let token = token.trim();
+        Self::KNOWN
+            .into_iter()
+            .find(|encoding| encoding.as_str().eq_ignore_ascii_case(token))
+    }
+
+    /// Returns the token for a `Content-Transfer-Encoding` header.
+    pub fn as_str(&self) -> &str {
+        match self {
+            TransferEncoding::SevenBit => "7bit",
+            TransferEncoding::EightBit => "8bit",
+            TransferEncoding::QuotedPrintable => "quoted-printable",
+            TransferEncoding::Base64 => "base64",
+            TransferEncoding::Other(token) => token,
+        }
+    }
+
+    /// Checks whether entries with this encoding are binary: `base64` and
+    /// `8bit` are, anything else is text.
+    pub fn is_binary(&self) -> bool {
+        matches!(self, TransferEncoding::Base64 | TransferEncoding::EightBit)
+    }
+}
+
+impl fmt::Display for TransferEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Deref for TransferEncoding {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for TransferEncoding {
+    /// Parses a token as [`TransferEncoding::parse`] does, keeping an
+    /// unknown one as [`TransferEncoding::Other`].
+    fn from(token: &str) -> Self {
+        Self::parse(token).unwrap_or_else(|| TransferEncoding::Other(token.to_string()))
+    }
+}
+
+impl From<String> for TransferEncoding {
+    fn from(token: String) -> Self {
+        Self::parse(&token).unwrap_or(TransferEncoding::Other(token))
+    }
+}
+
+impl From<TransferEncoding> for String {
+    fn from(encoding: TransferEncoding) -> Self {
+        match encoding {
+            TransferEncoding::Other(token) => token,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl FromStr for TransferEncoding {
+    type Err = Infallible;
+
+    fn from_str(token: &str) -> Result<Self, Infallible> {
+        Ok(token.into())
+    }
+}
+
+impl PartialEq<str> for TransferEncoding {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for TransferEncoding {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        for encoding in TransferEncoding::KNOWN {
+            assert_eq!(TransferEncoding::from(encoding.as_str()), encoding);
+            assert_eq!(String::from(encoding.clone()), encoding.to_string());
+        }
+        assert_eq!(
+            TransferEncoding::from(" Quoted-Printable "),
+            TransferEncoding::QuotedPrintable
+        );
+        let other = TransferEncoding::from("x-uuencode");
+        assert_eq!(other, TransferEncoding::Other("x-uuencode".to_string()));
+        assert_eq!(other, "x-uuencode");
+        assert_eq!(&*other, "x-uuencode");
+        assert!(!other.is_binary());
+        assert!(TransferEncoding::EightBit.is_binary());
+        assert_eq!("7bit".parse(), Ok(TransferEncoding::SevenBit));
+    }
+}
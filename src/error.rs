@@ -0,0 +1,100 @@
+//! The error type of database construction and loading.
+
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{MergeConflict, ParseError};
+
+/// Error returned when building or loading a [`Db`](crate::Db): by
+/// [`Db::new`](crate::Db::new), [`Db::from_file`](crate::Db::from_file),
+/// the `load_extra_*` methods and [`DbBuilder::build`](crate::DbBuilder::build).
+///
+/// Errors that come from a file carry its path, so the message names it.
+/// The message also includes the cause, which is not repeated by
+/// [`source`](std::error::Error::source); the variant's fields hold it.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{Db, Error};
+///
+/// let err = Db::builder().text("rpt").build().unwrap_err();
+/// assert!(matches!(err, Error::Parse { path: None, ref source } if source.line == 1));
+///
+/// let err = Db::from_file("missing.db").unwrap_err();
+/// assert!(err.to_string().starts_with("missing.db: "));
+/// assert!(std::error::Error::source(&err).is_none());
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    /// A database file could not be read
+    Io {
+        /// The file, if the data came from one
+        path: Option<PathBuf>,
+        /// Why reading failed
+        source: io::Error,
+    },
+    /// A database line is malformed
+    Parse {
+        /// The file, if the data came from one
+        path: Option<PathBuf>,
+        /// The line number and content of the malformed line
+        source: ParseError,
+    },
+    /// An entry conflicts with an existing one under
+    /// [`MergeStrategy::ErrorOnConflict`](crate::MergeStrategy::ErrorOnConflict)
+    Conflict(MergeConflict),
+}
+
+impl Error {
+    /// Returns the file the error comes from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Io { path, .. } | Error::Parse { path, .. } => path.as_deref(),
+            Error::Conflict(_) => None,
+        }
+    }
+
+    /// Attributes an error from reading data to the file it was read from.
+    pub(crate) fn in_file(mut self, file: &Path) -> Self {
+        if let Error::Io { path, .. } | Error::Parse { path, .. } = &mut self {
+            path.get_or_insert_with(|| file.to_path_buf());
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = self.path() {
+            write!(f, "{}: ", path.display())?;
+        }
+        match self {
+            Error::Io { source, .. } => source.fmt(f),
+            Error::Parse { source, .. } => source.fmt(f),
+            Error::Conflict(conflict) => conflict.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Error::Io { path: None, source }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(source: ParseError) -> Self {
+        Error::Parse { path: None, source }
+    }
+}
+
+impl From<MergeConflict> for Error {
+    fn from(conflict: MergeConflict) -> Self {
+        Error::Conflict(conflict)
+    }
+}
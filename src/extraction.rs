@@ -0,0 +1,139 @@
+//! Routing hints for search indexers.
+//!
+//! An indexer handles a file in one of three ways: feed its bytes to the
+//! tokenizer as they are, run a text extractor over it first, or skip it.
+//! [`ExtractionHint`] says which one an entry calls for. Entries tagged
+//! `document` or `office` in the database need extraction; textual content
+//! types (see [`is_text`]) are indexable as they are.
+
+/// How a search indexer should treat a file.
+///
+/// With the `serde` feature it serializes as `"text"`, `"extract"` or
+/// `"opaque"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ExtractionHint {
+    /// Directly indexable text: plain text, markup, source code, JSON, XML
+    Text,
+    /// Text locked in a container format that needs an extractor first,
+    /// such as PDF, EPUB or Office documents
+    Extract,
+    /// Binary content with no text to index, such as images or archives
+    Opaque,
+}
+
+impl ExtractionHint {
+    /// Checks whether the content is indexable text as it is.
+    pub const fn is_indexable_text(self) -> bool {
+        matches!(self, ExtractionHint::Text)
+    }
+}
+
+/// Textual `application/*` types; a trailing `*` matches any rest.
+const TEXT_APPLICATION_TYPES: &[&str] = &[
+    "application/ecmascript",
+    "application/javascript",
+    "application/json",
+    "application/ld+json",
+    "application/sql",
+    "application/toml",
+    "application/x-httpd-php",
+    "application/x-javascript",
+    "application/x-latex",
+    "application/x-sh",
+    "application/x-tex",
+    "application/x-yaml",
+    "application/xml",
+    "application/xml-*",
+    "application/yaml",
+];
+
+/// Checks whether a content type denotes text that can be indexed as it is.
+///
+/// That is any `text/*` or `message/*` type, a structured syntax suffix of
+/// `+json` or `+xml` (so `image/svg+xml` counts), and well-known textual
+/// `application/*` types such as JSON, XML, JavaScript and YAML. Parameters
+/// are ignored and the comparison is ASCII case-insensitive.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::extraction;
+///
+/// assert!(extraction::is_text("text/markdown; charset=utf-8"));
+/// assert!(extraction::is_text("application/vnd.api+json"));
+/// assert!(!extraction::is_text("application/pdf"));
+/// ```
+pub fn is_text(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    let Some((ty, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    ty.eq_ignore_ascii_case("text")
+        || ty.eq_ignore_ascii_case("message")
+        || subtype.rsplit_once('+').is_some_and(|(_, suffix)| {
+            suffix.eq_ignore_ascii_case("json") || suffix.eq_ignore_ascii_case("xml")
+        })
+        || TEXT_APPLICATION_TYPES
+            .iter()
+            .any(|pattern| crate::media_type::matches_prefix_pattern(pattern, essence))
+}
+
+/// Classifies an entry from its content type and whether it is tagged as a
+/// `document` or `office` file.
+pub(crate) fn hint(content_type: &str, needs_extraction: bool) -> ExtractionHint {
+    if is_text(content_type) {
+        ExtractionHint::Text
+    } else if needs_extraction {
+        ExtractionHint::Extract
+    } else {
+        ExtractionHint::Opaque
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_text() {
+        for content_type in [
+            "text/plain",
+            "TEXT/HTML",
+            "text/csv; header=present",
+            "message/rfc822",
+            "application/json",
+            "application/manifest+json",
+            "application/xhtml+xml",
+            "image/svg+xml",
+            "application/javascript",
+            "application/xml-dtd",
+        ] {
+            assert!(is_text(content_type), "{content_type}");
+        }
+        for content_type in [
+            "",
+            "text",
+            "application/pdf",
+            "application/epub+zip",
+            "application/octet-stream",
+            "image/png",
+            "application/jsonx",
+        ] {
+            assert!(!is_text(content_type), "{content_type}");
+        }
+    }
+
+    #[test]
+    fn test_hint() {
+        assert_eq!(hint("text/html", false), ExtractionHint::Text);
+        assert_eq!(hint("application/pdf", true), ExtractionHint::Extract);
+        assert_eq!(hint("image/png", false), ExtractionHint::Opaque);
+        assert!(ExtractionHint::Text.is_indexable_text());
+        assert!(!ExtractionHint::Extract.is_indexable_text());
+    }
+}
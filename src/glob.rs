@@ -0,0 +1,75 @@
+//! Minimal glob matching for filename rules.
+
+/// Matches a file name against a glob pattern, ignoring ASCII case.
+///
+/// `*` matches any run of characters and `?` matches exactly one; every
+/// other character matches itself. Patterns apply to a file name, not a
+/// path, so callers strip directories first.
+pub(crate) fn matches(pattern: &str, name: &str) -> bool {
+    // Most patterns end in a literal such as `.blade.php`; checking it first
+    // rejects nearly every rule without walking the pattern.
+    let literal = &pattern[pattern.rfind(['*', '?']).map_or(0, |i| i + 1)..];
+    let ends_with_literal = name
+        .len()
+        .checked_sub(literal.len())
+        .is_some_and(|start| name.as_bytes()[start..].eq_ignore_ascii_case(literal.as_bytes()));
+    if !ends_with_literal {
+        return false;
+    }
+
+    // Byte offsets into both strings, stepped a character at a time.
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it was tried at.
+    let mut backtrack = None;
+    while let Some(c) = name[n..].chars().next() {
+        match pattern[p..].chars().next() {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, n));
+            }
+            Some(pc) if pc == '?' || pc.eq_ignore_ascii_case(&c) => {
+                p += pc.len_utf8();
+                n += c.len_utf8();
+            }
+            _ => match backtrack {
+                // Let the last `*` absorb one more character and retry.
+                Some((star_p, star_n)) => {
+                    let absorbed = name[star_n..].chars().next().map_or(1, char::len_utf8);
+                    p = star_p;
+                    n = star_n + absorbed;
+                    backtrack = Some((star_p, n));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].bytes().all(|b| b == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*.blade.php", "welcome.blade.php"));
+        assert!(matches("*.blade.php", "Welcome.BLADE.php"));
+        assert!(!matches("*.blade.php", "welcome.php"));
+        assert!(matches("CMakeLists.txt", "CMakeLists.txt"));
+        assert!(!matches("CMakeLists.txt", "MyCMakeLists.txt"));
+        assert!(matches("*.conf.j2", "nginx.conf.j2"));
+        assert!(matches("Dockerfile*", "Dockerfile"));
+        assert!(matches("Dockerfile*", "Dockerfile.dev"));
+        assert!(matches("?akefile", "Makefile"));
+        assert!(!matches("?akefile", "akefile"));
+        assert!(matches("*a*b*c", "xxaxxbxxbxxc"));
+        assert!(!matches("*a*b*c", "xxaxxbxxbxx"));
+        assert!(matches("*", ""));
+        assert!(!matches("", "a"));
+        assert!(matches("*.ünï", "fïle.ünï"));
+        assert!(matches("f?le.*", "fïle.txt"));
+        assert!(matches("*?", "ï"));
+        assert!(!matches("*.txt", "ï"));
+        assert!(!matches("*.conf.j2", "j2"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Freedesktop icon names for content types.
+//!
+//! Desktop icon themes name their MIME type icons after the shared MIME-info
+//! database: a specific icon such as `application-pdf`, and a generic icon
+//! from the [Icon Naming Specification] such as `image-x-generic` to fall
+//! back on when the theme has no specific one. Look up [`icon_name`] first,
+//! then [`generic_icon_name`].
+//!
+//! [Icon Naming Specification]: https://specifications.freedesktop.org/icon-naming-spec/latest/
+
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// Generic icons for content type patterns, checked in order; a trailing
+/// `*` matches any rest.
+const GENERIC_ICONS: &[(&str, &str)] = &[
+    ("application/x-msdownload", "application-x-executable"),
+    (
+        "application/x-ms-dos-executable",
+        "application-x-executable",
+    ),
+    ("application/x-msdos-program", "application-x-executable"),
+    ("application/x-executable", "application-x-executable"),
+    ("application/x-sharedlib", "application-x-executable"),
+    (
+        "application/vnd.microsoft.portable-executable",
+        "application-x-executable",
+    ),
+    (
+        "application/vnd.android.package-archive",
+        "application-x-executable",
+    ),
+    ("application/java-archive", "application-x-executable"),
+    ("application/x-sh", "text-x-script"),
+    ("application/javascript", "text-x-script"),
+    ("text/javascript", "text-x-script"),
+    ("text/x-python", "text-x-script"),
+    ("text/x-sh", "text-x-script"),
+    ("text/html", "text-html"),
+    ("application/xhtml+xml", "text-html"),
+    ("application/zip", "package-x-generic"),
+    ("application/gzip", "package-x-generic"),
+    ("application/zstd", "package-x-generic"),
+    ("application/x-tar", "package-x-generic"),
+    ("application/x-gtar", "package-x-generic"),
+    ("application/x-bzip2", "package-x-generic"),
+    ("application/x-xz", "package-x-generic"),
+    ("application/x-7z-compressed", "package-x-generic"),
+    ("application/x-rar-compressed", "package-x-generic"),
+    ("application/vnd.rar", "package-x-generic"),
+    ("application/x-debian-package", "package-x-generic"),
+    ("application/x-rpm", "package-x-generic"),
+    ("application/msword", "x-office-document"),
+    ("application/rtf", "x-office-document"),
+    ("application/vnd.ms-word*", "x-office-document"),
+    (
+        "application/vnd.openxmlformats-officedocument.wordprocessingml*",
+        "x-office-document",
+    ),
+    (
+        "application/vnd.oasis.opendocument.text*",
+        "x-office-document",
+    ),
+    ("application/x-iwork-pages*", "x-office-document"),
+    ("application/vnd.ms-excel*", "x-office-spreadsheet"),
+    (
+        "application/vnd.openxmlformats-officedocument.spreadsheetml*",
+        "x-office-spreadsheet",
+    ),
+    (
+        "application/vnd.oasis.opendocument.spreadsheet*",
+        "x-office-spreadsheet",
+    ),
+    ("application/x-iwork-numbers*", "x-office-spreadsheet"),
+    ("text/csv", "x-office-spreadsheet"),
+    ("application/vnd.ms-powerpoint*", "x-office-presentation"),
+    (
+        "application/vnd.openxmlformats-officedocument.presentationml*",
+        "x-office-presentation",
+    ),
+    (
+        "application/vnd.oasis.opendocument.presentation*",
+        "x-office-presentation",
+    ),
+    ("application/x-iwork-keynote*", "x-office-presentation"),
+    (
+        "application/vnd.oasis.opendocument.graphics*",
+        "x-office-drawing",
+    ),
+    ("text/calendar", "x-office-calendar"),
+    ("text/vcard", "x-office-address-book"),
+    ("text/x-vcard", "x-office-address-book"),
+    ("application/font-*", "font-x-generic"),
+    ("application/x-font-*", "font-x-generic"),
+    ("application/vnd.ms-fontobject", "font-x-generic"),
+    ("font/*", "font-x-generic"),
+    ("image/*", "image-x-generic"),
+    ("audio/*", "audio-x-generic"),
+    ("video/*", "video-x-generic"),
+    ("text/*", "text-x-generic"),
+];
+
+/// Fallback for content types no rule covers.
+const DEFAULT_GENERIC_ICON: &str = "application-x-generic";
+
+/// Returns the specific icon name for a content type.
+///
+/// This is the content type in lowercase with the `/` replaced by `-`, as
+/// icon themes name their MIME type icons.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::icon;
+///
+/// assert_eq!(icon::icon_name("application/pdf"), "application-pdf");
+/// assert_eq!(icon::icon_name("video/MP2T"), "video-mp2t");
+/// ```
+#[cfg(feature = "std")]
+pub fn icon_name(content_type: &str) -> String {
+    content_type.to_ascii_lowercase().replacen('/', "-", 1)
+}
+
+/// Returns the generic icon name for a content type.
+///
+/// Generic icons come from the Icon Naming Specification:
+/// `application-x-executable`, `package-x-generic` for archives,
+/// `x-office-document` and its spreadsheet, presentation and drawing
+/// siblings, `text-html`, `text-x-script`, `font-x-generic`, and
+/// `image-`, `audio-`, `video-` and `text-x-generic` by top-level type.
+/// Anything else is `application-x-generic`.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::icon;
+///
+/// assert_eq!(icon::generic_icon_name("image/png"), "image-x-generic");
+/// assert_eq!(icon::generic_icon_name("application/zip"), "package-x-generic");
+/// assert_eq!(icon::generic_icon_name("application/x-unknown"), "application-x-generic");
+/// ```
+pub fn generic_icon_name(content_type: &str) -> &'static str {
+    GENERIC_ICONS
+        .iter()
+        .find(|(pattern, _)| crate::media_type::matches_prefix_pattern(pattern, content_type))
+        .map_or(DEFAULT_GENERIC_ICON, |(_, icon)| icon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_icon_name() {
+        let cases = [
+            ("image/png", "image-x-generic"),
+            ("AUDIO/mpeg", "audio-x-generic"),
+            ("video/MP2T", "video-x-generic"),
+            ("text/plain", "text-x-generic"),
+            ("text/html", "text-html"),
+            ("text/javascript", "text-x-script"),
+            ("font/woff2", "font-x-generic"),
+            ("application/x-msdownload", "application-x-executable"),
+            ("application/x-7z-compressed", "package-x-generic"),
+            ("application/msword", "x-office-document"),
+            (
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                "x-office-spreadsheet",
+            ),
+            ("application/vnd.ms-powerpoint", "x-office-presentation"),
+            (
+                "application/vnd.oasis.opendocument.graphics",
+                "x-office-drawing",
+            ),
+            ("application/pdf", "application-x-generic"),
+            ("", "application-x-generic"),
+        ];
+        for (content_type, icon) in cases {
+            assert_eq!(generic_icon_name(content_type), icon, "{content_type}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_icon_name() {
+        assert_eq!(icon_name("image/svg+xml"), "image-svg+xml");
+        assert_eq!(
+            icon_name("application/vnd.ms-excel"),
+            "application-vnd.ms-excel"
+        );
+    }
+}
@@ -0,0 +1,478 @@
+use std::borrow::Cow;
+
+use crate::{category::Category, TransferEncoding};
+
+/// MIME type information including extension, content type, and encoding.
+///
+/// This struct contains all the information about a specific MIME type,
+/// including whether it's a binary or text format.
+///
+/// The fields are copy-on-write strings: entries of the embedded database
+/// borrow its `'static` strings, so looking them up through the global
+/// functions, which return clones, allocates nothing. Entries parsed at
+/// runtime own their strings. Compare the fields with
+/// `&*info.content_type == "text/plain"` or through [`str`] methods.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Info {
+    /// File extension (without the dot)
+    pub extension: Cow<'static, str>,
+    /// MIME content type (e.g., "text/plain", "image/png")
+    pub content_type: Cow<'static, str>,
+    /// Transfer encoding (e.g., `8bit`, `base64`); it derefs to its token,
+    /// so `&*info.encoding == "base64"` works as with a string
+    pub encoding: TransferEncoding,
+    /// Priority among rows sharing an extension or content type; the highest
+    /// weight wins. Rows without a weight column have weight 0.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub weight: u16,
+    /// Tags such as `archive`, `document`, `executable`, `font`, `media`,
+    /// `office` or `web`, for policy rules over groups of types
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: Cow<'static, [Cow<'static, str>]>,
+    /// Where the entry comes from: the embedded database, the system's or
+    /// a runtime source. Entries created with [`Info::new`] are
+    /// [`Origin::Custom`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub origin: Origin,
+}
+
+/// Where an entry comes from, recorded in [`Info::origin`].
+///
+/// Lookups return the entry that wins among every source a database was
+/// built from; its origin tells which one that was, such as a system
+/// shared-mime-info glob overriding the embedded entry for an extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Origin {
+    /// The database embedded at build time
+    Embedded,
+    /// The operating system's database: the shared-mime-info files (with
+    /// the `xdg` feature) or the Windows registry (with `windows-registry`)
+    System,
+    /// Parsed at runtime: registered, or loaded from a database file
+    #[default]
+    Custom,
+}
+
+impl Info {
+    /// Creates a new `Info` instance from a database line.
+    ///
+    /// The line format is: `extension content_type encoding [weight [tags]]`,
+    /// where the optional weight is a non-negative integer and the optional
+    /// tags are comma-separated.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - A whitespace-separated string containing extension, content type, and encoding
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Info)` if the line is valid
+    /// * `None` if the line doesn't have at least 3 parts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{Info, TransferEncoding};
+    ///
+    /// let info = Info::new("pdf application/pdf base64").unwrap();
+    /// assert_eq!(&*info.extension, "pdf");
+    /// assert_eq!(&*info.content_type, "application/pdf");
+    /// assert_eq!(info.encoding, TransferEncoding::Base64);
+    /// ```
+    pub fn new(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 3 {
+            let weight = match parts.get(3) {
+                Some(weight) => weight.parse().ok()?,
+                None => 0,
+            };
+            let tags = parts
+                .get(4)
+                .map(|tags| {
+                    tags.split(',')
+                        .filter(|tag| !tag.is_empty())
+                        .map(|tag| Cow::Owned(tag.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(Info {
+                extension: Cow::Owned(parts[0].to_string()),
+                content_type: Cow::Owned(parts[1].to_string()),
+                encoding: parts[2].into(),
+                weight,
+                tags,
+                origin: Origin::Custom,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Determines if this MIME type represents a binary file format.
+    ///
+    /// Binary files are those that use "base64" or "8bit" encoding; see
+    /// [`TransferEncoding::is_binary`].
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the file type is binary
+    /// * `false` if the file type is text-based
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Info;
+    ///
+    /// let pdf = Info::new("pdf application/pdf base64").unwrap();
+    /// assert!(pdf.is_binary());
+    ///
+    /// let txt = Info::new("txt text/plain 7bit").unwrap();
+    /// assert!(!txt.is_binary());
+    /// ```
+    pub fn is_binary(&self) -> bool {
+        self.encoding.is_binary()
+    }
+
+    /// Returns the structured syntax suffix of this content type (RFC
+    /// 6839): `json` for `application/ld+json`, `xml` for `image/svg+xml`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = minimime::lookup_by_extension("svg").unwrap();
+    /// assert_eq!(info.structured_suffix(), Some("xml"));
+    /// assert_eq!(minimime::lookup_by_extension("png").unwrap().structured_suffix(), None);
+    /// ```
+    pub fn structured_suffix(&self) -> Option<&str> {
+        let essence = self.content_type.split(';').next().unwrap_or_default();
+        let (_, subtype) = essence.split_once('/')?;
+        let (_, suffix) = subtype.rsplit_once('+')?;
+        Some(suffix.trim()).filter(|suffix| !suffix.is_empty())
+    }
+
+    /// Returns the stable numeric ID of this content type.
+    ///
+    /// Returns `None` for content types missing from the embedded database,
+    /// such as custom ones loaded with [`Db::parse`](crate::Db::parse). See
+    /// [`tables::find_by_id`](crate::tables::find_by_id) for the stability
+    /// guarantees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Info;
+    ///
+    /// let info = minimime::lookup_by_extension("pdf").unwrap();
+    /// let id = info.id().unwrap();
+    /// assert_eq!(Info::from_id(id).as_ref(), Some(&info));
+    /// ```
+    pub fn id(&self) -> Option<u16> {
+        crate::tables::find_by_content_type(&self.content_type).map(|entry| entry.id)
+    }
+
+    /// Returns every extension of this content type in the embedded
+    /// database, the preferred one first.
+    ///
+    /// Returns an empty slice for content types missing from the embedded
+    /// database; [`Db::all_extensions`](crate::Db::all_extensions) also
+    /// covers entries registered or loaded at runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = minimime::lookup_by_content_type("image/jpeg").unwrap();
+    /// assert_eq!(info.extensions()[0], "jpeg");
+    /// assert!(info.extensions().contains(&"jpg"));
+    /// ```
+    pub fn extensions(&self) -> &'static [&'static str] {
+        crate::tables::find_by_content_type(&self.content_type)
+            .or_else(|| {
+                crate::tables::find_by_extension(&self.extension)
+                    .filter(|entry| entry.content_type.eq_ignore_ascii_case(&self.content_type))
+            })
+            .map_or(&[], |entry| entry.extensions)
+    }
+
+    /// Reconstructs the preferred entry for a content type from its numeric
+    /// ID, as returned by [`Info::id`].
+    ///
+    /// Returns `None` for unknown IDs.
+    pub fn from_id(id: u16) -> Option<Self> {
+        crate::tables::find_by_id(id).map(|entry| entry.to_info())
+    }
+
+    /// Returns the freedesktop icon name for this content type, such as
+    /// `application-pdf`; see [`icon::icon_name`](crate::icon::icon_name).
+    pub fn icon_name(&self) -> String {
+        crate::icon::icon_name(&self.content_type)
+    }
+
+    /// Returns the freedesktop generic icon name for this content type, such
+    /// as `image-x-generic`; see
+    /// [`icon::generic_icon_name`](crate::icon::generic_icon_name).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = minimime::lookup_by_extension("xlsx").unwrap();
+    /// assert_eq!(info.generic_icon_name(), "x-office-spreadsheet");
+    /// ```
+    pub fn generic_icon_name(&self) -> &'static str {
+        crate::icon::generic_icon_name(&self.content_type)
+    }
+
+    /// Returns the Apple Uniform Type Identifier of this content type, such
+    /// as `public.jpeg`; see
+    /// [`uti::uti_for_content_type`](crate::uti::uti_for_content_type).
+    pub fn uti(&self) -> Option<&'static str> {
+        crate::uti::uti_for_content_type(&self.content_type)
+    }
+
+    /// Returns how a search indexer should treat this type; see
+    /// [`extraction`](crate::extraction).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::extraction::ExtractionHint;
+    ///
+    /// let hint = |ext| minimime::lookup_by_extension(ext).unwrap().extraction_hint();
+    /// assert_eq!(hint("md"), ExtractionHint::Text);
+    /// assert_eq!(hint("pdf"), ExtractionHint::Extract);
+    /// assert_eq!(hint("png"), ExtractionHint::Opaque);
+    /// ```
+    pub fn extraction_hint(&self) -> crate::extraction::ExtractionHint {
+        crate::extraction::hint(
+            &self.content_type,
+            self.has_tag("document") || self.has_tag("office"),
+        )
+    }
+
+    /// Checks whether this type is text a search indexer can use as it is.
+    pub fn is_indexable_text(&self) -> bool {
+        self.extraction_hint().is_indexable_text()
+    }
+
+    /// Builds a `Content-Type` header value for content of this type, with
+    /// the content's charset for textual types; see
+    /// [`charset::content_type_header`](crate::charset::content_type_header).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = minimime::lookup_by_filename("notes.txt").unwrap();
+    /// assert_eq!(info.content_type_header(b"hello"), "text/plain; charset=utf-8");
+    /// ```
+    pub fn content_type_header(&self, bytes: &[u8]) -> String {
+        crate::charset::content_type_header(&self.content_type, bytes)
+    }
+
+    /// Converts this type into a `Content-Type` header value for the `http`
+    /// crate, and so for hyper, axum and others built on it.
+    ///
+    /// Textual types (see [`extraction::is_text`](crate::extraction::is_text))
+    /// get `charset=utf-8`; use [`Info::content_type_header`] to detect the
+    /// charset from the content instead. An entry whose content type is not
+    /// a valid header value, which only entries parsed at runtime can have,
+    /// gives `application/octet-stream`. Requires the `http` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = minimime::lookup_by_extension("csv").unwrap();
+    /// assert_eq!(info.to_header_value(), "text/csv; charset=utf-8");
+    /// let info = minimime::lookup_by_extension("png").unwrap();
+    /// assert_eq!(info.to_header_value(), "image/png");
+    /// ```
+    #[cfg(feature = "http")]
+    pub fn to_header_value(&self) -> http::HeaderValue {
+        let value = if crate::extraction::is_text(&self.content_type) {
+            http::HeaderValue::try_from(format!(
+                "{}; charset={}",
+                self.content_type,
+                crate::charset::Charset::Utf8
+            ))
+        } else {
+            http::HeaderValue::from_str(&self.content_type)
+        };
+        value.unwrap_or(http::HeaderValue::from_static("application/octet-stream"))
+    }
+
+    /// Converts this type into a [`mime::Mime`] for libraries built on the
+    /// `mime` crate.
+    ///
+    /// Only the type and subtype are carried over. An entry whose content
+    /// type `mime` can't parse, which only entries parsed at runtime can
+    /// have, gives `application/octet-stream`. Requires the `mime` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = minimime::lookup_by_extension("svg").unwrap();
+    /// assert_eq!(info.to_mime(), mime::IMAGE_SVG);
+    /// ```
+    #[cfg(feature = "mime")]
+    pub fn to_mime(&self) -> mime::Mime {
+        self.content_type
+            .parse()
+            .unwrap_or(mime::APPLICATION_OCTET_STREAM)
+    }
+
+    /// Returns the broad kind of this type; see [`category`](crate::category).
+    ///
+    /// Entries tagged `font` or `archive` count as such whatever their
+    /// content type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::category::Category;
+    ///
+    /// let category = |ext| minimime::lookup_by_extension(ext).unwrap().category();
+    /// assert_eq!(category("jpg"), Category::Image);
+    /// assert_eq!(category("ttf"), Category::Font);
+    /// assert_eq!(category("tgz"), Category::Archive);
+    /// assert_eq!(category("csv"), Category::Text);
+    /// ```
+    pub fn category(&self) -> Category {
+        crate::category::classify(
+            &self.content_type,
+            self.has_tag("font"),
+            self.has_tag("archive"),
+        )
+    }
+
+    /// Checks whether this is an image type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert!(minimime::lookup_by_filename("avatar.webp").unwrap().is_image());
+    /// assert!(!minimime::lookup_by_filename("avatar.pdf").unwrap().is_image());
+    /// ```
+    pub fn is_image(&self) -> bool {
+        self.category() == Category::Image
+    }
+
+    /// Checks whether this is an audio type.
+    pub fn is_audio(&self) -> bool {
+        self.category() == Category::Audio
+    }
+
+    /// Checks whether this is a video type.
+    pub fn is_video(&self) -> bool {
+        self.category() == Category::Video
+    }
+
+    /// Checks whether this is a text type, including JSON, XML and
+    /// JavaScript.
+    pub fn is_text(&self) -> bool {
+        self.category() == Category::Text
+    }
+
+    /// Checks whether this is a font type.
+    pub fn is_font(&self) -> bool {
+        self.category() == Category::Font
+    }
+
+    /// Checks whether this is an archive or compressed file type.
+    pub fn is_archive(&self) -> bool {
+        self.category() == Category::Archive
+    }
+
+    /// Checks whether this entry carries a tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = minimime::lookup_by_extension("zip").unwrap();
+    /// assert!(info.has_tag("archive"));
+    /// assert!(!info.has_tag("executable"));
+    /// ```
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| **own == *tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_creation() {
+        let info = Info::new("pdf application/pdf base64").unwrap();
+        assert_eq!(&*info.extension, "pdf");
+        assert_eq!(&*info.content_type, "application/pdf");
+        assert_eq!(&*info.encoding, "base64");
+        assert_eq!(info.weight, 0);
+        assert!(info.is_binary());
+    }
+
+    #[test]
+    fn test_info_weight() {
+        let info = Info::new("ts text/typescript quoted-printable 10").unwrap();
+        assert_eq!(info.weight, 10);
+        assert!(Info::new("ts text/typescript quoted-printable heavy").is_none());
+        assert!(Info::new("ts text/typescript quoted-printable -1").is_none());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_info_category() {
+        let db = crate::Db::new().unwrap();
+        let info = |ext| db.lookup_by_extension(ext).unwrap();
+        assert!(info("png").is_image());
+        assert!(info("mp3").is_audio());
+        assert!(info("mkv").is_video());
+        assert!(info("md").is_text());
+        assert!(info("woff2").is_font());
+        assert!(info("zip").is_archive());
+        assert_eq!(info("exe").category(), Category::Other);
+
+        let custom = Info::new("pkg application/x-acme-bundle base64 0 archive").unwrap();
+        assert!(custom.is_archive());
+    }
+
+    #[test]
+    fn test_info_tags() {
+        let info = Info::new("tpl text/x-template quoted-printable 0 web,,internal").unwrap();
+        assert_eq!(&*info.tags, ["web", "internal"]);
+        assert!(info.has_tag("internal"));
+        assert!(!info.has_tag("office"));
+        assert!(Info::new("pdf application/pdf base64")
+            .unwrap()
+            .tags
+            .is_empty());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_to_header_value() {
+        let info = Info::new("json application/json 8bit").unwrap();
+        assert_eq!(info.to_header_value(), "application/json; charset=utf-8");
+        let info = Info::new("bin application/x-acme\x01 base64").unwrap();
+        assert_eq!(info.to_header_value(), "application/octet-stream");
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn test_to_mime() {
+        let info = Info::new("json application/json 8bit").unwrap();
+        assert_eq!(info.to_mime(), mime::APPLICATION_JSON);
+        let info = Info::new("bin not-a-type base64").unwrap();
+        assert_eq!(info.to_mime(), mime::APPLICATION_OCTET_STREAM);
+    }
+
+    #[test]
+    fn test_info_size() {
+        // Three copy-on-write strings, the copy-on-write tags, then the
+        // weight and the one-byte origin, padded to pointer alignment.
+        assert_eq!(std::mem::size_of::<Info>(), 104);
+    }
+}
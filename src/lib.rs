@@ -11,6 +11,10 @@
 //! - **No external dependencies**: Database files are embedded at compile time
 //! - **Case insensitive**: Handles file extensions in any case
 //! - **Binary detection**: Identifies binary vs text file types
+//! - **Content sniffing**: Detects types from magic bytes via the [`sniff`] module
+//! - **Content negotiation**: Picks a representation from an `Accept` header via the [`negotiate`] module
+//! - **Embedded mode**: Heap-free, `no_std` lookups with the `embedded` feature
+//! - **No hidden state**: Disable the default `global` feature to use only explicit [`Db`] instances
 //! - **Thread safe**: Safe for concurrent use across multiple threads
 //!
 //! ## Quick Start
@@ -38,418 +42,122 @@
 //! ## Supported File Types
 //!
 //! This library supports hundreds of file extensions and MIME types, including:
-//! - Web formats (HTML, CSS, JS, JSON, XML)
-//! - Images (PNG, JPEG, GIF, SVG, WebP)
+//! - Web formats (HTML, CSS, JS, JSON, XML, JSX, TSX, Vue, Svelte, WebAssembly)
+//! - Images (PNG, JPEG, GIF, SVG, WebP, AVIF, HEIC/HEIF, JPEG XL)
+//! - Camera raw (CR2, CR3, NEF, ARW, ORF, RAF, DNG)
 //! - Documents (PDF, DOC, XLS, PPT)
 //! - Archives (ZIP, TAR, GZ)
 //! - Media files (MP3, MP4, AVI, MOV)
+//! - Subtitles (WebVTT, SRT, ASS/SSA, TTML)
+//! - Email (EML, MSG, mbox)
 //! - And many more...
 
-use std::{
-    collections::HashMap,
-    path::Path,
-    sync::{Mutex, OnceLock},
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "bench-support")]
+pub mod bench_support;
+pub mod category;
+pub mod charset;
+#[cfg(feature = "std")]
+mod data_uri;
+#[cfg(feature = "std")]
+mod db;
+#[cfg(feature = "std")]
+pub mod detection;
+#[cfg(feature = "std")]
+pub mod disposition;
+#[cfg(feature = "embedded")]
+pub mod embedded;
+#[cfg(feature = "std")]
+mod encoding;
+#[cfg(feature = "std")]
+mod error;
+pub mod extraction;
+#[cfg(feature = "std")]
+mod glob;
+#[cfg(feature = "global")]
+mod global;
+pub mod icon;
+#[cfg(feature = "std")]
+mod info;
+#[cfg(feature = "mail")]
+pub mod mail;
+#[cfg(feature = "std")]
+pub mod mapped;
+pub mod media_type;
+#[cfg(feature = "std")]
+pub mod multipart;
+#[cfg(feature = "std")]
+pub mod negotiate;
+#[cfg(feature = "std")]
+mod normalize;
+#[cfg(feature = "std")]
+mod options;
+#[cfg(feature = "std")]
+pub mod policy;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "windows-registry")]
+mod registry;
+#[cfg(feature = "std")]
+mod report;
+pub mod sniff;
+#[cfg(feature = "std")]
+mod suggest;
+pub mod tables;
+mod telemetry;
+#[cfg(all(feature = "std", any(test, feature = "testing")))]
+pub mod testing;
+pub mod uti;
+mod version;
+#[cfg(feature = "walk")]
+pub mod walk;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "xdg")]
+mod xdg;
+
+#[cfg(feature = "std")]
+pub use db::{Db, DbBuilder, DbStats, InsertionPolicy, MergeConflict, MergeStrategy, ParseError};
+#[cfg(feature = "std")]
+pub use encoding::TransferEncoding;
+#[cfg(feature = "std")]
+pub use error::Error;
+#[cfg(all(feature = "global", feature = "mime"))]
+pub use global::lookup_by_mime;
+#[cfg(all(feature = "global", feature = "tokio"))]
+pub use global::lookup_by_path_async;
+#[cfg(all(feature = "global", feature = "http"))]
+pub use global::lookup_header_for_filename;
+#[cfg(all(feature = "global", feature = "rayon"))]
+pub use global::par_detect_files;
+#[cfg(feature = "global")]
+pub use global::{
+    all_extensions, detect, extensions_for_content_type, lookup_by_content_type,
+    lookup_by_content_type_or_suffix, lookup_by_extension, lookup_by_filename,
+    lookup_by_filename_with, lookup_by_magic, lookup_by_path, lookup_by_url, preferred_extension,
+    preload, register, register_line, register_with, suggest_extensions, suggest_filename,
+    to_data_uri, try_init, try_lookup_by_content_type, try_lookup_by_extension,
+    try_lookup_by_filename, try_lookup_by_path, unregister_extension, verify, Handle, InitError,
 };
-
-/// MIME type information including extension, content type, and encoding.
-///
-/// This struct contains all the information about a specific MIME type,
-/// including whether it's a binary or text format.
-#[derive(Debug, Clone, PartialEq)]
-pub struct Info {
-    /// File extension (without the dot)
-    pub extension: String,
-    /// MIME content type (e.g., "text/plain", "image/png")
-    pub content_type: String,
-    /// Encoding type (e.g., "8bit", "base64")
-    pub encoding: String,
-}
-
-impl Info {
-    /// Encodings that indicate binary file types
-    const BINARY_ENCODINGS: &'static [&'static str] = &["base64", "8bit"];
-
-    /// Creates a new `Info` instance from a database line.
-    ///
-    /// The line format is: `extension content_type encoding`
-    ///
-    /// # Arguments
-    ///
-    /// * `line` - A whitespace-separated string containing extension, content type, and encoding
-    ///
-    /// # Returns
-    ///
-    /// * `Some(Info)` if the line is valid
-    /// * `None` if the line doesn't have at least 3 parts
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use minimime::Info;
-    ///
-    /// let info = Info::new("pdf application/pdf base64").unwrap();
-    /// assert_eq!(info.extension, "pdf");
-    /// assert_eq!(info.content_type, "application/pdf");
-    /// assert_eq!(info.encoding, "base64");
-    /// ```
-    pub fn new(line: &str) -> Option<Self> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 3 {
-            Some(Info {
-                extension: parts[0].to_string(),
-                content_type: parts[1].to_string(),
-                encoding: parts[2].to_string(),
-            })
-        } else {
-            None
-        }
-    }
-
-    /// Determines if this MIME type represents a binary file format.
-    ///
-    /// Binary files are those that use "base64" or "8bit" encoding.
-    ///
-    /// # Returns
-    ///
-    /// * `true` if the file type is binary
-    /// * `false` if the file type is text-based
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use minimime::Info;
-    ///
-    /// let pdf = Info::new("pdf application/pdf base64").unwrap();
-    /// assert!(pdf.is_binary());
-    ///
-    /// let txt = Info::new("txt text/plain 7bit").unwrap();
-    /// assert!(!txt.is_binary());
-    /// ```
-    pub fn is_binary(&self) -> bool {
-        Self::BINARY_ENCODINGS.contains(&self.encoding.as_str())
-    }
-}
-
-/// Internal database for MIME type lookups.
-///
-/// This struct manages the hash maps used for fast MIME type lookups
-/// by file extension and content type.
-pub struct Db {
-    ext_db: HashMap<String, Info>,
-    content_type_db: HashMap<String, Info>,
-}
-
-impl Db {
-    /// Creates a new database instance and loads the embedded data files.
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let mut db = Db {
-            ext_db: HashMap::new(),
-            content_type_db: HashMap::new(),
-        };
-
-        // Load extension database
-        db.load_ext_db()?;
-        // Load content type database
-        db.load_content_type_db()?;
-
-        Ok(db)
-    }
-
-    /// Loads the file extension to MIME type database.
-    ///
-    /// This method reads the embedded `ext_mime.db` file and populates
-    /// the extension lookup hash map.
-    fn load_ext_db(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let db_content = include_str!("db/ext_mime.db");
-        for line in db_content.lines() {
-            if let Some(info) = Info::new(line) {
-                self.ext_db.insert(info.extension.clone(), info);
-            }
-        }
-        Ok(())
-    }
-
-    /// Loads the content type to MIME type database.
-    ///
-    /// This method reads the embedded `content_type_mime.db` file and populates
-    /// the content type lookup hash map.
-    fn load_content_type_db(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let db_content = include_str!("db/content_type_mime.db");
-        for line in db_content.lines() {
-            if let Some(info) = Info::new(line) {
-                self.content_type_db.insert(info.content_type.clone(), info);
-            }
-        }
-        Ok(())
-    }
-
-    /// Looks up MIME information by file extension.
-    ///
-    /// The lookup is case-insensitive, trying the exact extension first,
-    /// then falling back to lowercase.
-    ///
-    /// # Arguments
-    ///
-    /// * `extension` - File extension (with or without leading dot)
-    ///
-    /// # Returns
-    ///
-    /// * `Some(&Info)` if the extension is found
-    /// * `None` if the extension is not recognized
-    pub fn lookup_by_extension(&self, extension: &str) -> Option<&Info> {
-        self.ext_db
-            .get(extension)
-            .or_else(|| self.ext_db.get(&extension.to_lowercase()))
-    }
-
-    /// Looks up MIME information by content type.
-    ///
-    /// # Arguments
-    ///
-    /// * `content_type` - MIME content type (e.g., "text/plain")
-    ///
-    /// # Returns
-    ///
-    /// * `Some(&Info)` if the content type is found
-    /// * `None` if the content type is not recognized
-    pub fn lookup_by_content_type(&self, content_type: &str) -> Option<&Info> {
-        self.content_type_db.get(content_type)
-    }
-
-    /// Looks up MIME information by filename.
-    ///
-    /// Extracts the file extension from the filename and performs a lookup.
-    /// The lookup is case-insensitive.
-    ///
-    /// # Arguments
-    ///
-    /// * `filename` - Full filename or path
-    ///
-    /// # Returns
-    ///
-    /// * `Some(&Info)` if the file extension is recognized
-    /// * `None` if the file has no extension or the extension is not recognized
-    pub fn lookup_by_filename(&self, filename: &str) -> Option<&Info> {
-        let path = Path::new(filename);
-        if let Some(ext) = path.extension() {
-            if let Some(ext_str) = ext.to_str() {
-                return self.lookup_by_extension(ext_str);
-            }
-        }
-        None
-    }
-}
-
-// Global database instance
-static DB: OnceLock<Mutex<Db>> = OnceLock::new();
-
-/// Gets the global database instance.
-///
-/// This function initializes the database on first access and returns
-/// a reference to the global singleton instance.
-///
-/// # Returns
-///
-/// A reference to the global `Mutex<Db>` instance
-///
-/// # Panics
-///
-/// Panics if the database fails to initialize
-fn get_db() -> &'static Mutex<Db> {
-    DB.get_or_init(|| Mutex::new(Db::new().expect("Failed to initialize MIME database")))
-}
-
-/// Looks up MIME information by filename.
-///
-/// This is a convenience function that uses the global database instance
-/// to perform the lookup. The lookup is case-insensitive.
-///
-/// # Arguments
-///
-/// * `filename` - Full filename or path
-///
-/// # Returns
-///
-/// * `Some(Info)` if the file extension is recognized
-/// * `None` if the file has no extension or the extension is not recognized
-///
-/// # Examples
-///
-/// ```
-/// use minimime::lookup_by_filename;
-///
-/// if let Some(info) = lookup_by_filename("document.pdf") {
-///     println!("MIME type: {}", info.content_type);
-///     println!("Is binary: {}", info.is_binary());
-/// }
-/// ```
-pub fn lookup_by_filename(filename: &str) -> Option<Info> {
-    let db = get_db().lock().unwrap();
-    db.lookup_by_filename(filename).cloned()
-}
-
-/// Looks up MIME information by file extension.
-///
-/// This is a convenience function that uses the global database instance
-/// to perform the lookup. The lookup is case-insensitive.
-///
-/// # Arguments
-///
-/// * `extension` - File extension (with or without leading dot)
-///
-/// # Returns
-///
-/// * `Some(Info)` if the extension is found
-/// * `None` if the extension is not recognized
-///
-/// # Examples
-///
-/// ```
-/// use minimime::lookup_by_extension;
-///
-/// if let Some(info) = lookup_by_extension("pdf") {
-///     println!("MIME type: {}", info.content_type);
-///     println!("Encoding: {}", info.encoding);
-/// }
-/// ```
-pub fn lookup_by_extension(extension: &str) -> Option<Info> {
-    let db = get_db().lock().unwrap();
-    db.lookup_by_extension(extension).cloned()
-}
-
-/// Looks up MIME information by content type.
-///
-/// This is a convenience function that uses the global database instance
-/// to perform the lookup.
-///
-/// # Arguments
-///
-/// * `content_type` - MIME content type (e.g., "text/plain")
-///
-/// # Returns
-///
-/// * `Some(Info)` if the content type is found
-/// * `None` if the content type is not recognized
-///
-/// # Examples
-///
-/// ```
-/// use minimime::lookup_by_content_type;
-///
-/// if let Some(info) = lookup_by_content_type("application/pdf") {
-///     println!("Extension: {}", info.extension);
-///     println!("Is binary: {}", info.is_binary());
-/// }
-/// ```
-pub fn lookup_by_content_type(content_type: &str) -> Option<Info> {
-    let db = get_db().lock().unwrap();
-    db.lookup_by_content_type(content_type).cloned()
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_info_creation() {
-        let info = Info::new("pdf application/pdf base64").unwrap();
-        assert_eq!(info.extension, "pdf");
-        assert_eq!(info.content_type, "application/pdf");
-        assert_eq!(info.encoding, "base64");
-        assert!(info.is_binary());
-    }
-
-    #[test]
-    fn test_extension() {
-        if let Some(info) = lookup_by_extension("zip") {
-            assert_eq!(info.content_type, "application/zip");
-        }
-    }
-
-    #[test]
-    fn test_mixed_case() {
-        // Test case insensitive lookups
-        if let Some(info) = lookup_by_filename("a.GTM") {
-            assert_eq!(info.content_type, "application/vnd.groove-tool-message");
-        }
-        if let Some(info) = lookup_by_extension("ZiP") {
-            assert_eq!(info.content_type, "application/zip");
-        }
-    }
-
-    #[test]
-    fn test_content_type_lookups() {
-        // Test various file extensions
-        if let Some(info) = lookup_by_filename("a.123") {
-            assert_eq!(info.content_type, "application/vnd.lotus-1-2-3");
-        }
-        if let Some(info) = lookup_by_filename("a.Z") {
-            assert_eq!(info.content_type, "application/x-compressed");
-        }
-        if let Some(info) = lookup_by_filename("a.gtm") {
-            assert_eq!(info.content_type, "application/vnd.groove-tool-message");
-        }
-        if let Some(info) = lookup_by_filename("a.zmm") {
-            assert_eq!(
-                info.content_type,
-                "application/vnd.HandHeld-Entertainment+xml"
-            );
-        }
-        if let Some(info) = lookup_by_filename("x.csv") {
-            assert_eq!(info.content_type, "text/csv");
-        }
-        if let Some(info) = lookup_by_filename("x.mda") {
-            assert_eq!(info.content_type, "application/x-msaccess");
-        }
-
-        // Test unknown extension
-        assert!(lookup_by_filename("a.frog").is_none());
-    }
-
-    #[test]
-    fn test_binary() {
-        // Test binary detection
-        if let Some(info) = lookup_by_filename("a.z") {
-            assert!(info.is_binary());
-        }
-        if let Some(info) = lookup_by_filename("a.Z") {
-            assert!(info.is_binary());
-        }
-        if let Some(info) = lookup_by_filename("a.txt") {
-            assert!(!info.is_binary());
-        }
-        assert!(lookup_by_filename("a.frog").is_none());
-    }
-
-    #[test]
-    fn test_binary_content_type() {
-        if let Some(info) = lookup_by_content_type("application/x-compressed") {
-            assert!(info.is_binary());
-        }
-        assert!(lookup_by_content_type("something-fake").is_none());
-        if let Some(info) = lookup_by_content_type("text/plain") {
-            assert!(!info.is_binary());
-        }
-    }
-
-    #[test]
-    fn test_prioritize_extensions_correctly() {
-        if let Some(info) = lookup_by_content_type("text/plain") {
-            assert_eq!(info.extension, "txt");
-        }
-    }
-
-    #[test]
-    fn test_lookup_by_filename() {
-        if let Some(info) = lookup_by_filename("document.pdf") {
-            assert_eq!(info.content_type, "application/pdf");
-        }
-    }
-
-    #[test]
-    fn test_lookup_by_extension() {
-        if let Some(info) = lookup_by_extension("pdf") {
-            assert_eq!(info.content_type, "application/pdf");
-        }
-    }
-}
+#[cfg(feature = "std")]
+pub use info::{Info, Origin};
+#[cfg(feature = "std")]
+pub use media_type::canonicalize;
+pub use media_type::{content_type_matches, validate_content_type};
+#[cfg(feature = "std")]
+pub use media_type::{MediaType, MediaTypeError};
+#[cfg(feature = "std")]
+pub use options::LookupOptions;
+#[cfg(feature = "std")]
+pub use report::{Collision, LoadReport, ReportLine};
+pub use version::{database_version, DatabaseVersion};
+
+/// Encodings that indicate binary file types
+const BINARY_ENCODINGS: &[&str] = &["base64", "8bit"];
@@ -0,0 +1,184 @@
+//! Content transfer encodings for email attachments.
+//!
+//! Every database row carries the transfer encoding mail clients use for
+//! its type. [`Info::suggested_transfer_encoding`] reads it as a
+//! [`TransferEncoding`], and [`encode_for_mime`] encodes an attachment body
+//! accordingly, ready to follow its `Content-Transfer-Encoding` header.
+//! Requires the `mail` feature.
+
+pub use crate::TransferEncoding;
+use crate::{data_uri::encode_base64, Info};
+
+/// Longest encoded line, without the line break (RFC 2045).
+const LINE_LEN: usize = 76;
+
+impl Info {
+    /// Returns the transfer encoding to attach files of this type with.
+    ///
+    /// This is the entry's encoding column; entries loaded with an encoding
+    /// this crate doesn't know get [`TransferEncoding::Base64`], which is
+    /// safe for any content. Requires the `mail` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::mail::TransferEncoding;
+    ///
+    /// let info = minimime::lookup_by_extension("txt").unwrap();
+    /// assert_eq!(info.suggested_transfer_encoding(), TransferEncoding::QuotedPrintable);
+    /// ```
+    pub fn suggested_transfer_encoding(&self) -> TransferEncoding {
+        match &self.encoding {
+            TransferEncoding::Other(_) => TransferEncoding::Base64,
+            known => known.clone(),
+        }
+    }
+}
+
+/// Encodes an attachment body with the transfer encoding its type suggests.
+///
+/// Base64 and quoted-printable output comes in lines of at most 76
+/// characters separated by CRLF. `7bit` and `8bit` bodies are returned as
+/// they are, so they must already keep to those encodings' limits.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::mail::encode_for_mime;
+///
+/// let png = minimime::lookup_by_extension("png").unwrap();
+/// assert_eq!(encode_for_mime(b"\x89PNG", &png), b"iVBORw==");
+///
+/// let txt = minimime::lookup_by_extension("txt").unwrap();
+/// assert_eq!(encode_for_mime("1 + 1 = 2 €\n".as_bytes(), &txt), b"1 + 1 =3D 2 =E2=82=AC\r\n");
+/// ```
+pub fn encode_for_mime(bytes: &[u8], info: &Info) -> Vec<u8> {
+    encode(bytes, info.suggested_transfer_encoding())
+}
+
+/// Encodes a body with the given transfer encoding; see
+/// [`encode_for_mime`]. Unknown encodings get base64, which is safe for any
+/// content.
+pub fn encode(bytes: &[u8], encoding: TransferEncoding) -> Vec<u8> {
+    match encoding {
+        TransferEncoding::SevenBit | TransferEncoding::EightBit => bytes.to_vec(),
+        TransferEncoding::QuotedPrintable => encode_quoted_printable(bytes),
+        TransferEncoding::Base64 | TransferEncoding::Other(_) => {
+            let mut encoded = String::new();
+            encode_base64(bytes, &mut encoded);
+            let mut out = Vec::with_capacity(encoded.len() + encoded.len() / LINE_LEN * 2);
+            for (i, line) in encoded.as_bytes().chunks(LINE_LEN).enumerate() {
+                if i > 0 {
+                    out.extend_from_slice(b"\r\n");
+                }
+                out.extend_from_slice(line);
+            }
+            out
+        }
+    }
+}
+
+/// Encodes quoted-printable (RFC 2045, section 6.7), turning line breaks
+/// into CRLF and splitting long lines with soft line breaks.
+fn encode_quoted_printable(bytes: &[u8]) -> Vec<u8> {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut out = Vec::with_capacity(bytes.len() + bytes.len() / 8);
+    let mut lines = bytes.split(|&byte| byte == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        let more = lines.peek().is_some();
+        let line = if more {
+            line.strip_suffix(b"\r").unwrap_or(line)
+        } else {
+            line
+        };
+        let mut len = 0;
+        for (i, &byte) in line.iter().enumerate() {
+            let last = i + 1 == line.len();
+            // Whitespace at the end of a line would be stripped in transit.
+            let literal = matches!(byte, b'!'..=b'<' | b'>'..=b'~')
+                || (byte == b' ' || byte == b'\t') && !last;
+            let width = if literal { 1 } else { 3 };
+            // Keep room for the `=` of a soft line break, except on the
+            // line's last character.
+            let limit = if last { LINE_LEN } else { LINE_LEN - 1 };
+            if len + width > limit {
+                out.extend_from_slice(b"=\r\n");
+                len = 0;
+            }
+            if literal {
+                out.push(byte);
+            } else {
+                out.extend_from_slice(&[
+                    b'=',
+                    HEX[usize::from(byte >> 4)],
+                    HEX[usize::from(byte & 0xF)],
+                ]);
+            }
+            len += width;
+        }
+        if more {
+            out.extend_from_slice(b"\r\n");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qp(text: &str) -> String {
+        String::from_utf8(encode(text.as_bytes(), TransferEncoding::QuotedPrintable)).unwrap()
+    }
+
+    #[test]
+    fn test_quoted_printable() {
+        assert_eq!(qp("plain"), "plain");
+        assert_eq!(qp("a=b\r\nc \t\nd "), "a=3Db\r\nc =09\r\nd=20");
+        assert_eq!(qp("\u{0}\u{7f}"), "=00=7F");
+
+        let long = "x".repeat(200);
+        let encoded = qp(&long);
+        assert!(encoded.split("\r\n").all(|line| line.len() <= LINE_LEN));
+        assert_eq!(encoded.replace("=\r\n", ""), long);
+        // An escape is never split by a soft line break.
+        let encoded = qp(&"é".repeat(40));
+        assert!(encoded
+            .split("\r\n")
+            .all(|line| line.len() <= LINE_LEN && !line.ends_with("=C")));
+        // A final 76th character needs no room for a soft break.
+        assert_eq!(qp(&"y".repeat(76)), "y".repeat(76));
+    }
+
+    #[test]
+    fn test_base64_lines() {
+        let encoded = encode(&[0u8; 100], TransferEncoding::Base64);
+        let encoded = String::from_utf8(encoded).unwrap();
+        let lines: Vec<&str> = encoded.split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), LINE_LEN);
+        assert!(lines[1].ends_with("=="));
+        assert!(encode(b"", TransferEncoding::Base64).is_empty());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_suggested_transfer_encoding() {
+        let db = crate::Db::new().unwrap();
+        let encoding = |ext| {
+            db.lookup_by_extension(ext)
+                .unwrap()
+                .suggested_transfer_encoding()
+        };
+        assert_eq!(encoding("pdf"), TransferEncoding::Base64);
+        assert_eq!(encoding("csv"), TransferEncoding::EightBit);
+        assert_eq!(encoding("txt"), TransferEncoding::QuotedPrintable);
+        let custom = Info::new("uu application/x-acme x-uuencode").unwrap();
+        assert_eq!(
+            custom.suggested_transfer_encoding(),
+            TransferEncoding::Base64
+        );
+        assert_eq!(TransferEncoding::SevenBit.to_string(), "7bit");
+    }
+}
@@ -0,0 +1,420 @@
+//! Compact binary database layout for sharing across processes.
+//!
+//! A [`Db`] can be written once with [`Db::to_mapped_bytes`] and then opened
+//! read-only by any number of processes. The layout is designed to be used in
+//! place: lookups binary-search fixed-size records and slice strings straight
+//! out of the buffer, so a memory-mapped file (see `MappedDb::open`, behind
+//! the `mmap` feature) is shared through the page cache instead of being
+//! copied into every worker of a pre-fork server.
+//!
+//! # Layout
+//!
+//! All integers are little-endian `u32`.
+//!
+//! | Section            | Contents                                                    |
+//! |--------------------|-------------------------------------------------------------|
+//! | Header (16 bytes)  | magic `MMDB`, version, extension count, content type count  |
+//! | Extension records  | one record per extension, sorted by lowercase extension     |
+//! | Content type records | one record per content type or alias, sorted by lowercase key |
+//! | String table       | deduplicated UTF-8 strings                                  |
+//!
+//! Each record is 44 bytes: `(offset, length)` pairs into the string table
+//! for the lookup key, the extension, the content type, the encoding and the
+//! comma-separated tags, then the weight in the low 16 bits and the [`Origin`] of the entry
+//! (0 embedded, 1 system, 2 custom) in the high ones.
+
+use std::{borrow::Cow, collections::HashMap, io, ops::Range};
+
+use crate::{Db, Info, Origin};
+
+const MAGIC: &[u8; 4] = b"MMDB";
+const VERSION: u32 = 5;
+const HEADER_LEN: usize = 16;
+const RECORD_LEN: usize = 44;
+const FIELDS: usize = 5;
+
+impl Db {
+    /// Serializes this database into the compact layout read by [`MappedDb`].
+    ///
+    /// Only the extension and content type tables are written; filename
+    /// rules (see [`Db::add_filename_rule`]) are not part of the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::{mapped::MappedDb, Db};
+    ///
+    /// let bytes = Db::new().unwrap().to_mapped_bytes();
+    /// let mapped = MappedDb::new(bytes).unwrap();
+    /// assert_eq!(&*mapped.lookup_by_extension("pdf").unwrap().content_type, "application/pdf");
+    /// ```
+    pub fn to_mapped_bytes(&self) -> Vec<u8> {
+        // Records are keyed as in the `Db` tables, lowercased and with the
+        // aliases of the content types, so lookups fold the query once and
+        // search a single sorted run.
+        let mut ext: Vec<(&str, &Info)> = self
+            .ext_db
+            .iter()
+            .map(|(key, info)| (&**key, info))
+            .collect();
+        ext.sort_by(|a, b| a.0.cmp(b.0));
+        let mut content_type: Vec<(&str, &Info)> = self
+            .content_type_db
+            .iter()
+            .map(|(key, info)| (&**key, info))
+            .collect();
+        content_type.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut strings = Vec::new();
+        let mut interned: HashMap<&str, [u8; 8]> = HashMap::new();
+        let mut records = Vec::with_capacity((ext.len() + content_type.len()) * RECORD_LEN);
+        let tags: Vec<String> = ext
+            .iter()
+            .chain(&content_type)
+            .map(|(_, info)| info.tags.join(","))
+            .collect();
+        for ((key, info), tags) in ext.iter().chain(&content_type).zip(&tags) {
+            let fields = [
+                *key,
+                &*info.extension,
+                &*info.content_type,
+                &*info.encoding,
+                tags,
+            ];
+            for field in fields {
+                let span = interned.entry(field).or_insert_with(|| {
+                    let mut span = [0; 8];
+                    span[..4].copy_from_slice(&(strings.len() as u32).to_le_bytes());
+                    span[4..].copy_from_slice(&(field.len() as u32).to_le_bytes());
+                    strings.extend_from_slice(field.as_bytes());
+                    span
+                });
+                records.extend_from_slice(span);
+            }
+            let origin = match info.origin {
+                Origin::Embedded => 0,
+                Origin::System => 1,
+                Origin::Custom => 2,
+            };
+            records.extend_from_slice(&(u32::from(info.weight) | origin << 16).to_le_bytes());
+        }
+
+        let mut out = Vec::with_capacity(HEADER_LEN + records.len() + strings.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(ext.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(content_type.len() as u32).to_le_bytes());
+        out.extend_from_slice(&records);
+        out.extend_from_slice(&strings);
+        out
+    }
+}
+
+/// A read-only database backed by the compact layout.
+///
+/// `B` is any byte container: a `Vec<u8>`, a `&'static [u8]`, or a
+/// `memmap2::Mmap` when opened with `MappedDb::open` (`mmap` feature).
+#[derive(Debug)]
+pub struct MappedDb<B> {
+    bytes: B,
+    ext_count: usize,
+    content_type_count: usize,
+}
+
+impl<B: AsRef<[u8]>> MappedDb<B> {
+    /// Wraps a buffer holding the compact layout.
+    ///
+    /// The header, record bounds and string encoding are validated up front,
+    /// so lookups on the returned database cannot fail on malformed data.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MappedDb)` if the buffer holds a valid database
+    /// * `Err` with [`io::ErrorKind::InvalidData`] otherwise
+    pub fn new(bytes: B) -> io::Result<Self> {
+        let data = bytes.as_ref();
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err(invalid("not a minimime database"));
+        }
+        if read_u32(data, 4) != VERSION {
+            return Err(invalid("unsupported minimime database version"));
+        }
+        let ext_count = read_u32(data, 8) as usize;
+        let content_type_count = read_u32(data, 12) as usize;
+        let records = ext_count
+            .checked_add(content_type_count)
+            .ok_or_else(|| invalid("truncated minimime database"))?;
+        let strings = records
+            .checked_mul(RECORD_LEN)
+            .and_then(|len| len.checked_add(HEADER_LEN))
+            .and_then(|start| data.get(start..))
+            .ok_or_else(|| invalid("truncated minimime database"))?;
+        for record in 0..records {
+            for field in 0..FIELDS {
+                let valid = strings
+                    .get(span(data, record, field))
+                    .is_some_and(|bytes| std::str::from_utf8(bytes).is_ok());
+                if !valid {
+                    return Err(invalid("corrupt minimime string table"));
+                }
+            }
+        }
+
+        Ok(MappedDb {
+            bytes,
+            ext_count,
+            content_type_count,
+        })
+    }
+
+    /// Looks up MIME information by file extension.
+    ///
+    /// The extension is lowercased once, without allocating, and searched
+    /// among the lowercase keys, as in [`Db::lookup_by_extension`].
+    pub fn lookup_by_extension(&self, extension: &str) -> Option<Info> {
+        let mut buf = [0; crate::normalize::MAX_FOLDED_LEN];
+        self.find(
+            0..self.ext_count,
+            &crate::normalize::lowercase(extension, &mut buf),
+        )
+    }
+
+    /// Looks up MIME information by content type.
+    ///
+    /// As in [`Db::lookup_by_content_type`], the lookup is ASCII
+    /// case-insensitive, parameters are ignored, and legacy spellings such
+    /// as `image/jpg` resolve to the type they stand for.
+    pub fn lookup_by_content_type(&self, content_type: &str) -> Option<Info> {
+        let records = self.ext_count..self.ext_count + self.content_type_count;
+        let essence = crate::media_type::essence(content_type)?;
+        let mut buf = [0; crate::normalize::MAX_FOLDED_LEN];
+        self.find(
+            records.clone(),
+            &crate::normalize::fold_ascii_lowercase(essence, &mut buf),
+        )
+        .or_else(|| self.find(records, crate::media_type::resolve_alias(essence)?))
+    }
+
+    /// Looks up MIME information by filename.
+    pub fn lookup_by_filename(&self, filename: &str) -> Option<Info> {
+        crate::normalize::compound_extension(filename)
+            .and_then(|ext| self.lookup_by_extension(ext))
+            .or_else(|| self.lookup_by_extension(crate::normalize::extension(filename)?))
+    }
+
+    /// Binary-searches `records` by their lookup key.
+    fn find(&self, records: Range<usize>, key: &str) -> Option<Info> {
+        let (mut low, mut high) = (records.start, records.end);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.field(mid, 0).cmp(key) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => {
+                    let weight = read_u32(self.bytes.as_ref(), record_start(mid) + FIELDS * 8);
+                    return Some(Info {
+                        extension: Cow::Owned(self.field(mid, 1).to_owned()),
+                        content_type: Cow::Owned(self.field(mid, 2).to_owned()),
+                        encoding: self.field(mid, 3).into(),
+                        weight: weight as u16,
+                        tags: self
+                            .field(mid, 4)
+                            .split(',')
+                            .filter(|tag| !tag.is_empty())
+                            .map(|tag| Cow::Owned(tag.to_owned()))
+                            .collect(),
+                        origin: match weight >> 16 {
+                            0 => Origin::Embedded,
+                            1 => Origin::System,
+                            _ => Origin::Custom,
+                        },
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a record field; the string table was validated in [`MappedDb::new`].
+    fn field(&self, record: usize, field: usize) -> &str {
+        let data = self.bytes.as_ref();
+        let strings_start = HEADER_LEN + (self.ext_count + self.content_type_count) * RECORD_LEN;
+        std::str::from_utf8(&data[strings_start..][span(data, record, field)]).unwrap_or_default()
+    }
+}
+
+#[cfg(all(feature = "mmap", not(target_os = "wasi")))]
+impl MappedDb<memmap2::Mmap> {
+    /// Memory-maps a database file written from [`Db::to_mapped_bytes`].
+    ///
+    /// The mapping is read-only and shared, so every process that opens the
+    /// same file uses the same physical pages. The file must not be modified
+    /// while it is mapped; replace it atomically (write and rename) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use minimime::mapped::MappedDb;
+    ///
+    /// let db = MappedDb::open("/var/cache/minimime.db").unwrap();
+    /// println!("{:?}", db.lookup_by_extension("pdf"));
+    /// ```
+    pub fn open(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only and the contents are validated before
+        // use; callers are told not to modify the file while it is mapped.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Self::new(map)
+    }
+}
+
+#[cfg(all(feature = "mmap", target_os = "wasi"))]
+impl MappedDb<Vec<u8>> {
+    /// Reads a database file written from [`Db::to_mapped_bytes`].
+    ///
+    /// WASI has no memory mapping, so the file is read into memory instead;
+    /// the lookups behave exactly as with a mapped file.
+    pub fn open(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        Self::new(std::fs::read(path)?)
+    }
+}
+
+/// Returns the byte range of a record field within the string table.
+fn span(data: &[u8], record: usize, field: usize) -> Range<usize> {
+    let at = record_start(record) + field * 8;
+    let start = read_u32(data, at) as usize;
+    start..start.saturating_add(read_u32(data, at + 4) as usize)
+}
+
+/// Returns the byte offset of a record.
+fn record_start(record: usize) -> usize {
+    HEADER_LEN + record * RECORD_LEN
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_round_trip() {
+        let db = Db::new().unwrap();
+        let mapped = MappedDb::new(db.to_mapped_bytes()).unwrap();
+        for (ext, info) in db.ext_db.iter() {
+            assert_eq!(mapped.lookup_by_extension(ext).as_ref(), Some(info));
+        }
+        for info in db.content_type_db.values() {
+            assert_eq!(
+                mapped.lookup_by_content_type(&info.content_type).as_ref(),
+                Some(info)
+            );
+        }
+        assert_eq!(
+            &*mapped.lookup_by_filename("a.PDF").unwrap().extension,
+            "pdf"
+        );
+        assert!(mapped.lookup_by_extension("frog").is_none());
+        assert!(mapped.lookup_by_content_type("something-fake").is_none());
+    }
+
+    #[test]
+    fn test_extension_case() {
+        let db = crate::testing::embedded_with("RPT application/x-rpt base64");
+        let mapped = MappedDb::new(db.to_mapped_bytes()).unwrap();
+        for query in ["rpt", "RPT", "Rpt", "PDF", "pdf", "Tar.GZ"] {
+            assert_eq!(
+                mapped.lookup_by_extension(query).as_ref(),
+                db.lookup_by_extension(query),
+                "{query}"
+            );
+        }
+        assert_eq!(
+            &*mapped.lookup_by_extension("rpt").unwrap().extension,
+            "RPT"
+        );
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_content_type_normalization() {
+        let db = Db::new().unwrap();
+        let mapped = MappedDb::new(db.to_mapped_bytes()).unwrap();
+        for query in [
+            "image/PNG",
+            "text/html; charset=utf-8",
+            " Text/HTML ;charset=UTF-8",
+            "image/jpg",
+            "application/vnd.handheld-entertainment+xml",
+            "application/x-frog",
+            "not a type",
+        ] {
+            assert_eq!(
+                mapped.lookup_by_content_type(query).as_ref(),
+                db.lookup_by_content_type(query),
+                "{query}"
+            );
+        }
+        assert_eq!(
+            &*mapped
+                .lookup_by_content_type("image/jpg")
+                .unwrap()
+                .content_type,
+            "image/jpeg"
+        );
+        assert!(mapped
+            .lookup_by_content_type("application/vnd.handheld-entertainment+xml")
+            .is_some());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_strings_are_shared() {
+        let db = Db::new().unwrap();
+        let bytes = db.to_mapped_bytes();
+        let naive: usize = db
+            .ext_db
+            .values()
+            .chain(db.content_type_db.values())
+            .map(|info| info.extension.len() + info.content_type.len() + info.encoding.len())
+            .sum();
+        let records = db.ext_db.len() + db.content_type_db.len();
+        let strings = bytes.len() - HEADER_LEN - records * RECORD_LEN;
+        assert!(strings < naive / 2);
+    }
+
+    #[test]
+    fn test_rejects_invalid_data() {
+        assert!(MappedDb::new(b"".as_slice()).is_err());
+        assert!(MappedDb::new(b"NOPE\x01\0\0\0\0\0\0\0\0\0\0\0".as_slice()).is_err());
+
+        let mut bytes = Db::new().unwrap().to_mapped_bytes();
+        bytes.truncate(bytes.len() - 1);
+        assert!(MappedDb::new(bytes).is_err());
+
+        let mut bytes = Db::new().unwrap().to_mapped_bytes();
+        bytes[4] = 1;
+        assert!(MappedDb::new(bytes).is_err());
+    }
+
+    #[cfg(all(feature = "mmap", feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_open() {
+        let path = std::env::temp_dir().join(format!("minimime-{}.db", std::process::id()));
+        std::fs::write(&path, Db::new().unwrap().to_mapped_bytes()).unwrap();
+        let mapped = MappedDb::open(&path).unwrap();
+        assert_eq!(
+            &*mapped.lookup_by_extension("json").unwrap().content_type,
+            "application/json"
+        );
+        drop(mapped);
+        std::fs::remove_file(path).unwrap();
+    }
+}
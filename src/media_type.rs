@@ -0,0 +1,657 @@
+//! Media type comparison with HTTP semantics.
+//!
+//! `Content-Type` header values are not plain strings: per RFC 9110 the type
+//! and subtype are case-insensitive and parameters such as `charset` do not
+//! change what the media type is. [`validate_content_type`] compares a
+//! received header against an expected type on those terms, so clients
+//! don't need ad-hoc string handling. With `std`, [`MediaType`] parses a
+//! value into its parts, parameters included.
+
+#[cfg(feature = "std")]
+use std::{fmt, str::FromStr};
+
+/// Checks whether a `Content-Type` header value denotes the expected type.
+///
+/// The type and subtype are compared case-insensitively, and parameters on
+/// either side are ignored. Malformed header values never match.
+///
+/// # Arguments
+///
+/// * `header_value` - The received `Content-Type` header value
+/// * `expected` - The expected media type, e.g. `application/json`
+///
+/// # Examples
+///
+/// ```
+/// use minimime::validate_content_type;
+///
+/// assert!(validate_content_type("Application/JSON; charset=utf-8", "application/json"));
+/// assert!(!validate_content_type("application/problem+json", "application/json"));
+/// ```
+pub fn validate_content_type(header_value: &str, expected: &str) -> bool {
+    match (parse(header_value), parse(expected)) {
+        (Some(actual), Some(expected)) => {
+            actual.0.eq_ignore_ascii_case(expected.0) && actual.1.eq_ignore_ascii_case(expected.1)
+        }
+        _ => false,
+    }
+}
+
+/// Like [`validate_content_type`], but also accepts structured syntax
+/// suffixes.
+///
+/// A subtype with a `+suffix` (RFC 6838) also matches the type its suffix
+/// names, so `application/problem+json` and `application/vnd.api+json`
+/// satisfy an expected `application/json`.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::media_type::validate_content_type_with_suffix;
+///
+/// assert!(validate_content_type_with_suffix("application/vnd.api+json", "application/json"));
+/// assert!(validate_content_type_with_suffix("image/svg+xml", "image/svg+xml"));
+/// assert!(!validate_content_type_with_suffix("application/problem+json", "text/json"));
+/// ```
+pub fn validate_content_type_with_suffix(header_value: &str, expected: &str) -> bool {
+    if validate_content_type(header_value, expected) {
+        return true;
+    }
+    let (Some((ty, subtype)), Some((expected_ty, expected_subtype))) =
+        (parse(header_value), parse(expected))
+    else {
+        return false;
+    };
+    ty.eq_ignore_ascii_case(expected_ty)
+        && subtype
+            .rsplit_once('+')
+            .is_some_and(|(_, suffix)| suffix.eq_ignore_ascii_case(expected_subtype))
+}
+
+/// Checks whether a content type matches a pattern with wildcards.
+///
+/// The pattern is an exact `type/subtype`, `type/*`, `*/*`, or a structured
+/// syntax suffix pattern `type/*+suffix` or `*/*+suffix`. Suffix patterns
+/// only match subtypes carrying the suffix: `application/*+json` matches
+/// `application/ld+json` but not `application/json`. As with
+/// [`validate_content_type`], comparisons are ASCII case-insensitive,
+/// parameters are ignored, and malformed values or patterns never match; nor
+/// does a `value` that is itself a wildcard.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::content_type_matches;
+///
+/// assert!(content_type_matches("image/*", "image/png"));
+/// assert!(content_type_matches("*/*", "application/pdf; name=a.pdf"));
+/// assert!(content_type_matches("application/*+json", "application/vnd.api+json"));
+/// assert!(!content_type_matches("application/*+json", "application/json"));
+/// assert!(!content_type_matches("image/*", "text/plain"));
+/// ```
+pub fn content_type_matches(pattern: &str, value: &str) -> bool {
+    let (Some((pattern_ty, pattern_subtype)), Some((ty, subtype))) = (parse(pattern), parse(value))
+    else {
+        return false;
+    };
+    if ty.contains('*') || subtype.contains('*') {
+        return false;
+    }
+    let ty_matches = match pattern_ty {
+        "*" => pattern_subtype.starts_with('*'),
+        pattern_ty => pattern_ty.eq_ignore_ascii_case(ty),
+    };
+    ty_matches
+        && match pattern_subtype.strip_prefix('*') {
+            Some("") => true,
+            Some(rest) => rest.strip_prefix('+').is_some_and(|suffix| {
+                !suffix.is_empty()
+                    && !suffix.contains(['*', '+'])
+                    && subtype
+                        .rsplit_once('+')
+                        .is_some_and(|(_, actual)| actual.eq_ignore_ascii_case(suffix))
+            }),
+            None => pattern_subtype.eq_ignore_ascii_case(subtype),
+        }
+}
+
+/// Checks a content type against a pattern of the crate's own tables, where
+/// a trailing `*` matches any rest (`application/x-font-*`), ignoring ASCII
+/// case.
+pub(crate) fn matches_prefix_pattern(pattern: &str, content_type: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => content_type
+            .get(..prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(prefix)),
+        None => content_type.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Legacy and alternate spellings of content types, with the type the
+/// database lists them under.
+#[cfg(feature = "std")]
+const ALIASES: &[(&str, &str)] = &[
+    ("application/acrobat", "application/pdf"),
+    ("application/csv", "text/csv"),
+    ("application/font-woff", "font/woff"),
+    ("application/font-woff2", "font/woff2"),
+    ("application/javascript", "text/javascript"),
+    ("application/vnd.rar", "application/x-rar-compressed"),
+    ("application/x-font-otf", "font/otf"),
+    ("application/x-font-ttf", "font/ttf"),
+    ("application/x-font-woff", "font/woff"),
+    ("application/x-gzip", "application/gzip"),
+    ("application/x-javascript", "text/javascript"),
+    ("application/x-json", "application/json"),
+    ("application/x-pdf", "application/pdf"),
+    ("application/x-rar", "application/x-rar-compressed"),
+    ("application/x-yaml", "text/x-yaml"),
+    ("application/x-zip", "application/zip"),
+    ("application/x-zip-compressed", "application/zip"),
+    ("application/yaml", "text/x-yaml"),
+    ("audio/mp3", "audio/mpeg"),
+    ("audio/mpeg3", "audio/mpeg"),
+    ("audio/vnd.wave", "audio/wav"),
+    ("audio/wave", "audio/wav"),
+    ("audio/x-flac", "audio/flac"),
+    ("audio/x-m4a", "audio/mp4"),
+    ("audio/x-mp3", "audio/mpeg"),
+    ("audio/x-mpeg", "audio/mpeg"),
+    ("audio/x-wav", "audio/wav"),
+    ("image/jpg", "image/jpeg"),
+    ("image/pjpeg", "image/jpeg"),
+    ("image/svg", "image/svg+xml"),
+    ("image/x-bmp", "image/bmp"),
+    ("image/x-icon", "image/vnd.microsoft.icon"),
+    ("image/x-ms-bmp", "image/bmp"),
+    ("image/x-png", "image/png"),
+    ("text/ecmascript", "text/javascript"),
+    ("text/json", "application/json"),
+    ("text/rtf", "application/rtf"),
+    ("text/x-csv", "text/csv"),
+    ("text/x-javascript", "text/javascript"),
+    ("text/x-json", "application/json"),
+    ("text/x-markdown", "text/markdown"),
+    ("text/yaml", "text/x-yaml"),
+];
+
+/// Returns the canonical type for a legacy or alternate spelling of a
+/// content type essence, ignoring ASCII case.
+#[cfg(feature = "std")]
+pub(crate) fn resolve_alias(essence: &str) -> Option<&'static str> {
+    ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(essence))
+        .map(|(_, canonical)| *canonical)
+}
+
+/// Returns the canonical spelling of a content type.
+///
+/// This is the lowercase `type/subtype`, without parameters, with legacy
+/// and alternate spellings replaced by the type the database lists: `image/jpg`
+/// becomes `image/jpeg`, `application/x-gzip` becomes `application/gzip`
+/// and `application/javascript` becomes `text/javascript` (RFC 9239).
+/// [`Db::lookup_by_content_type`](crate::Db::lookup_by_content_type)
+/// resolves the same spellings when the database has no entry of their own.
+///
+/// # Returns
+///
+/// * `Some(String)` with the canonical type
+/// * `None` if the value is not a media type
+///
+/// # Examples
+///
+/// ```
+/// use minimime::canonicalize;
+///
+/// assert_eq!(canonicalize("Image/JPG").as_deref(), Some("image/jpeg"));
+/// assert_eq!(canonicalize("text/HTML; charset=utf-8").as_deref(), Some("text/html"));
+/// assert_eq!(canonicalize("jpeg"), None);
+/// ```
+#[cfg(feature = "std")]
+pub fn canonicalize(content_type: &str) -> Option<String> {
+    let essence = essence(content_type)?;
+    Some(match resolve_alias(essence) {
+        Some(canonical) => canonical.to_string(),
+        None => essence.to_ascii_lowercase(),
+    })
+}
+
+/// Splits a media type into its type and subtype, dropping parameters.
+///
+/// Returns `None` unless both parts are non-empty tokens.
+fn parse(value: &str) -> Option<(&str, &str)> {
+    let essence = value.split(';').next().unwrap_or_default().trim();
+    let (ty, subtype) = essence.split_once('/')?;
+    (is_token(ty) && is_token(subtype)).then_some((ty, subtype))
+}
+
+/// Returns the `type/subtype` part of a media type, without parameters or
+/// surrounding whitespace.
+///
+/// Returns `None` unless both parts are non-empty tokens.
+#[cfg(feature = "std")]
+pub(crate) fn essence(value: &str) -> Option<&str> {
+    parse(value)?;
+    Some(value.split(';').next().unwrap_or_default().trim())
+}
+
+/// Checks for an RFC 9110 token.
+pub(crate) fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// A parsed media type, such as a `Content-Type` header value.
+///
+/// Parsing follows RFC 9110: the type, subtype and parameter names are
+/// case-insensitive and stored lowercase, parameter values may be quoted
+/// strings, and whitespace around `;` is ignored. The value of a `charset`
+/// parameter is lowercased as well, since charset names are
+/// case-insensitive; other values are kept as given. [`Display`] writes the
+/// normalized form, quoting values where needed.
+///
+/// [`Display`]: fmt::Display
+///
+/// # Examples
+///
+/// ```
+/// use minimime::MediaType;
+///
+/// let media_type: MediaType = "Text/HTML; Charset=\"UTF-8\"; boundary=x".parse().unwrap();
+/// assert_eq!(media_type.essence(), "text/html");
+/// assert_eq!(media_type.param("charset"), Some("utf-8"));
+/// assert_eq!(media_type.to_string(), "text/html; charset=utf-8; boundary=x");
+///
+/// let media_type = MediaType::parse("application/vnd.api+json").unwrap();
+/// assert_eq!(media_type.suffix(), Some("json"));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MediaType {
+    /// `type/subtype`, lowercase
+    essence: String,
+    /// Position of the `/` in `essence`
+    slash: usize,
+    /// Lowercase names with their values, in order of appearance; only the
+    /// `charset` value is lowercased, the others keep their case
+    params: Vec<(String, String)>,
+}
+
+#[cfg(feature = "std")]
+impl MediaType {
+    /// Parses a media type with optional parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MediaTypeError`] if the type or subtype is not a token,
+    /// or a parameter is not a `name=value` pair of a token and a token or
+    /// quoted string.
+    pub fn parse(value: &str) -> Result<MediaType, MediaTypeError> {
+        let error = || MediaTypeError {
+            value: value.to_string(),
+        };
+        let (essence, mut rest) = value.split_once(';').unwrap_or((value, ""));
+        let (ty, subtype) = parse(essence).ok_or_else(error)?;
+        let mut params = Vec::new();
+        loop {
+            rest = rest.trim_start_matches([' ', '\t', ';']);
+            if rest.is_empty() {
+                break;
+            }
+            let (name, after_name) = rest.split_once('=').ok_or_else(error)?;
+            if !is_token(name) {
+                return Err(error());
+            }
+            let (param_value, after_value) = if let Some(quoted) = after_name.strip_prefix('"') {
+                unquote(quoted).ok_or_else(error)?
+            } else {
+                let end = after_name.find(';').unwrap_or(after_name.len());
+                let token = after_name[..end].trim_end_matches([' ', '\t']);
+                if !is_token(token) {
+                    return Err(error());
+                }
+                (token.to_string(), &after_name[end..])
+            };
+            let after_value = after_value.trim_start_matches([' ', '\t']);
+            if !after_value.is_empty() && !after_value.starts_with(';') {
+                return Err(error());
+            }
+            let name = name.to_ascii_lowercase();
+            let param_value = if name == "charset" {
+                param_value.to_ascii_lowercase()
+            } else {
+                param_value
+            };
+            params.push((name, param_value));
+            rest = after_value;
+        }
+        Ok(MediaType {
+            essence: format!("{ty}/{subtype}").to_ascii_lowercase(),
+            slash: ty.len(),
+            params,
+        })
+    }
+
+    /// Returns the lowercase type, such as `text` for `text/html`.
+    pub fn ty(&self) -> &str {
+        &self.essence[..self.slash]
+    }
+
+    /// Returns the lowercase subtype, such as `html` for `text/html`.
+    pub fn subtype(&self) -> &str {
+        &self.essence[self.slash + 1..]
+    }
+
+    /// Returns the structured syntax suffix of the subtype (RFC 6838), such
+    /// as `json` for `application/vnd.api+json`.
+    pub fn suffix(&self) -> Option<&str> {
+        self.subtype().rsplit_once('+').map(|(_, suffix)| suffix)
+    }
+
+    /// Returns the lowercase `type/subtype`, without parameters, as used to
+    /// look up the media type in a database.
+    pub fn essence(&self) -> &str {
+        &self.essence
+    }
+
+    /// Returns the value of a parameter; the name is compared
+    /// case-insensitively. If the parameter is repeated, the first value
+    /// wins.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| &**value)
+    }
+
+    /// Iterates over the parameters as `(name, value)` pairs, in order.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(name, value)| (&**name, &**value))
+    }
+}
+
+/// Parses the rest of a quoted string after its opening quote, returning the
+/// unescaped content and what follows the closing quote.
+#[cfg(feature = "std")]
+fn unquote(quoted: &str) -> Option<(String, &str)> {
+    let mut content = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((content, &quoted[i + 1..])),
+            '\\' => content.push(chars.next()?.1),
+            c => content.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for MediaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.essence)?;
+        for (name, value) in &self.params {
+            if is_token(value) {
+                write!(f, "; {name}={value}")?;
+            } else {
+                write!(f, "; {name}=\"")?;
+                for c in value.chars() {
+                    if matches!(c, '"' | '\\') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                f.write_str("\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for MediaType {
+    type Err = MediaTypeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        MediaType::parse(value)
+    }
+}
+
+/// Error returned by [`MediaType::parse`] for a malformed media type.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaTypeError {
+    /// The offending value
+    pub value: String,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for MediaTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed media type: {:?}", self.value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MediaTypeError {}
+
+/// Serializes as the string form, e.g. `"text/html; charset=utf-8"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MediaType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes from the string form, rejecting malformed media types.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MediaType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        MediaType::parse(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_aliases() {
+        assert!(ALIASES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(ALIASES
+            .iter()
+            .all(|(alias, canonical)| resolve_alias(canonical).is_none() && alias != canonical));
+        assert_eq!(resolve_alias("IMAGE/X-PNG"), Some("image/png"));
+        assert_eq!(resolve_alias("image/png"), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(
+            canonicalize(" application/x-zip-compressed ; name=a.zip").as_deref(),
+            Some("application/zip")
+        );
+        assert_eq!(
+            canonicalize("application/JavaScript").as_deref(),
+            Some("text/javascript")
+        );
+        assert_eq!(
+            canonicalize("application/x-unknown").as_deref(),
+            Some("application/x-unknown")
+        );
+        assert_eq!(canonicalize("image/"), None);
+    }
+
+    #[test]
+    fn test_content_type_matches() {
+        for (pattern, value) in [
+            ("image/png", "IMAGE/PNG"),
+            ("image/*", "image/webp; q=1"),
+            ("Image/*", "image/svg+xml"),
+            ("*/*", "text/plain"),
+            ("application/*+json", "application/ld+json"),
+            ("*/*+xml", "image/svg+xml"),
+            ("*/*+XML", "application/atom+xml"),
+            (" text/* ; charset=utf-8", "text/html"),
+        ] {
+            assert!(content_type_matches(pattern, value), "{pattern} {value}");
+        }
+        for (pattern, value) in [
+            ("image/*", "text/plain"),
+            ("application/*+json", "application/json"),
+            ("application/*+json", "text/x-report+json"),
+            ("*/*+zip", "application/zip"),
+            ("*/png", "image/png"),
+            ("image/png*", "image/png"),
+            ("image/*+", "image/svg+"),
+            ("*/*", "*/*"),
+            ("image/*", "image/*"),
+            ("image/*", "image"),
+            ("", "image/png"),
+        ] {
+            assert!(!content_type_matches(pattern, value), "{pattern} {value}");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_media_type_serde() {
+        let media_type = MediaType::parse("Text/HTML; Charset=UTF-8").unwrap();
+        let json = serde_json::to_string(&media_type).unwrap();
+        assert_eq!(json, r#""text/html; charset=utf-8""#);
+        assert_eq!(
+            serde_json::from_str::<MediaType>(&json).unwrap(),
+            media_type
+        );
+        let err = serde_json::from_str::<MediaType>(r#""text""#).unwrap_err();
+        assert!(err.to_string().contains("malformed media type"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_content_type() {
+        assert!(validate_content_type(
+            "application/json",
+            "application/json"
+        ));
+        assert!(validate_content_type("TEXT/Html", "text/html"));
+        assert!(validate_content_type(
+            " text/html ; charset=\"utf-8\"",
+            "text/html;charset=iso-8859-1"
+        ));
+        assert!(!validate_content_type("text/plain", "text/html"));
+        assert!(!validate_content_type("text/html", "text/htm"));
+        assert!(!validate_content_type(
+            "application/ld+json",
+            "application/json"
+        ));
+    }
+
+    #[test]
+    fn test_malformed_values() {
+        for value in [
+            "",
+            "text",
+            "text/",
+            "/html",
+            "text/ html",
+            "te xt/html",
+            "text/html/x",
+        ] {
+            assert!(!validate_content_type(value, "text/html"), "{value:?}");
+        }
+        assert!(!validate_content_type("text/html", "garbage"));
+    }
+
+    #[test]
+    fn test_suffix_matching() {
+        assert!(validate_content_type_with_suffix(
+            "application/json",
+            "application/json"
+        ));
+        assert!(validate_content_type_with_suffix(
+            "application/problem+JSON; charset=utf-8",
+            "application/json"
+        ));
+        assert!(validate_content_type_with_suffix(
+            "image/svg+xml",
+            "image/xml"
+        ));
+        assert!(!validate_content_type_with_suffix(
+            "application/json",
+            "application/ld+json"
+        ));
+        assert!(!validate_content_type_with_suffix(
+            "text/x+json",
+            "application/json"
+        ));
+        assert!(!validate_content_type_with_suffix(
+            "application/jsonx",
+            "application/json"
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_media_type_parse() {
+        let media_type =
+            MediaType::parse(" multipart/Form-Data ;boundary=\"a; \\\"b\" ; ;CHARSET=UTF-8 ")
+                .unwrap();
+        assert_eq!(media_type.ty(), "multipart");
+        assert_eq!(media_type.subtype(), "form-data");
+        assert_eq!(media_type.suffix(), None);
+        assert_eq!(media_type.param("Boundary"), Some("a; \"b"));
+        assert_eq!(media_type.param("charset"), Some("utf-8"));
+        assert_eq!(media_type.param("missing"), None);
+        assert_eq!(
+            media_type.params().collect::<Vec<_>>(),
+            [("boundary", "a; \"b"), ("charset", "utf-8")]
+        );
+        assert_eq!(
+            media_type.to_string(),
+            "multipart/form-data; boundary=\"a; \\\"b\"; charset=utf-8"
+        );
+        assert_eq!(media_type.to_string().parse(), Ok(media_type));
+
+        let media_type: MediaType = "image/SVG+xml".parse().unwrap();
+        assert_eq!(media_type.essence(), "image/svg+xml");
+        assert_eq!(media_type.suffix(), Some("xml"));
+        assert_eq!(media_type.params().count(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_media_type_errors() {
+        for value in [
+            "",
+            "text",
+            "text/ html",
+            "text/html; charset",
+            "text/html; =utf-8",
+            "text/html; charset=",
+            "text/html; charset=utf 8",
+            "text/html; charset=\"utf-8",
+            "text/html; charset=\"utf-8\"x",
+        ] {
+            assert_eq!(
+                MediaType::parse(value),
+                Err(MediaTypeError {
+                    value: value.to_string()
+                }),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_essence() {
+        assert_eq!(essence(" Text/HTML ; charset=utf-8"), Some("Text/HTML"));
+        assert_eq!(essence("text/html"), Some("text/html"));
+        assert_eq!(essence("text; charset=utf-8"), None);
+    }
+}
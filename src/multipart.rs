@@ -0,0 +1,375 @@
+//! Inspection of streamed `multipart/form-data` uploads.
+//!
+//! A proxy that must not buffer uploads can still check what each part
+//! contains. It drives an [`Inspector`] from its multipart parser: announce
+//! each part with its headers, pass the part's body chunks through as they
+//! arrive, and collect one [`PartReport`] per part at the end of the stream.
+//! Only the first [`PREFIX_LEN`](crate::sniff::PREFIX_LEN) bytes of each part
+//! are kept, for sniffing with a [`Sniffer`].
+//!
+//! Upload endpoints that get each part whole can decide on it in one call
+//! with [`validate_part`], against an allow-list such as `image/*` and
+//! `application/pdf`.
+
+use crate::{
+    detection::{Source, VerifyResult},
+    policy::TypePolicy,
+    sniff::Sniffer,
+    Db, Info,
+};
+
+/// Whether [`validate_part`] accepts a part.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartDecision {
+    /// The part's type is allowed; holds its entry
+    Accept(Info),
+    /// The part is rejected
+    Reject(Rejection),
+}
+
+impl PartDecision {
+    /// Whether the part was accepted.
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, PartDecision::Accept(_))
+    }
+}
+
+/// Why [`validate_part`] rejected a part.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    /// Neither the content nor the file name tells the part's type
+    Unknown,
+    /// The declared content type or the file name contradicts the part's
+    /// type; holds the type's entry
+    Mismatch(Info),
+    /// The part's type is not allowed; holds its entry
+    NotAllowed(Info),
+}
+
+/// Decides whether to accept an uploaded part, from its declared content
+/// type, file name and first bytes, any of which may be missing.
+///
+/// The part's type is found as by [`Db::detect`]: the sniffed content wins,
+/// and a generic container such as ZIP defers to a file name such as
+/// `.docx`. The part is rejected if neither the content nor the file name
+/// identify it, since a declared type alone is the client's word, if a
+/// declared type or the file name contradicts it, if the file name's type
+/// has a signature the content lacks (see [`Db::verify`]), or if `allowed`
+/// doesn't allow it. A declared `application/octet-stream` makes no claim.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{
+///     multipart::{validate_part, PartDecision, Rejection},
+///     policy::TypePolicy,
+///     Db,
+/// };
+///
+/// let db = Db::new().unwrap();
+/// let allowed = TypePolicy::allow_list(["image/*", "application/pdf"]).unwrap();
+/// let webp = b"RIFF\x24\0\0\0WEBPVP8 ";
+///
+/// let decision = validate_part(&db, &allowed, Some("image/webp"), Some("cat.webp"), webp);
+/// assert!(decision.is_accepted());
+///
+/// // A script posing as an image.
+/// let decision = validate_part(&db, &allowed, Some("image/png"), Some("x.png"), b"<?php");
+/// assert!(matches!(decision, PartDecision::Reject(Rejection::Mismatch(_))));
+///
+/// let decision = validate_part(&db, &allowed, None, Some("a.zip"), b"PK\x03\x04");
+/// assert!(matches!(decision, PartDecision::Reject(Rejection::NotAllowed(_))));
+/// ```
+pub fn validate_part(
+    db: &Db,
+    allowed: &TypePolicy,
+    declared_type: Option<&str>,
+    filename: Option<&str>,
+    bytes: &[u8],
+) -> PartDecision {
+    let detection = db.detect(filename, Some(bytes), declared_type);
+    let info = match detection.info {
+        Some(info) if detection.source != Some(Source::Declared) => info,
+        _ => return PartDecision::Reject(Rejection::Unknown),
+    };
+    let missing_signature = filename.is_some_and(|filename| {
+        matches!(
+            db.verify(filename, bytes),
+            VerifyResult::MissingSignature { .. }
+        )
+    });
+    if detection.mismatch || missing_signature {
+        PartDecision::Reject(Rejection::Mismatch(info))
+    } else if !allowed.allows(&info) {
+        PartDecision::Reject(Rejection::NotAllowed(info))
+    } else {
+        PartDecision::Accept(info)
+    }
+}
+
+/// What was found in one part of a multipart stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartReport<'a> {
+    /// The `name` parameter of the part's `Content-Disposition`
+    pub name: Option<String>,
+    /// The `filename` parameter of the part's `Content-Disposition`
+    pub filename: Option<String>,
+    /// The part's `Content-Type` header, as sent by the client
+    pub declared_content_type: Option<String>,
+    /// Entry for the file name's extension
+    pub by_filename: Option<&'a Info>,
+    /// Entry for the content type sniffed from the part's leading bytes
+    pub sniffed: Option<&'a Info>,
+    /// Size of the part's body in bytes
+    pub size: u64,
+    /// Whether a signal contradicts the sniffed entry, set when the part ends
+    mismatch: bool,
+}
+
+impl<'a> PartReport<'a> {
+    /// Returns the best available entry for the part: the sniffed one, or
+    /// else the one for the file name.
+    pub fn detected(&self) -> Option<&'a Info> {
+        self.sniffed.or(self.by_filename)
+    }
+
+    /// Checks whether the part claims to be something other than what its
+    /// content is.
+    ///
+    /// This is the case when the content was sniffed and either the declared
+    /// content type or the file name's type disagrees with it, as decided by
+    /// [`Db::detect`]: a `.webm` name agrees with WebM video, and a `.docx`
+    /// name with the ZIP container it is built on. A declared
+    /// `application/octet-stream` makes no claim.
+    pub fn mismatches(&self) -> bool {
+        self.mismatch
+    }
+}
+
+/// Builds [`PartReport`]s from a multipart stream fed part by part.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{multipart::Inspector, Db};
+///
+/// let db = Db::new().unwrap();
+/// let mut inspector = Inspector::new(&db);
+///
+/// inspector.begin_part(Some("avatar"), Some("me.jpg"), Some("image/jpeg"));
+/// inspector.feed(b"RIFF\x24\0\0\0");
+/// inspector.feed(b"WEBPVP8 ...rest of the image...");
+///
+/// inspector.begin_part(Some("comment"), None, None);
+/// inspector.feed(b"hello");
+///
+/// let reports = inspector.finish();
+/// assert_eq!(&*reports[0].detected().unwrap().content_type, "image/webp");
+/// assert!(reports[0].mismatches());
+/// assert_eq!(reports[1].size, 5);
+/// assert!(reports[1].detected().is_none());
+/// ```
+#[derive(Debug)]
+pub struct Inspector<'a> {
+    db: &'a Db,
+    reports: Vec<PartReport<'a>>,
+    /// The open part, if any
+    current: Option<PartReport<'a>>,
+    sniffer: Box<Sniffer>,
+}
+
+impl<'a> Inspector<'a> {
+    /// Creates an inspector resolving types through `db`.
+    pub fn new(db: &'a Db) -> Self {
+        Inspector {
+            db,
+            reports: Vec::new(),
+            current: None,
+            sniffer: Box::default(),
+        }
+    }
+
+    /// Starts a new part, ending the open one if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The `name` parameter of the part's `Content-Disposition`
+    /// * `filename` - The `filename` parameter of the part's
+    ///   `Content-Disposition`
+    /// * `content_type` - The part's `Content-Type` header
+    pub fn begin_part(
+        &mut self,
+        name: Option<&str>,
+        filename: Option<&str>,
+        content_type: Option<&str>,
+    ) {
+        self.end_part();
+        self.current = Some(PartReport {
+            name: name.map(str::to_string),
+            filename: filename.map(str::to_string),
+            declared_content_type: content_type.map(str::to_string),
+            by_filename: filename.and_then(|filename| self.db.lookup_by_filename(filename)),
+            sniffed: None,
+            size: 0,
+            mismatch: false,
+        });
+    }
+
+    /// Feeds the next chunk of the open part's body.
+    ///
+    /// Chunks fed outside a part, such as a preamble, are ignored.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if self.current.is_some() {
+            self.sniffer.feed(chunk);
+        }
+    }
+
+    /// Ends the open part and records its report.
+    pub fn end_part(&mut self) {
+        let Some(mut report) = self.current.take() else {
+            return;
+        };
+        report.sniffed = self.db.lookup_by_magic(self.sniffer.prefix());
+        report.mismatch = report.sniffed.is_some()
+            && self
+                .db
+                .detect_entries(
+                    report.by_filename,
+                    report.sniffed,
+                    report.declared_content_type.as_deref(),
+                )
+                .mismatch;
+        report.size = self.sniffer.bytes_fed();
+        *self.sniffer = Sniffer::new();
+        self.reports.push(report);
+    }
+
+    /// Ends the stream and returns the reports of all parts, in order.
+    pub fn finish(mut self) -> Vec<PartReport<'a>> {
+        self.end_part();
+        self.reports
+    }
+}
+
+#[cfg(all(test, feature = "ext-db", feature = "content-type-db"))]
+mod tests {
+    use super::*;
+
+    const WEBP: &[u8] = b"RIFF\x24\0\0\0WEBPVP8 ";
+
+    #[test]
+    fn test_reports() {
+        let db = Db::new().unwrap();
+        let mut inspector = Inspector::new(&db);
+        inspector.feed(b"preamble is ignored");
+        inspector.begin_part(Some("photo"), Some("cat.webp"), Some("image/webp"));
+        for byte in WEBP {
+            inspector.feed(&[*byte]);
+        }
+        inspector.end_part();
+        inspector.feed(b"between parts");
+        inspector.begin_part(Some("doc"), Some("report.pdf"), None);
+        inspector.feed(b"not a pdf at all");
+        let reports = inspector.finish();
+
+        assert_eq!(reports.len(), 2);
+        let photo = &reports[0];
+        assert_eq!(photo.name.as_deref(), Some("photo"));
+        assert_eq!(photo.size, WEBP.len() as u64);
+        assert_eq!(photo.sniffed, db.lookup_by_extension("webp"));
+        assert!(!photo.mismatches());
+
+        let doc = &reports[1];
+        assert_eq!(doc.size, 16);
+        assert_eq!(doc.sniffed, None);
+        assert_eq!(doc.detected(), db.lookup_by_extension("pdf"));
+        assert!(!doc.mismatches(), "nothing sniffed, nothing to contradict");
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let db = Db::new().unwrap();
+        let report = |filename, content_type| {
+            let mut inspector = Inspector::new(&db);
+            inspector.begin_part(None, filename, content_type);
+            inspector.feed(WEBP);
+            inspector.finish().remove(0)
+        };
+        assert!(!report(None, None).mismatches());
+        assert!(!report(None, Some("application/octet-stream")).mismatches());
+        assert!(!report(Some("a.WEBP"), Some("Image/WebP; x=y")).mismatches());
+        assert!(report(None, Some("application/pdf")).mismatches());
+        assert!(report(Some("a.pdf"), None).mismatches());
+
+        let report = |filename, content_type, bytes: &[u8]| {
+            let mut inspector = Inspector::new(&db);
+            inspector.begin_part(None, Some(filename), content_type);
+            inspector.feed(bytes);
+            inspector.finish().remove(0)
+        };
+        let webm = b"\x1A\x45\xDF\xA3\x9F\x42\x82\x84webm";
+        assert!(!report("clip.webm", Some("video/webm"), webm).mismatches());
+        assert!(report("clip.mp4", None, webm).mismatches());
+        let docx = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+        let zip = b"PK\x03\x04\x14\0\0\0";
+        assert!(!report("report.docx", Some(docx), zip).mismatches());
+        assert!(!report("report.docx", None, zip).mismatches());
+        assert!(report("report.docx", Some("application/pdf"), zip).mismatches());
+    }
+
+    #[test]
+    fn test_validate_part() {
+        let db = Db::new().unwrap();
+        let allowed = TypePolicy::allow_list(["image/*", "application/pdf", "text/csv"]).unwrap();
+        let validate =
+            |declared, filename, bytes| validate_part(&db, &allowed, declared, filename, bytes);
+
+        assert_eq!(
+            validate(None, None, WEBP),
+            PartDecision::Accept(db.lookup_by_extension("webp").unwrap().clone())
+        );
+        assert!(validate(Some("application/octet-stream"), Some("a.webp"), WEBP).is_accepted());
+        assert!(validate(Some("text/csv"), Some("rows.csv"), b"a,b\n1,2\n").is_accepted());
+        assert!(validate(None, Some("rows.csv"), b"From the desk of the CEO\n").is_accepted());
+        assert_eq!(
+            validate(Some("text/csv"), None, b"a,b\n"),
+            PartDecision::Reject(Rejection::Unknown),
+            "a declared type alone is not trusted"
+        );
+        assert_eq!(
+            validate(None, Some("noext"), b"???"),
+            PartDecision::Reject(Rejection::Unknown)
+        );
+        assert!(matches!(
+            validate(Some("application/pdf"), None, WEBP),
+            PartDecision::Reject(Rejection::Mismatch(_))
+        ));
+        assert!(matches!(
+            validate(None, Some("a.gif"), b"GIF87a\x01\0"),
+            PartDecision::Accept(_)
+        ));
+        assert!(matches!(
+            validate(None, Some("a.gif"), b"<script>"),
+            PartDecision::Reject(Rejection::Mismatch(_))
+        ));
+        assert!(matches!(
+            validate(None, Some("page.html"), b"<html>"),
+            PartDecision::Reject(Rejection::NotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_large_part() {
+        let db = Db::new().unwrap();
+        let mut inspector = Inspector::new(&db);
+        inspector.begin_part(None, None, None);
+        inspector.feed(WEBP);
+        let chunk = [0u8; 4096];
+        for _ in 0..1024 {
+            inspector.feed(&chunk);
+        }
+        let report = inspector.finish().remove(0);
+        assert_eq!(report.size, WEBP.len() as u64 + 4096 * 1024);
+        assert_eq!(report.sniffed, db.lookup_by_extension("webp"));
+    }
+}
@@ -0,0 +1,407 @@
+//! Content negotiation against an `Accept` header.
+//!
+//! A server that can respond in several formats picks the one the client
+//! prefers with [`content_type`]. Because the response then depends on the
+//! request's `Accept` header, the result also carries the `Vary` header
+//! contribution that keeps shared caches from serving one client's
+//! representation to another.
+//!
+//! Static file servers can also pick a pre-compressed sibling of a requested
+//! file (`app.js.br`, `app.js.gz`) from `Accept-Encoding` with
+//! [`precompressed`], which keeps the content type of the uncompressed file.
+//!
+//! On the client side, [`accept_header`] builds the `Accept` header that
+//! asks for formats in order of preference.
+
+use crate::{Db, Info};
+
+/// Header name to add to `Vary` when the response depends on `Accept`.
+pub const VARY_ACCEPT: &str = "Accept";
+/// Header name to add to `Vary` when the response depends on `Accept-Encoding`.
+pub const VARY_ACCEPT_ENCODING: &str = "Accept-Encoding";
+
+/// Pre-compressed file suffixes and their `Content-Encoding`, in the order
+/// they are preferred when the client accepts several equally.
+const PRECOMPRESSED: &[(&str, &str)] = &[(".br", "br"), (".zst", "zstd"), (".gz", "gzip")];
+
+/// The outcome of negotiating a representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Negotiated<'a> {
+    /// The chosen content type, or `None` if the client accepts none of the
+    /// available ones (typically answered with `406 Not Acceptable`)
+    pub content_type: Option<&'a str>,
+    /// Header name to append to the response's `Vary` header, if any.
+    ///
+    /// This is `Some("Accept")` whenever more than one representation was
+    /// available, even if the client sent no `Accept` header or nothing
+    /// matched: another request could have received a different response.
+    pub vary: Option<&'static str>,
+}
+
+/// Chooses the best available content type for an `Accept` header.
+///
+/// Each available type gets the quality (`q`) of the most specific media
+/// range matching it (`text/html` over `text/*` over `*/*`). The type with
+/// the highest non-zero quality wins; ties go to the earliest entry in
+/// `available`, so list representations in the server's order of
+/// preference. An empty `Accept` header (or a missing one, passed as `""`)
+/// accepts everything.
+///
+/// # Arguments
+///
+/// * `accept` - The value of the request's `Accept` header
+/// * `available` - The content types the server can produce
+///
+/// # Returns
+///
+/// A [`Negotiated`] holding the chosen content type and the `Vary` header
+/// contribution
+///
+/// # Examples
+///
+/// ```
+/// use minimime::negotiate;
+///
+/// let available = ["application/json", "text/html"];
+/// let negotiated = negotiate::content_type("text/html, */*;q=0.8", &available);
+/// assert_eq!(negotiated.content_type, Some("text/html"));
+/// assert_eq!(negotiated.vary, Some("Accept"));
+/// ```
+pub fn content_type<'a>(accept: &str, available: &[&'a str]) -> Negotiated<'a> {
+    let mut best: Option<(&'a str, f32)> = None;
+    for &candidate in available {
+        let q = quality(accept, candidate);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((candidate, q));
+        }
+    }
+    Negotiated {
+        content_type: best.map(|(content_type, _)| content_type),
+        vary: (available.len() > 1).then_some(VARY_ACCEPT),
+    }
+}
+
+/// Builds an `Accept` header value asking for formats in order of
+/// preference.
+///
+/// Each format is a content type, or an extension (with or without the
+/// leading dot) resolved through `db`; extensions `db` does not know are
+/// skipped, as are repeated content types. The first format gets the
+/// implicit quality 1 and each following one a lower `q`, in steps of 0.1
+/// for up to ten formats and smaller steps beyond that, so that every
+/// format keeps a non-zero quality.
+///
+/// # Arguments
+///
+/// * `db` - The database to resolve extensions with
+/// * `preferred` - Extensions or content types, most preferred first
+///
+/// # Returns
+///
+/// The header value, or an empty string if no format resolved
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{negotiate, Db};
+///
+/// let db = Db::new().unwrap();
+/// let accept = negotiate::accept_header(&db, &["json", "text/html", ".txt"]);
+/// assert_eq!(accept, "application/json, text/html;q=0.9, text/plain;q=0.8");
+/// ```
+pub fn accept_header(db: &Db, preferred: &[&str]) -> String {
+    let mut content_types: Vec<&str> = Vec::with_capacity(preferred.len());
+    for &format in preferred {
+        let content_type = if format.contains('/') {
+            format
+        } else {
+            match db.lookup_by_extension(format.trim_start_matches('.')) {
+                Some(info) => &info.content_type,
+                None => continue,
+            }
+        };
+        if !content_types
+            .iter()
+            .any(|seen| seen.eq_ignore_ascii_case(content_type))
+        {
+            content_types.push(content_type);
+        }
+    }
+
+    // Quality in thousandths, the precision RFC 9110 allows.
+    let step = match content_types.len() {
+        0 | 1 => 0,
+        n => (999 / (n - 1)).min(100),
+    };
+    let mut header = String::new();
+    for (i, content_type) in content_types.iter().enumerate() {
+        if i > 0 {
+            header.push_str(", ");
+        }
+        header.push_str(content_type);
+        if i > 0 {
+            let q = format!("{:03}", 1000 - i * step);
+            header.push_str(";q=0.");
+            header.push_str(q.trim_end_matches('0'));
+        }
+    }
+    header
+}
+
+/// The file to serve for a request, chosen by [`precompressed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precompressed<'a> {
+    /// Path of the file to serve: the requested path, or a compressed sibling
+    pub path: String,
+    /// Entry for the requested (uncompressed) file, used for `Content-Type`
+    pub info: Option<&'a Info>,
+    /// Value for the `Content-Encoding` header, if a compressed sibling is served
+    pub content_encoding: Option<&'static str>,
+    /// Header name to append to the response's `Vary` header, if any.
+    ///
+    /// This is `Some("Accept-Encoding")` whenever a compressed sibling exists,
+    /// even if it was not chosen for this request.
+    pub vary: Option<&'static str>,
+}
+
+/// Resolves the pre-compressed sibling of a file to serve.
+///
+/// For a request of `app.js`, the siblings `app.js.br`, `app.js.zst` and
+/// `app.js.gz` are considered in that order, and the first one that exists
+/// and whose coding the `Accept-Encoding` header accepts is served. The
+/// content type always comes from the requested file, so `app.js.gz` is
+/// served as `text/javascript` with `Content-Encoding: gzip` rather than as
+/// `application/gzip`.
+///
+/// # Arguments
+///
+/// * `db` - The database to look the requested file up in
+/// * `path` - The requested file path
+/// * `accept_encoding` - The value of the request's `Accept-Encoding` header
+///   (`""` if missing, which only accepts the uncompressed file)
+/// * `exists` - Reports whether a sibling path exists, e.g.
+///   `|path| Path::new(path).is_file()`
+///
+/// # Returns
+///
+/// A [`Precompressed`] describing the file to serve and the headers to set
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{negotiate, Db};
+///
+/// let db = Db::new().unwrap();
+/// let on_disk = ["app.js", "app.js.gz"];
+/// let variant =
+///     negotiate::precompressed(&db, "app.js", "gzip, deflate", |p| on_disk.contains(&p));
+/// assert_eq!(variant.path, "app.js.gz");
+/// assert_eq!(variant.content_encoding, Some("gzip"));
+/// assert_eq!(&*variant.info.unwrap().content_type, "text/javascript");
+/// ```
+pub fn precompressed<'a>(
+    db: &'a Db,
+    path: &str,
+    accept_encoding: &str,
+    mut exists: impl FnMut(&str) -> bool,
+) -> Precompressed<'a> {
+    let mut variant = Precompressed {
+        path: path.to_string(),
+        info: db.lookup_by_filename(path),
+        content_encoding: None,
+        vary: None,
+    };
+    let mut best_q = 0.0;
+    for &(suffix, coding) in PRECOMPRESSED {
+        let sibling = format!("{path}{suffix}");
+        if !exists(&sibling) {
+            continue;
+        }
+        variant.vary = Some(VARY_ACCEPT_ENCODING);
+        let q = coding_quality(accept_encoding, coding);
+        if q > best_q {
+            best_q = q;
+            variant.path = sibling;
+            variant.content_encoding = Some(coding);
+        }
+    }
+    variant
+}
+
+/// Returns the quality an `Accept-Encoding` header assigns to a coding.
+fn coding_quality(accept_encoding: &str, coding: &str) -> f32 {
+    // (exact match, q) of the most specific matching coding.
+    let mut best: Option<(bool, f32)> = None;
+    for entry in accept_encoding.split(',') {
+        let mut params = entry.split(';');
+        let name = params.next().unwrap_or_default().trim();
+        let exact = if name.eq_ignore_ascii_case(coding)
+            || (coding == "gzip" && name.eq_ignore_ascii_case("x-gzip"))
+        {
+            true
+        } else if name == "*" {
+            false
+        } else {
+            continue;
+        };
+        let Some(q) = q_value(params) else {
+            continue;
+        };
+        if best.is_none_or(|(best_exact, _)| exact && !best_exact) {
+            best = Some((exact, q));
+        }
+    }
+    best.map_or(0.0, |(_, q)| q)
+}
+
+/// Reads the `q` parameter of a header entry, defaulting to 1.
+///
+/// Returns `None` if the value is malformed.
+fn q_value<'a>(params: impl Iterator<Item = &'a str>) -> Option<f32> {
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+        .map_or(Some(1.0), |(_, value)| value.trim().parse::<f32>().ok())
+        .map(|q| q.clamp(0.0, 1.0))
+}
+
+/// Returns the quality an `Accept` header assigns to a content type.
+fn quality(accept: &str, content_type: &str) -> f32 {
+    if accept.trim().is_empty() {
+        return 1.0;
+    }
+    let Some((ty, subtype)) = content_type.split_once('/') else {
+        return 0.0;
+    };
+
+    // (specificity, q) of the most specific matching range.
+    let mut best: Option<(u8, f32)> = None;
+    for range in accept.split(',') {
+        let mut params = range.split(';');
+        let Some((range_ty, range_subtype)) = params.next().unwrap_or_default().split_once('/')
+        else {
+            continue;
+        };
+        let (range_ty, range_subtype) = (range_ty.trim(), range_subtype.trim());
+        let specificity = if range_ty == "*" && range_subtype == "*" {
+            0
+        } else if !range_ty.eq_ignore_ascii_case(ty) {
+            continue;
+        } else if range_subtype == "*" {
+            1
+        } else if range_subtype.eq_ignore_ascii_case(subtype) {
+            2
+        } else {
+            continue;
+        };
+        let Some(q) = q_value(params) else {
+            continue;
+        };
+        if best.is_none_or(|(best_specificity, _)| specificity > best_specificity) {
+            best = Some((specificity, q));
+        }
+    }
+    best.map_or(0.0, |(_, q)| q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVAILABLE: [&str; 3] = ["application/json", "text/html", "text/plain"];
+
+    #[test]
+    fn test_content_type() {
+        let chosen = |accept| content_type(accept, &AVAILABLE).content_type;
+        assert_eq!(chosen("text/html"), Some("text/html"));
+        assert_eq!(chosen("text/*"), Some("text/html"));
+        assert_eq!(chosen("TEXT/Plain"), Some("text/plain"));
+        assert_eq!(chosen("text/*;q=0.5, text/plain"), Some("text/plain"));
+        assert_eq!(chosen("*/*;q=0.1, text/html;q=0.2"), Some("text/html"));
+        assert_eq!(chosen("*/*, application/json;q=0"), Some("text/html"));
+        assert_eq!(chosen(""), Some("application/json"));
+        assert_eq!(chosen("image/png"), None);
+        assert_eq!(chosen("text/html;q=0"), None);
+        assert_eq!(chosen("garbage, text/plain;q=nope"), None);
+    }
+
+    #[test]
+    fn test_vary() {
+        assert_eq!(content_type("text/html", &AVAILABLE).vary, Some("Accept"));
+        // Even without a match, another Accept could have picked a type.
+        assert_eq!(content_type("image/png", &AVAILABLE).vary, Some("Accept"));
+        assert_eq!(content_type("", &AVAILABLE).vary, Some("Accept"));
+        // A single representation is served regardless of Accept.
+        let only = ["text/html"];
+        assert_eq!(content_type("text/html", &only).vary, None);
+        assert_eq!(content_type("image/png", &only).vary, None);
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_accept_header() {
+        let db = Db::new().unwrap();
+        assert_eq!(accept_header(&db, &[]), "");
+        assert_eq!(accept_header(&db, &["png"]), "image/png");
+        assert_eq!(
+            accept_header(&db, &["webp", "frog", "image/avif", ".PNG", "image/WEBP"]),
+            "image/webp, image/avif;q=0.9, image/png;q=0.8"
+        );
+
+        let many: Vec<String> = (0..20).map(|i| format!("application/x-{i}")).collect();
+        let many: Vec<&str> = many.iter().map(String::as_str).collect();
+        let accept = accept_header(&db, &many);
+        assert!(accept.ends_with("application/x-19;q=0.012"), "{accept}");
+        assert!(accept.contains("application/x-1;q=0.948"), "{accept}");
+
+        // Negotiating against the generated header honours its order.
+        let accept = accept_header(&db, &["csv", "json", "html"]);
+        let available = ["text/html", "application/json"];
+        assert_eq!(
+            content_type(&accept, &available).content_type,
+            Some("application/json")
+        );
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_precompressed() {
+        let db = Db::new().unwrap();
+        let on_disk = ["app.js", "app.js.br", "app.js.gz", "style.css"];
+        let resolve = |path, accept_encoding| {
+            precompressed(&db, path, accept_encoding, |p| on_disk.contains(&p))
+        };
+
+        let variant = resolve("app.js", "gzip, deflate, br");
+        assert_eq!(variant.path, "app.js.br");
+        assert_eq!(variant.content_encoding, Some("br"));
+        assert_eq!(
+            variant.info,
+            db.lookup_by_extension("js"),
+            "content type comes from the inner extension"
+        );
+        assert_eq!(variant.vary, Some("Accept-Encoding"));
+
+        let variant = resolve("app.js", "br;q=0.5, gzip");
+        assert_eq!(variant.path, "app.js.gz");
+        assert_eq!(variant.content_encoding, Some("gzip"));
+
+        assert_eq!(resolve("app.js", "x-gzip").content_encoding, Some("gzip"));
+        assert_eq!(resolve("app.js", "*").content_encoding, Some("br"));
+        assert_eq!(
+            resolve("app.js", "*, br;q=0").content_encoding,
+            Some("gzip")
+        );
+
+        let variant = resolve("app.js", "");
+        assert_eq!(variant.path, "app.js");
+        assert_eq!(variant.content_encoding, None);
+        assert_eq!(variant.vary, Some("Accept-Encoding"));
+
+        let variant = resolve("style.css", "gzip, br");
+        assert_eq!(variant.path, "style.css");
+        assert_eq!(variant.content_encoding, None);
+        assert_eq!(variant.vary, None);
+        assert_eq!(&*variant.info.unwrap().content_type, "text/css");
+    }
+}
@@ -0,0 +1,310 @@
+//! Allocation-free normalization for the lookup hot path.
+//!
+//! Bulk classification spends most of its time before the hash lookup:
+//! finding the extension in a filename and folding its case. These helpers
+//! do both without allocating. The byte scans use `memchr` (SIMD-accelerated)
+//! when the `simd` feature is enabled, and case folding works on eight bytes
+//! at a time.
+
+use std::{borrow::Cow, path::Path};
+
+/// Longest string folded on the stack; longer ones fall back to allocating.
+///
+/// This covers any extension in a file name (at most 255 bytes on common
+/// file systems) and any content type (RFC 6838 caps both names at 127).
+pub(crate) const MAX_FOLDED_LEN: usize = 256;
+
+/// Returns the extension of a filename, matching [`Path::extension`].
+///
+/// The common case (a plain file name after the last separator) is handled
+/// with two reverse byte scans. Unusual names such as trailing separators
+/// or `.`/`..` components are delegated to [`Path`].
+pub(crate) fn extension(filename: &str) -> Option<&str> {
+    let name = file_name(filename);
+    if name.is_empty() || name == "." || name == ".." {
+        return Path::new(filename).extension()?.to_str();
+    }
+    match last_byte(b'.', name.as_bytes()) {
+        Some(dot) if dot > 0 => Some(&name[dot + 1..]),
+        _ => None,
+    }
+}
+
+/// Returns the compound extension of a filename: its last two dot-separated
+/// segments, such as `tar.gz` for `backup.tar.gz`.
+///
+/// Returns `None` unless a non-empty stem precedes both segments, so neither
+/// `a.gz` nor the hidden file `.tar.gz` has one, and for the unusual names
+/// [`extension`] delegates to [`Path`].
+pub(crate) fn compound_extension(filename: &str) -> Option<&str> {
+    let name = file_name(filename);
+    let dot = last_byte(b'.', name.as_bytes())?;
+    match last_byte(b'.', &name.as_bytes()[..dot]) {
+        Some(inner) if inner > 0 => Some(&name[inner + 1..]),
+        _ => None,
+    }
+}
+
+/// Returns the file name of a path: everything after the last separator.
+pub(crate) fn file_name(filename: &str) -> &str {
+    let start = last_separator(filename.as_bytes()).map_or(0, |i| i + 1);
+    &filename[start..]
+}
+
+/// Returns the file name in a URL: the last segment of its path,
+/// percent-decoded.
+///
+/// The query string and fragment are dropped, and so is the authority
+/// after `scheme://` (or a leading `//`), so `https://example.com` has no
+/// file name rather than a `com` one. Only names with a `%` escape
+/// allocate; escapes that don't decode to UTF-8 are replaced.
+pub(crate) fn url_file_name(url: &str) -> Cow<'_, str> {
+    let mut path = &url[..url.find(['?', '#']).unwrap_or(url.len())];
+    let after_authority = match path.find("://") {
+        Some(scheme_end) => Some(&path[scheme_end + 3..]),
+        None => path.strip_prefix("//"),
+    };
+    if let Some(rest) = after_authority {
+        path = rest.find('/').map_or("", |slash| &rest[slash..]);
+    }
+    percent_decode(&path[path.rfind('/').map_or(0, |slash| slash + 1)..])
+}
+
+/// Decodes `%XX` escapes, leaving malformed ones as they are.
+fn percent_decode(input: &str) -> Cow<'_, str> {
+    if !input.contains('%') {
+        return Cow::Borrowed(input);
+    }
+    let hex = |byte: Option<&u8>| byte.and_then(|&byte| (byte as char).to_digit(16));
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], hex(bytes.get(i + 1)), hex(bytes.get(i + 2))) {
+            (b'%', Some(high), Some(low)) => {
+                decoded.push((high * 16 + low) as u8);
+                i += 3;
+            }
+            (byte, ..) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// Lowercases the ASCII letters of a string, as [`str::to_ascii_lowercase`]
+/// does, folding into `buf`.
+///
+/// A string without uppercase ASCII letters is returned as it is. Other
+/// bytes, non-ASCII ones included, are kept, so only strings longer than
+/// [`MAX_FOLDED_LEN`] allocate.
+pub(crate) fn fold_ascii_lowercase<'a>(
+    input: &'a str,
+    buf: &'a mut [u8; MAX_FOLDED_LEN],
+) -> Cow<'a, str> {
+    let len = input.len();
+    if !input.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Cow::Borrowed(input);
+    }
+    if len > MAX_FOLDED_LEN {
+        return Cow::Owned(input.to_ascii_lowercase());
+    }
+    buf[..len].copy_from_slice(input.as_bytes());
+    for chunk in buf[..len.next_multiple_of(8)].chunks_exact_mut(8) {
+        let word = u64::from_ne_bytes(chunk.try_into().unwrap());
+        chunk.copy_from_slice(&lowercase_word(word).to_ne_bytes());
+    }
+    // Only ASCII letters changed, so the bytes are still UTF-8.
+    std::str::from_utf8(&buf[..len])
+        .map_or_else(|_| Cow::Owned(input.to_ascii_lowercase()), Cow::Borrowed)
+}
+
+/// Lowercases a string as [`str::to_lowercase`] does, folding into `buf`.
+///
+/// Only strings with non-ASCII letters that change case (see
+/// [`has_unicode_case`]) or longer than [`MAX_FOLDED_LEN`] allocate;
+/// anything else is folded as ASCII.
+pub(crate) fn lowercase<'a>(input: &'a str, buf: &'a mut [u8; MAX_FOLDED_LEN]) -> Cow<'a, str> {
+    if has_unicode_case(input) {
+        Cow::Owned(input.to_lowercase())
+    } else {
+        fold_ascii_lowercase(input, buf)
+    }
+}
+
+/// Checks whether a string has non-ASCII characters that Unicode
+/// lowercasing changes, such as `É` or the Kelvin sign, which lowercases to
+/// an ASCII `k`.
+pub(crate) fn has_unicode_case(input: &str) -> bool {
+    !input.is_ascii()
+        && input
+            .chars()
+            .any(|c| !c.is_ascii() && !c.to_lowercase().eq([c]))
+}
+
+/// Lowercases the ASCII letters of eight packed bytes at once.
+///
+/// Each byte's high bit is used as a per-lane flag: it ends up set exactly
+/// for bytes in `A..=Z`, and is then shifted down to `0x20`, the ASCII case
+/// bit. Bytes with the high bit set in the input are left untouched.
+fn lowercase_word(word: u64) -> u64 {
+    const ONES: u64 = u64::from_ne_bytes([0x01; 8]);
+    const HIGH: u64 = ONES * 0x80;
+    let low_bits = word & !HIGH;
+    let at_least_a = low_bits + ONES * (0x80 - b'A' as u64);
+    let above_z = low_bits + ONES * (0x80 - b'Z' as u64 - 1);
+    let upper = at_least_a & !above_z & !word & HIGH;
+    word | (upper >> 2)
+}
+
+#[cfg(feature = "simd")]
+fn last_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
+    memchr::memrchr(needle, haystack)
+}
+
+#[cfg(not(feature = "simd"))]
+fn last_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| b == needle)
+}
+
+#[cfg(all(feature = "simd", windows))]
+fn last_separator(haystack: &[u8]) -> Option<usize> {
+    memchr::memrchr2(b'/', b'\\', haystack)
+}
+
+#[cfg(all(not(feature = "simd"), windows))]
+fn last_separator(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| b == b'/' || b == b'\\')
+}
+
+#[cfg(not(windows))]
+fn last_separator(haystack: &[u8]) -> Option<usize> {
+    last_byte(b'/', haystack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_matches_path() {
+        let names = [
+            "a.pdf",
+            "dir/a.tar.gz",
+            "dir.d/readme",
+            ".bashrc",
+            "dir/.bashrc",
+            "..bar",
+            "a.",
+            "a..b",
+            "a.pdf/",
+            "a.pdf/.",
+            "dir/..",
+            ".",
+            "",
+            "/",
+            "noext",
+            "päth/ünï.cödé",
+        ];
+        for name in names {
+            let expected = Path::new(name).extension().and_then(|ext| ext.to_str());
+            assert_eq!(extension(name), expected, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn test_compound_extension() {
+        assert_eq!(compound_extension("dir/backup.tar.gz"), Some("tar.gz"));
+        assert_eq!(compound_extension("a.b.c.d"), Some("c.d"));
+        assert_eq!(compound_extension("jquery.min.js"), Some("min.js"));
+        assert_eq!(compound_extension("dir.d/a.gz"), None);
+        assert_eq!(compound_extension(".tar.gz"), None);
+        assert_eq!(compound_extension("noext"), None);
+        assert_eq!(compound_extension("a.tar.gz/"), None);
+    }
+
+    #[test]
+    fn test_url_file_name() {
+        let cases = [
+            (
+                "https://cdn.example.com/a/b/image%20name.PNG?sig=a/b.c",
+                "image name.PNG",
+            ),
+            ("/static/app.js#L10", "app.js"),
+            ("//example.com/r%C3%A9sum%C3%A9.pdf", "résumé.pdf"),
+            ("photo.jpg", "photo.jpg"),
+            ("https://example.com", ""),
+            ("https://example.com/docs/", ""),
+            ("file:///tmp/50%.txt", "50%.txt"),
+            ("/bad%FF%zz.txt", "bad\u{FFFD}%zz.txt"),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(url_file_name(url), expected, "{url:?}");
+        }
+        assert!(matches!(url_file_name("/a/b.png"), Cow::Borrowed("b.png")));
+    }
+
+    #[test]
+    fn test_fold_ascii_lowercase() {
+        let mut buf = [0; MAX_FOLDED_LEN];
+        assert_eq!(fold_ascii_lowercase("PDF", &mut buf), "pdf");
+        assert_eq!(fold_ascii_lowercase("Tar.Gz-@[`{", &mut buf), "tar.gz-@[`{");
+        assert_eq!(fold_ascii_lowercase("ÄB.Ünï", &mut buf), "Äb.Ünï");
+        assert!(matches!(
+            fold_ascii_lowercase("", &mut buf),
+            Cow::Borrowed("")
+        ));
+        let input = "already.lower";
+        let folded = fold_ascii_lowercase(input, &mut buf);
+        assert!(matches!(folded, Cow::Borrowed(folded) if folded.as_ptr() == input.as_ptr()));
+        let longest = "X".repeat(MAX_FOLDED_LEN);
+        assert!(matches!(
+            fold_ascii_lowercase(&longest, &mut buf),
+            Cow::Borrowed(_)
+        ));
+        let long = "X".repeat(MAX_FOLDED_LEN + 1);
+        let folded = fold_ascii_lowercase(&long, &mut buf);
+        assert!(matches!(&folded, Cow::Owned(folded) if *folded == long.to_lowercase()));
+    }
+
+    #[test]
+    fn test_lowercase() {
+        let mut buf = [0; MAX_FOLDED_LEN];
+        for input in [
+            "PDF",
+            "résumé.PDF",
+            "ÉTÉ",
+            "\u{212A}ELVIN",
+            "İ",
+            "ǅ",
+            "日本語.TXT",
+            "",
+        ] {
+            let expected = input.to_lowercase();
+            assert_eq!(lowercase(input, &mut buf), expected, "{input:?}");
+            assert_eq!(
+                has_unicode_case(input),
+                !input.is_ascii() && input.to_ascii_lowercase() != expected
+            );
+        }
+        assert!(matches!(
+            lowercase("résumé.PDF", &mut buf),
+            Cow::Borrowed("résumé.pdf")
+        ));
+    }
+
+    #[test]
+    fn test_lowercase_word_all_bytes() {
+        for byte in 0..=u8::MAX {
+            for lane in 0..8 {
+                let mut bytes = *b"aZ@[`{0~";
+                bytes[lane] = byte;
+                let folded = lowercase_word(u64::from_ne_bytes(bytes)).to_ne_bytes();
+                let expected = bytes.map(|b| b.to_ascii_lowercase());
+                assert_eq!(folded, expected, "byte {byte:#x} in lane {lane}");
+            }
+        }
+    }
+}
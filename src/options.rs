@@ -0,0 +1,152 @@
+//! Lenient filename lookups.
+
+use crate::normalize;
+
+/// Suffixes editors and tools append to a copy of a file, stripped by
+/// [`LookupOptions::backup_suffixes`].
+const BACKUP_EXTENSIONS: &[&str] = &["bak", "old", "orig"];
+
+/// How [`Db::lookup_by_filename_with`](crate::Db::lookup_by_filename_with)
+/// reads a filename.
+///
+/// [`LookupOptions::strict`], the default, reads it as
+/// [`Db::lookup_by_filename`](crate::Db::lookup_by_filename) does: every
+/// character is part of the name. [`LookupOptions::lenient`] also accepts
+/// the inputs web code and file managers pass around, such as
+/// `photo.jpg?w=200`, `notes.txt~` or a bare `.pdf`. Each option can be
+/// switched on its own.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{Db, LookupOptions};
+///
+/// let db = Db::new().unwrap();
+/// let url = "https://cdn.example.com/photos/cat.JPG?w=200#top";
+/// assert!(db.lookup_by_filename(url).is_none());
+///
+/// let options = LookupOptions::lenient();
+/// let info = db.lookup_by_filename_with(url, &options).unwrap();
+/// assert_eq!(&*info.content_type, "image/jpeg");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LookupOptions {
+    trim_leading_dot: bool,
+    backup_suffixes: bool,
+    strip_query: bool,
+}
+
+impl LookupOptions {
+    /// Reads filenames as they are, like
+    /// [`Db::lookup_by_filename`](crate::Db::lookup_by_filename).
+    pub const fn strict() -> Self {
+        LookupOptions {
+            trim_leading_dot: false,
+            backup_suffixes: false,
+            strip_query: false,
+        }
+    }
+
+    /// Switches every option on.
+    pub const fn lenient() -> Self {
+        LookupOptions {
+            trim_leading_dot: true,
+            backup_suffixes: true,
+            strip_query: true,
+        }
+    }
+
+    /// Reads a name made of a dot and an extension, such as `.pdf`, as that
+    /// extension rather than as a hidden file without one. Filename rules
+    /// for hidden files (`.bashrc`) still win.
+    pub const fn trim_leading_dot(mut self, trim: bool) -> Self {
+        self.trim_leading_dot = trim;
+        self
+    }
+
+    /// Looks through the suffixes of backup copies: trailing tildes
+    /// (`notes.txt~`) and the `.bak`, `.old` and `.orig` extensions
+    /// (`report.pdf.bak`), when another extension precedes them. A name
+    /// without another extension, such as `settings.bak`, is kept.
+    pub const fn backup_suffixes(mut self, strip: bool) -> Self {
+        self.backup_suffixes = strip;
+        self
+    }
+
+    /// Cuts the input at the first `?` or `#`, dropping the query string
+    /// and fragment of a URL or URL path (`/img/photo.jpg?w=200`). Off in
+    /// strict mode because both characters are valid in file names.
+    pub const fn strip_query(mut self, strip: bool) -> Self {
+        self.strip_query = strip;
+        self
+    }
+
+    /// Returns the file name to look up: the last path segment of
+    /// `filename`, without the query string or backup suffixes the options
+    /// strip.
+    pub(crate) fn file_name<'a>(&self, filename: &'a str) -> &'a str {
+        let mut filename = filename;
+        if self.strip_query {
+            if let Some(end) = filename.find(['?', '#']) {
+                filename = &filename[..end];
+            }
+        }
+        let mut name = normalize::file_name(filename);
+        if self.backup_suffixes {
+            while let Some(stripped) = strip_backup_suffix(name) {
+                name = stripped;
+            }
+        }
+        name
+    }
+
+    /// Returns the extension a name made of a dot and an extension stands
+    /// for, if [`LookupOptions::trim_leading_dot`] is on.
+    pub(crate) fn bare_extension<'a>(&self, name: &'a str) -> Option<&'a str> {
+        let extension = name.strip_prefix('.').filter(|_| self.trim_leading_dot)?;
+        (!extension.is_empty() && !extension.contains('.')).then_some(extension)
+    }
+}
+
+/// Strips one backup suffix from a file name: a trailing tilde, or a backup
+/// extension preceded by another extension.
+fn strip_backup_suffix(name: &str) -> Option<&str> {
+    if let Some(stripped) = name.strip_suffix('~') {
+        return Some(stripped);
+    }
+    let extension = normalize::extension(name)?;
+    let stem = &name[..name.len() - extension.len() - 1];
+    let is_backup = BACKUP_EXTENSIONS
+        .iter()
+        .any(|backup| backup.eq_ignore_ascii_case(extension));
+    (is_backup && normalize::extension(stem).is_some()).then_some(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        let strict = LookupOptions::strict();
+        let lenient = LookupOptions::lenient();
+        assert_eq!(strict, LookupOptions::default());
+        assert_eq!(strict.file_name("dir/photo.jpg?w=200"), "photo.jpg?w=200");
+        assert_eq!(lenient.file_name("dir/photo.jpg?w=200"), "photo.jpg");
+        assert_eq!(lenient.file_name("/a.png?next=/b.gif"), "a.png");
+        assert_eq!(lenient.file_name("/guide.html#intro"), "guide.html");
+        assert_eq!(lenient.file_name("https://example.com/"), "");
+
+        assert_eq!(strict.file_name("notes.txt~"), "notes.txt~");
+        assert_eq!(lenient.file_name("notes.txt~"), "notes.txt");
+        assert_eq!(lenient.file_name("report.pdf.BAK~"), "report.pdf");
+        assert_eq!(lenient.file_name("a.tar.gz.orig.old"), "a.tar.gz");
+        assert_eq!(lenient.file_name("settings.bak"), "settings.bak");
+        assert_eq!(lenient.file_name(".bak"), ".bak");
+
+        assert_eq!(lenient.bare_extension(".PDF"), Some("PDF"));
+        assert_eq!(lenient.bare_extension(".tar.gz"), None);
+        assert_eq!(lenient.bare_extension("."), None);
+        assert_eq!(strict.bare_extension(".pdf"), None);
+    }
+}
@@ -0,0 +1,311 @@
+//! Allow/deny policies over content types.
+//!
+//! Upload gateways and content filters decide which types to accept from a
+//! short list of rules. [`TypePolicy`] compiles such a list once and then
+//! answers each check with a handful of hash lookups, with the media type
+//! semantics spelled out: parameters are ignored, comparisons are
+//! case-insensitive, and structured syntax suffixes only match where a rule
+//! asks for them.
+
+use std::{fmt, str::FromStr};
+
+use crate::{db::Map, media_type::is_token, normalize, Info};
+
+/// A compiled set of allow/deny rules for content types.
+///
+/// Rules are written `allow <pattern>` or `deny <pattern>`, separated by `;`
+/// or newlines; blank rules and `#` comments are ignored. A pattern is one
+/// of, from most to least specific:
+///
+/// 1. `type/subtype`, an exact content type
+/// 2. `type/*+suffix`, any subtype of `type` with a structured syntax suffix
+/// 3. `*/*+suffix`, any type with that suffix
+/// 4. `type/*`, any subtype of `type`
+/// 5. `*/*`, anything
+///
+/// The most specific matching rule decides, wherever it appears in the list,
+/// and `deny` wins over `allow` for the same pattern. A content type no rule
+/// matches is denied, as is a malformed one or a wildcard such as
+/// `image/*`, which is a range rather than a type. Suffix patterns only match
+/// subtypes carrying the suffix: `application/*+zip` matches
+/// `application/epub+zip` but not `application/zip`.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::policy::TypePolicy;
+///
+/// let policy: TypePolicy = "allow image/*; allow application/*; deny application/*+zip"
+///     .parse()
+///     .unwrap();
+/// assert!(policy.allows_content_type("image/PNG"));
+/// assert!(policy.allows_content_type("application/pdf; name=\"a.pdf\""));
+/// assert!(!policy.allows_content_type("application/epub+zip"));
+/// assert!(!policy.allows_content_type("text/html"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TypePolicy {
+    /// Verdicts by lowercase pattern; `true` allows.
+    rules: Map<bool>,
+}
+
+impl TypePolicy {
+    /// Compiles a policy from its rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PolicyError`] for a rule that is not `allow` or `deny`
+    /// followed by a valid pattern.
+    pub fn parse(rules: &str) -> Result<TypePolicy, PolicyError> {
+        let mut policy = TypePolicy::default();
+        for rule in rules.lines().flat_map(|line| {
+            line.split('#')
+                .next()
+                .unwrap_or_default()
+                .split(';')
+                .map(str::trim)
+        }) {
+            if rule.is_empty() {
+                continue;
+            }
+            let error = || PolicyError {
+                rule: rule.to_string(),
+            };
+            let (action, pattern) = rule.split_once(char::is_whitespace).ok_or_else(error)?;
+            let allow = match action {
+                "allow" => true,
+                "deny" => false,
+                _ => return Err(error()),
+            };
+            let pattern = pattern.trim().to_ascii_lowercase();
+            if !is_pattern(&pattern) {
+                return Err(error());
+            }
+            *policy.rules.entry(pattern.into()).or_insert(allow) &= allow;
+        }
+        Ok(policy)
+    }
+
+    /// Compiles a policy that allows the given patterns and denies anything
+    /// else, for the common allow-list of an upload endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PolicyError`] for an invalid pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::policy::TypePolicy;
+    ///
+    /// let policy = TypePolicy::allow_list(["image/*", "application/pdf"]).unwrap();
+    /// assert!(policy.allows_content_type("image/webp"));
+    /// assert!(!policy.allows_content_type("text/html"));
+    /// ```
+    pub fn allow_list<'p>(
+        patterns: impl IntoIterator<Item = &'p str>,
+    ) -> Result<TypePolicy, PolicyError> {
+        let mut policy = TypePolicy::default();
+        for pattern in patterns {
+            let lowercase = pattern.trim().to_ascii_lowercase();
+            if !is_pattern(&lowercase) {
+                return Err(PolicyError {
+                    rule: format!("allow {pattern}"),
+                });
+            }
+            policy.rules.insert(lowercase.into(), true);
+        }
+        Ok(policy)
+    }
+
+    /// Checks whether the policy allows an entry's content type.
+    pub fn allows(&self, info: &Info) -> bool {
+        self.allows_content_type(&info.content_type)
+    }
+
+    /// Checks whether the policy allows a content type or `Content-Type`
+    /// header value.
+    pub fn allows_content_type(&self, content_type: &str) -> bool {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        let mut buf = [0; normalize::MAX_FOLDED_LEN];
+        let essence = normalize::fold_ascii_lowercase(essence, &mut buf);
+        let Some((ty, subtype)) = essence.split_once('/') else {
+            return false;
+        };
+        // `*` is a token character, but a wildcard names a range of types,
+        // not a content type any rule could allow.
+        if !is_token(ty) || !is_token(subtype) || ty.contains('*') || subtype.contains('*') {
+            return false;
+        }
+        if let Some(&allow) = self.rules.get(&*essence) {
+            return allow;
+        }
+
+        let suffix = subtype.rsplit_once('+').map(|(_, suffix)| suffix);
+        let wildcards = [
+            suffix.map(|suffix| [ty, "/*+", suffix]),
+            suffix.map(|suffix| ["*/*+", suffix, ""]),
+            Some([ty, "/*", ""]),
+            Some(["*/*", "", ""]),
+        ];
+        let mut pattern = String::with_capacity(essence.len() + 3);
+        let verdict = wildcards.into_iter().flatten().find_map(|parts| {
+            pattern.clear();
+            pattern.extend(parts);
+            self.rules.get(pattern.as_str()).copied()
+        });
+        verdict.unwrap_or(false)
+    }
+}
+
+impl FromStr for TypePolicy {
+    type Err = PolicyError;
+
+    fn from_str(rules: &str) -> Result<Self, Self::Err> {
+        TypePolicy::parse(rules)
+    }
+}
+
+/// Checks a lowercase rule pattern against the accepted forms.
+fn is_pattern(pattern: &str) -> bool {
+    let Some((ty, subtype)) = pattern.split_once('/') else {
+        return false;
+    };
+    let suffix_ok = |subtype: &str| {
+        subtype
+            .strip_prefix("*+")
+            .is_some_and(|suffix| is_token(suffix) && !suffix.contains(['*', '+']))
+    };
+    match (ty, subtype) {
+        ("*", "*") => true,
+        ("*", subtype) => suffix_ok(subtype),
+        (ty, _) if !is_token(ty) || ty.contains('*') => false,
+        (_, "*") => true,
+        (_, subtype) => suffix_ok(subtype) || (is_token(subtype) && !subtype.contains('*')),
+    }
+}
+
+/// Error returned by [`TypePolicy::parse`] for a malformed rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyError {
+    /// The offending rule
+    pub rule: String,
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed type policy rule: {:?}", self.rule)
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specificity() {
+        let policy = TypePolicy::parse(
+            "deny application/*+zip
+             allow image/*; allow application/pdf
+             allow */*+json  # structured JSON from any type
+             deny image/svg+xml
+             allow application/*
+             deny application/x-msdownload",
+        )
+        .unwrap();
+        let allowed = |content_type| policy.allows_content_type(content_type);
+
+        assert!(allowed("image/png"));
+        assert!(!allowed("image/svg+xml"), "exact deny beats image/*");
+        assert!(allowed("application/pdf"));
+        assert!(allowed("application/zip"), "no suffix, so application/*");
+        assert!(!allowed("application/epub+zip"));
+        assert!(allowed("application/vnd.api+json"));
+        assert!(allowed("text/x-report+json"));
+        assert!(!allowed("application/x-msdownload"));
+        assert!(!allowed("text/html"), "unmatched types are denied");
+    }
+
+    #[test]
+    fn test_header_values() {
+        let policy = TypePolicy::parse("allow text/plain").unwrap();
+        assert!(policy.allows_content_type("TEXT/Plain; charset=utf-8"));
+        assert!(policy.allows_content_type("  text/plain  "));
+        for malformed in ["", "text", "text/", "/plain", "text/pl ain", "*/*"] {
+            assert!(!policy.allows_content_type(malformed), "{malformed:?}");
+        }
+        assert!(!TypePolicy::default().allows_content_type("text/plain"));
+
+        let policy = TypePolicy::parse("allow */*; allow image/*; allow */*+json").unwrap();
+        assert!(policy.allows_content_type("image/png"));
+        for wildcard in [
+            "image/*",
+            "IMAGE/*; q=1",
+            "*/*",
+            "*/png",
+            "application/*+json",
+        ] {
+            assert!(!policy.allows_content_type(wildcard), "{wildcard:?}");
+        }
+    }
+
+    #[test]
+    fn test_deny_wins_on_same_pattern() {
+        let policy = TypePolicy::parse("allow image/*; deny IMAGE/*; allow image/*").unwrap();
+        assert!(!policy.allows_content_type("image/png"));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_allows_info() {
+        let db = crate::Db::new().unwrap();
+        let policy: TypePolicy = "allow */*; deny application/x-ms-dos-executable"
+            .parse()
+            .unwrap();
+        assert!(policy.allows(db.lookup_by_extension("png").unwrap()));
+        assert!(!policy.allows(db.lookup_by_extension("exe").unwrap()));
+    }
+
+    #[test]
+    fn test_allow_list() {
+        let policy = TypePolicy::allow_list(["Image/*", " application/pdf "]).unwrap();
+        assert!(policy.allows_content_type("image/png"));
+        assert!(policy.allows_content_type("application/pdf"));
+        assert!(!policy.allows_content_type("application/zip"));
+        assert_eq!(
+            TypePolicy::allow_list(["image/*", "*/png"]).unwrap_err(),
+            PolicyError {
+                rule: "allow */png".to_string()
+            }
+        );
+        assert!(!TypePolicy::allow_list([])
+            .unwrap()
+            .allows_content_type("image/png"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for rule in [
+            "allow",
+            "permit image/*",
+            "allow image",
+            "allow */png",
+            "allow image/png*",
+            "allow ima*ge/png",
+            "allow image/*+",
+            "allow image/*+x+y",
+            "allow image/png extra",
+        ] {
+            assert_eq!(
+                TypePolicy::parse(rule).unwrap_err(),
+                PolicyError {
+                    rule: rule.to_string()
+                },
+                "{rule}"
+            );
+        }
+        assert!(TypePolicy::parse(" ; # nothing\n\n").is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+//! Python bindings built with PyO3.
+//!
+//! Enabled by the `python` feature and packaged with maturin (see
+//! `pyproject.toml`). The module exposes the same lookups and sniffing as the
+//! Rust API, backed by the same embedded database:
+//!
+//! ```python
+//! import minimime
+//!
+//! info = minimime.lookup_by_filename("report.pdf")
+//! assert info.content_type == "application/pdf"
+//! assert info.is_binary()
+//! assert minimime.sniff(b"FUJIFILMCCD-RAW 0201") == "image/x-fuji-raf"
+//! ```
+
+use pyo3::prelude::*;
+
+/// Python view of [`crate::Info`].
+#[pyclass(
+    name = "Info",
+    module = "minimime",
+    frozen,
+    get_all,
+    eq,
+    skip_from_py_object
+)]
+#[derive(Clone, PartialEq)]
+struct PyInfo {
+    extension: String,
+    content_type: String,
+    encoding: String,
+    weight: u16,
+    tags: Vec<String>,
+}
+
+#[pymethods]
+impl PyInfo {
+    /// Whether the file type is binary.
+    fn is_binary(&self) -> bool {
+        crate::BINARY_ENCODINGS.contains(&self.encoding.as_str())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Info(extension={:?}, content_type={:?}, encoding={:?}, weight={})",
+            self.extension, self.content_type, self.encoding, self.weight
+        )
+    }
+}
+
+impl From<crate::Info> for PyInfo {
+    fn from(info: crate::Info) -> Self {
+        PyInfo {
+            extension: info.extension.into(),
+            content_type: info.content_type.into(),
+            encoding: info.encoding.into(),
+            weight: info.weight,
+            tags: info.tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+}
+
+/// Looks up MIME information by filename.
+#[pyfunction]
+fn lookup_by_filename(filename: &str) -> Option<PyInfo> {
+    crate::lookup_by_filename(filename).map(PyInfo::from)
+}
+
+/// Looks up MIME information by file extension.
+#[pyfunction]
+fn lookup_by_extension(extension: &str) -> Option<PyInfo> {
+    crate::lookup_by_extension(extension).map(PyInfo::from)
+}
+
+/// Looks up MIME information by content type.
+#[pyfunction]
+fn lookup_by_content_type(content_type: &str) -> Option<PyInfo> {
+    crate::lookup_by_content_type(content_type).map(PyInfo::from)
+}
+
+/// Looks up MIME information by the magic bytes at the start of a file.
+#[pyfunction]
+fn lookup_by_magic(data: &[u8]) -> Option<PyInfo> {
+    crate::lookup_by_magic(data).map(PyInfo::from)
+}
+
+/// Detects a content type string from magic bytes.
+#[pyfunction]
+fn sniff(data: &[u8]) -> Option<&'static str> {
+    crate::sniff::content_type(data)
+}
+
+#[pymodule]
+fn minimime(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyInfo>()?;
+    m.add_function(wrap_pyfunction!(lookup_by_filename, m)?)?;
+    m.add_function(wrap_pyfunction!(lookup_by_extension, m)?)?;
+    m.add_function(wrap_pyfunction!(lookup_by_content_type, m)?)?;
+    m.add_function(wrap_pyfunction!(lookup_by_magic, m)?)?;
+    m.add_function(wrap_pyfunction!(sniff, m)?)?;
+    Ok(())
+}
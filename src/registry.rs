@@ -0,0 +1,125 @@
+//! Content types registered in the Windows registry.
+//!
+//! Installers record the `Content Type` of the extensions they handle under
+//! `HKEY_CLASSES_ROOT\.ext`, so a Windows machine often knows application
+//! types the embedded database doesn't. [`Db::with_windows_registry`] adds
+//! them as a fallback.
+
+use std::io;
+
+use crate::{media_type, Db, Error};
+
+impl Db {
+    /// Creates a database from the embedded data, falling back to the
+    /// content types of the Windows registry.
+    ///
+    /// Every `HKEY_CLASSES_ROOT\.ext` key with a valid `Content Type` value
+    /// adds an entry for an extension the embedded database doesn't know;
+    /// known extensions keep their embedded entries, whatever the registry
+    /// says. The new entries share the encoding and tags of an existing
+    /// entry for their type, if any. On other platforms there is no
+    /// registry, and the embedded database is returned as it is. Requires
+    /// the `windows-registry` feature.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Db)` with the registry's entries added
+    /// * `Err` if `HKEY_CLASSES_ROOT` can't be enumerated
+    pub fn with_windows_registry() -> Result<Self, Error> {
+        let mut db = Db::new()?;
+        db.add_registry_types(read_registry()?);
+        Ok(db)
+    }
+
+    /// Registers `(".ext", content type)` pairs for unknown extensions.
+    fn add_registry_types(&mut self, types: impl IntoIterator<Item = (String, String)>) {
+        for (key, content_type) in types {
+            let Some(extension) = key.strip_prefix('.').filter(|ext| !ext.is_empty()) else {
+                continue;
+            };
+            let Some(content_type) = media_type::essence(&content_type) else {
+                continue;
+            };
+            if self.lookup_by_extension(extension).is_none() {
+                let info = self.derived_info(&extension.to_lowercase(), content_type, 0);
+                self.register(info);
+            }
+        }
+    }
+}
+
+/// Reads the `Content Type` of every extension key in `HKEY_CLASSES_ROOT`.
+#[cfg(windows)]
+fn read_registry() -> io::Result<Vec<(String, String)>> {
+    use winreg::{enums::HKEY_CLASSES_ROOT, RegKey};
+
+    let root = RegKey::predef(HKEY_CLASSES_ROOT);
+    let mut types = Vec::new();
+    for name in root.enum_keys() {
+        let name = name?;
+        if !name.starts_with('.') {
+            continue;
+        }
+        // Most extension keys only name a ProgID and have no content type.
+        let content_type = root
+            .open_subkey(&name)
+            .and_then(|key| key.get_value::<String, _>("Content Type"));
+        if let Ok(content_type) = content_type {
+            types.push((name, content_type));
+        }
+    }
+    Ok(types)
+}
+
+#[cfg(not(windows))]
+fn read_registry() -> io::Result<Vec<(String, String)>> {
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_registry_types() {
+        let mut db = Db::new().unwrap();
+        db.add_registry_types(
+            [
+                (".ACMEPROJ", "application/x-acme-project"),
+                (".acmelog", "text/x-acme-log; charset=utf-16"),
+                (".pdf", "application/x-vendor-pdf"),
+                (".broken", "not a type"),
+                ("acme.Document", "application/x-acme"),
+                (".", "application/x-empty"),
+            ]
+            .map(|(key, content_type)| (key.to_string(), content_type.to_string())),
+        );
+
+        let project = db.lookup_by_filename("site.acmeproj").unwrap();
+        assert_eq!(&*project.content_type, "application/x-acme-project");
+        assert!(project.is_binary());
+        assert_eq!(project.origin, crate::Origin::System);
+        let log = db.lookup_by_extension("acmelog").unwrap();
+        assert_eq!(&*log.content_type, "text/x-acme-log");
+        assert!(!log.is_binary());
+        assert_eq!(
+            &*db.lookup_by_extension("pdf").unwrap().content_type,
+            "application/pdf"
+        );
+        assert!(db.lookup_by_extension("broken").is_none());
+        assert!(db.lookup_by_content_type("application/x-acme").is_none());
+        assert!(db.lookup_by_content_type("application/x-empty").is_none());
+    }
+
+    #[test]
+    fn test_with_windows_registry() {
+        let db = Db::with_windows_registry().unwrap();
+        // Embedded entries win over the registry.
+        assert_eq!(
+            &*db.lookup_by_extension("pdf").unwrap().content_type,
+            "application/pdf"
+        );
+        #[cfg(not(windows))]
+        assert_eq!(db.ext_db, Db::new().unwrap().ext_db);
+    }
+}
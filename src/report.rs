@@ -0,0 +1,169 @@
+//! Audit of the embedded database files.
+
+use std::fmt;
+
+use crate::Info;
+
+include!(concat!(env!("OUT_DIR"), "/load_report.rs"));
+
+/// What loading the embedded database files left out, returned by
+/// [`Db::new_with_report`](crate::Db::new_with_report).
+///
+/// Malformed lines are skipped, and of the lines sharing a key (an
+/// extension in `ext_mime.db`, a content type in `content_type_mime.db`)
+/// only one is kept: the highest weight, then the first in the file. Both
+/// happen silently, so maintainers regenerating or hand-editing the files
+/// can check the report to catch data bugs, e.g. in a test asserting
+/// [`LoadReport::is_clean`]. Tables compiled out with the `ext-db` or
+/// `content-type-db` feature are not audited.
+///
+/// Its `Display` lists one issue per line, as `file:line: ...`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Malformed lines, skipped
+    pub skipped: Vec<ReportLine>,
+    /// Lines repeating the entry of a kept line, dropped without effect
+    pub duplicates: Vec<Collision>,
+    /// Lines with a different entry than the kept line for their key,
+    /// dropped: their content type, extension or encoding was lost
+    pub conflicts: Vec<Collision>,
+}
+
+/// A line of a database file, as listed in a [`LoadReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportLine {
+    /// The file, relative to the crate root
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+    /// The line as written
+    pub content: String,
+}
+
+/// A line dropped for sharing its key with a kept one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    /// The line that made it into the database
+    pub kept: ReportLine,
+    /// The line left out
+    pub dropped: ReportLine,
+}
+
+impl LoadReport {
+    /// Builds the report of the embedded database from what the build
+    /// recorded.
+    pub(crate) fn embedded() -> Self {
+        let line = |file: &str, (line, content): (usize, &str)| ReportLine {
+            file: file.to_string(),
+            line,
+            content: content.to_string(),
+        };
+        let mut report = LoadReport {
+            skipped: SKIPPED_LINES
+                .iter()
+                .map(|&(file, number, content)| line(file, (number, content)))
+                .collect(),
+            ..LoadReport::default()
+        };
+        for &(file, kept, dropped) in DROPPED_LINES {
+            let collision = Collision {
+                kept: line(file, kept),
+                dropped: line(file, dropped),
+            };
+            if same_entry(kept.1, dropped.1) {
+                report.duplicates.push(collision);
+            } else {
+                report.conflicts.push(collision);
+            }
+        }
+        report
+    }
+
+    /// Checks whether loading left nothing out.
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty() && self.duplicates.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+/// Checks whether two lines describe the same entry: extension, content type
+/// (both ignoring case) and encoding. Weights and tags don't count.
+fn same_entry(a: &str, b: &str) -> bool {
+    let entry = |line: &str| {
+        Info::new(line.split('#').next().unwrap_or_default()).map(|info| {
+            (
+                info.extension.to_ascii_lowercase(),
+                info.content_type.to_ascii_lowercase(),
+                info.encoding,
+            )
+        })
+    };
+    entry(a) == entry(b)
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.skipped {
+            writeln!(
+                f,
+                "{}:{}: skipped malformed line {:?}",
+                line.file, line.line, line.content
+            )?;
+        }
+        for (kind, collisions) in [
+            ("duplicate", &self.duplicates),
+            ("conflict", &self.conflicts),
+        ] {
+            for Collision { kept, dropped } in collisions {
+                writeln!(
+                    f,
+                    "{}:{}: {kind} of line {}, dropped {:?}",
+                    dropped.file, dropped.line, kept.line, dropped.content
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_report() {
+        let report = LoadReport::embedded();
+        assert!(report.is_clean(), "{report}");
+        assert_eq!(report.to_string(), "");
+    }
+
+    #[test]
+    fn test_display() {
+        let line = |line, content: &str| ReportLine {
+            file: "src/db/ext_mime.db".to_string(),
+            line,
+            content: content.to_string(),
+        };
+        assert!(same_entry(
+            "pdf application/pdf base64",
+            "PDF application/PDF base64 10 # again"
+        ));
+        assert!(!same_entry(
+            "ts video/mp2t base64",
+            "ts text/typescript quoted-printable"
+        ));
+        let report = LoadReport {
+            skipped: vec![line(3, "rpt")],
+            duplicates: vec![],
+            conflicts: vec![Collision {
+                kept: line(7, "ts video/mp2t base64"),
+                dropped: line(9, "ts text/typescript quoted-printable"),
+            }],
+        };
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.to_string(),
+            "src/db/ext_mime.db:3: skipped malformed line \"rpt\"\n\
+             src/db/ext_mime.db:9: conflict of line 7, dropped \"ts text/typescript quoted-printable\"\n"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! "Did you mean" suggestions for mistyped extensions.
+
+use crate::Db;
+
+/// Most suggestions returned by [`Db::suggest_extensions`].
+const MAX_SUGGESTIONS: usize = 5;
+
+impl Db {
+    /// Suggests known extensions close to a possibly mistyped one, for "did
+    /// you mean `.jpeg`?" errors.
+    ///
+    /// Extensions are compared case-insensitively, without the leading dot,
+    /// by edit distance: inserting, removing or replacing a character, or
+    /// swapping two neighbouring ones, counts as one edit. Up to two edits
+    /// are allowed, or one for inputs of four characters or fewer, where two
+    /// edits would match too much. The closest extensions come first, at
+    /// most five; among equally close ones, those with the same letters
+    /// (`pdf` for `pfd`) come first, then alphabetical order. A known
+    /// extension suggests itself first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// assert_eq!(db.suggest_extensions("jpeeg").first(), Some(&"jpeg"));
+    /// assert_eq!(db.suggest_extensions(".PFD").first(), Some(&"pdf"));
+    /// assert!(db.suggest_extensions("qqqqqqqq").is_empty());
+    /// ```
+    pub fn suggest_extensions(&self, extension: &str) -> Vec<&str> {
+        let input: Vec<char> = extension
+            .trim_start_matches('.')
+            .to_lowercase()
+            .chars()
+            .collect();
+        if input.is_empty() {
+            return Vec::new();
+        }
+        let max_distance = if input.len() <= 4 { 1 } else { 2 };
+        let mut letters = input.clone();
+        letters.sort_unstable();
+        let mut candidates: Vec<(usize, bool, &str)> = self
+            .ext_db
+            .keys()
+            .filter_map(|known| {
+                // Lengths alone rule most extensions out.
+                let len = known.chars().count();
+                if len.abs_diff(input.len()) > max_distance {
+                    return None;
+                }
+                let mut known_chars: Vec<char> = known.chars().collect();
+                let distance = distance(&input, &known_chars);
+                // At equal distances, swapped letters are the likelier typo.
+                known_chars.sort_unstable();
+                let swapped = known_chars == letters;
+                (distance <= max_distance).then_some((distance, !swapped, &**known))
+            })
+            .collect();
+        candidates.sort_unstable();
+        candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, _, known)| known)
+            .collect()
+    }
+}
+
+/// Edit distance counting insertions, deletions, substitutions and
+/// transpositions of adjacent characters (optimal string alignment).
+fn distance(a: &[char], b: &[char]) -> usize {
+    // Rows for the two previous prefixes of `a` and the current one.
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_distance() {
+        for (a, b, expected) in [
+            ("", "", 0),
+            ("", "abc", 3),
+            ("jpeg", "jpeg", 0),
+            ("jpeeg", "jpeg", 1),
+            ("pfd", "pdf", 1),
+            ("docx", "xlsx", 3),
+            ("kitten", "sitting", 3),
+            ("ca", "abc", 3),
+            ("mp4", "mp3", 1),
+        ] {
+            assert_eq!(distance(&chars(a), &chars(b)), expected, "{a} {b}");
+            assert_eq!(distance(&chars(b), &chars(a)), expected, "{b} {a}");
+        }
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_suggest_extensions() {
+        let db = Db::new().unwrap();
+        assert_eq!(db.suggest_extensions("docz"), ["doc", "docm", "docx"]);
+        assert_eq!(db.suggest_extensions("jpeeg").len(), MAX_SUGGESTIONS);
+        assert_eq!(db.suggest_extensions("xlxs"), ["xlsx", "xls"]);
+        assert_eq!(db.suggest_extensions("pdf")[0], "pdf");
+        assert_eq!(db.suggest_extensions("tar.gzz")[0], "tar.gz");
+        assert!(db.suggest_extensions("").is_empty());
+        assert!(db.suggest_extensions(".").is_empty());
+    }
+}
@@ -0,0 +1,512 @@
+//! The embedded database as `const` tables generated at build time.
+//!
+//! Every table is a `const` slice of [`Entry`], a borrowed `'static` form of
+//! [`Info`]. The whole dataset is available as
+//! [`BY_EXTENSION`] and [`BY_CONTENT_TYPE`], and per-category tables
+//! ([`IMAGES`], [`FONTS`], ...) group entries by top-level media type.
+//!
+//! The tables need no [`Db`](crate::Db) instance and no initialization, and
+//! because they are `const` they can be used in const contexts: downstream
+//! crates can derive their own `const` tables from them, and the linker only
+//! keeps what is actually referenced.
+//!
+//! The extension tables ([`BY_EXTENSION`] and the per-category ones) are
+//! only filled with the `ext-db` feature, and [`BY_CONTENT_TYPE`] and
+//! [`BY_ID`] only with `content-type-db`; both are on by default. Without
+//! one, its tables are empty and the lookups through them find nothing.
+//!
+//! # Examples
+//!
+//! ```
+//! use minimime::tables::{self, Entry};
+//!
+//! let png = tables::IMAGES.iter().find(|entry| entry.extension == "png").unwrap();
+//! assert_eq!(png.content_type, "image/png");
+//! assert!(tables::FONTS.iter().all(|entry| entry.content_type.starts_with("font/")));
+//!
+//! const PDF: &Entry = match tables::find_by_extension("pdf") {
+//!     Some(entry) => entry,
+//!     None => panic!("pdf is missing"),
+//! };
+//! const PDF_IS_BINARY: bool = PDF.is_binary();
+//! assert!(PDF_IS_BINARY);
+//! ```
+
+use core::cmp::Ordering;
+
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+#[cfg(feature = "std")]
+use crate::Info;
+
+/// A borrowed database row: one file extension and its MIME information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// File extension (without the dot)
+    pub extension: &'static str,
+    /// MIME content type (e.g., "text/plain", "image/png")
+    pub content_type: &'static str,
+    /// Encoding type (e.g., "8bit", "base64")
+    pub encoding: &'static str,
+    /// Priority that won this entry its place among rows sharing its key
+    pub weight: u16,
+    /// Stable numeric ID of the content type (see [`find_by_id`])
+    pub id: u16,
+    /// Sorted tags such as `archive`, `executable` or `office` (see [`TAGS`])
+    pub tags: &'static [&'static str],
+    /// Every extension of the content type, the preferred one first
+    pub extensions: &'static [&'static str],
+}
+
+impl Entry {
+    /// Converts this entry into an [`Info`].
+    ///
+    /// The `Info` borrows the entry's `'static` strings, so this allocates
+    /// nothing, and neither does cloning the result.
+    #[cfg(feature = "std")]
+    pub fn to_info(&self) -> Info {
+        Info {
+            extension: Cow::Borrowed(self.extension),
+            content_type: Cow::Borrowed(self.content_type),
+            encoding: self.encoding.into(),
+            weight: self.weight,
+            tags: match static_tags(self.tags) {
+                Some(tags) => Cow::Borrowed(tags),
+                None => self.tags.iter().map(|&tag| Cow::Borrowed(tag)).collect(),
+            },
+            origin: crate::Origin::Embedded,
+        }
+    }
+
+    /// Returns the freedesktop generic icon name for this entry's content
+    /// type; see [`icon::generic_icon_name`](crate::icon::generic_icon_name).
+    pub fn generic_icon_name(&self) -> &'static str {
+        crate::icon::generic_icon_name(self.content_type)
+    }
+
+    /// Returns how a search indexer should treat this entry; see
+    /// [`extraction`](crate::extraction).
+    pub fn extraction_hint(&self) -> crate::extraction::ExtractionHint {
+        crate::extraction::hint(
+            self.content_type,
+            self.has_tag("document") || self.has_tag("office"),
+        )
+    }
+
+    /// Returns the broad kind of this entry; see [`category`](crate::category).
+    pub fn category(&self) -> crate::category::Category {
+        crate::category::classify(
+            self.content_type,
+            self.has_tag("font"),
+            self.has_tag("archive"),
+        )
+    }
+
+    /// Checks whether this entry is text a search indexer can use as it is.
+    pub fn is_indexable_text(&self) -> bool {
+        self.extraction_hint().is_indexable_text()
+    }
+
+    /// Checks whether this entry carries a tag.
+    pub const fn has_tag(&self, tag: &str) -> bool {
+        let mut i = 0;
+        while i < self.tags.len() {
+            if cmp(self.tags[i], tag, false).is_eq() {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// Determines if this entry represents a binary file format.
+    ///
+    /// Binary entries are those that use "base64" or "8bit" encoding.
+    pub const fn is_binary(&self) -> bool {
+        let mut i = 0;
+        while i < crate::BINARY_ENCODINGS.len() {
+            if cmp(crate::BINARY_ENCODINGS[i], self.encoding, false).is_eq() {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+/// Finds an entry by file extension in [`BY_EXTENSION`].
+///
+/// This is a `const fn`: it can resolve entries at compile time. The lookup
+/// is ASCII case-insensitive.
+///
+/// # Returns
+///
+/// * `Some(&Entry)` if the extension is found
+/// * `None` if the extension is not recognized
+pub const fn find_by_extension(extension: &str) -> Option<&'static Entry> {
+    let (mut low, mut high) = (0, BY_EXTENSION.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match cmp(BY_EXTENSION[mid].extension, extension, true) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return Some(&BY_EXTENSION[mid]),
+        }
+    }
+    None
+}
+
+/// Resolves a file extension to its [`Entry`] at compile time.
+///
+/// Expands to a `&'static Entry` constant, found with [`find_by_extension`]
+/// (ASCII case-insensitive, without the dot), so the lookup costs nothing
+/// at run time. An extension missing from the embedded database fails the
+/// build. [`Entry::to_info`] turns the result into an [`Info`] without
+/// allocating. Without the `ext-db` feature every extension fails.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{mime, tables::Entry};
+///
+/// const PDF: &Entry = mime!("pdf");
+/// assert_eq!(PDF.content_type, "application/pdf");
+/// assert_eq!(mime!("PNG").extension, "png");
+/// ```
+///
+/// ```compile_fail
+/// let entry = minimime::mime!("not-an-extension");
+/// ```
+#[macro_export]
+macro_rules! mime {
+    ($extension:literal) => {{
+        const ENTRY: &'static $crate::tables::Entry =
+            match $crate::tables::find_by_extension($extension) {
+                ::core::option::Option::Some(entry) => entry,
+                ::core::option::Option::None => {
+                    ::core::panic!(::core::concat!("unknown extension `", $extension, "`"))
+                }
+            };
+        ENTRY
+    }};
+}
+
+/// Resolves a content type to its preferred [`Entry`] at compile time.
+///
+/// Expands to a `&'static Entry` constant, found with
+/// [`find_by_content_type`] (ASCII case-insensitive, without parameters),
+/// so the lookup costs nothing at run time. A content type missing from the
+/// embedded database fails the build, as does any content type without the
+/// `content-type-db` feature.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::content_type;
+///
+/// const JSON: &minimime::tables::Entry = content_type!("application/json");
+/// assert_eq!(JSON.extension, "json");
+/// assert_eq!(JSON.to_info().extensions(), ["json", "map"]);
+/// ```
+///
+/// ```compile_fail
+/// let entry = minimime::content_type!("application/x-not-a-type");
+/// ```
+#[macro_export]
+macro_rules! content_type {
+    ($content_type:literal) => {{
+        const ENTRY: &'static $crate::tables::Entry =
+            match $crate::tables::find_by_content_type($content_type) {
+                ::core::option::Option::Some(entry) => entry,
+                ::core::option::Option::None => ::core::panic!(::core::concat!(
+                    "unknown content type `",
+                    $content_type,
+                    "`"
+                )),
+            };
+        ENTRY
+    }};
+}
+
+/// Finds the preferred entry for a content type in [`BY_CONTENT_TYPE`].
+///
+/// This is a `const fn`: it can resolve entries at compile time. The lookup
+/// is ASCII case-insensitive.
+///
+/// # Returns
+///
+/// * `Some(&Entry)` if the content type is found
+/// * `None` if the content type is not recognized
+pub const fn find_by_content_type(content_type: &str) -> Option<&'static Entry> {
+    let (mut low, mut high) = (0, BY_CONTENT_TYPE.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match cmp(BY_CONTENT_TYPE[mid].content_type, content_type, true) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return Some(&BY_CONTENT_TYPE[mid]),
+        }
+    }
+    None
+}
+
+/// Finds the preferred entry for a content type by its numeric ID in
+/// [`BY_ID`].
+///
+/// Every content type in the database has a `u16` ID, listed in
+/// `src/db/content_type_ids.db`. IDs are stable across releases: a content
+/// type keeps its ID forever, new ones get fresh IDs, and the IDs of removed
+/// ones are never reused, so they can be stored in indexes or sent over the
+/// wire in place of the content type string. ID 0 is never assigned.
+///
+/// This is a `const fn`: it can resolve entries at compile time.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::tables;
+///
+/// let pdf = tables::find_by_content_type("application/pdf").unwrap();
+/// assert_eq!(tables::find_by_id(pdf.id), Some(pdf));
+/// assert_eq!(tables::find_by_id(0), None);
+/// ```
+pub const fn find_by_id(id: u16) -> Option<&'static Entry> {
+    let (mut low, mut high) = (0, BY_ID.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if BY_ID[mid].id < id {
+            low = mid + 1;
+        } else if BY_ID[mid].id > id {
+            high = mid;
+        } else {
+            return Some(&BY_ID[mid]);
+        }
+    }
+    None
+}
+
+/// Iterates over the entries of [`BY_EXTENSION`] carrying a tag.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::tables;
+///
+/// let blocked: Vec<&str> = tables::by_tag("executable").map(|entry| entry.extension).collect();
+/// assert!(blocked.contains(&"exe"));
+/// ```
+pub fn by_tag(tag: &str) -> impl Iterator<Item = &'static Entry> + '_ {
+    BY_EXTENSION.iter().filter(move |entry| entry.has_tag(tag))
+}
+
+/// Compares a table key with a query byte by byte, optionally folding the
+/// ASCII case of both (tables searched that way are sorted by folded key).
+const fn cmp(key: &str, query: &str, fold_case: bool) -> Ordering {
+    let (key, query) = (key.as_bytes(), query.as_bytes());
+    let mut i = 0;
+    while i < key.len() && i < query.len() {
+        let (k, q) = if fold_case {
+            (key[i].to_ascii_lowercase(), query[i].to_ascii_lowercase())
+        } else {
+            (key[i], query[i])
+        };
+        if k != q {
+            return if k < q {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        i += 1;
+    }
+    if key.len() < query.len() {
+        Ordering::Less
+    } else if key.len() > query.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/tables.rs"));
+#[cfg(feature = "std")]
+include!(concat!(env!("OUT_DIR"), "/static_tags.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(all(feature = "ext-db", feature = "content-type-db")))]
+    #[test]
+    fn test_compiled_out_tables() {
+        if !cfg!(feature = "ext-db") {
+            assert!(BY_EXTENSION.is_empty() && IMAGES.is_empty());
+            assert!(find_by_extension("pdf").is_none());
+        }
+        if !cfg!(feature = "content-type-db") {
+            assert!(BY_CONTENT_TYPE.is_empty() && BY_ID.is_empty());
+            assert!(find_by_content_type("application/pdf").is_none());
+            assert!(find_by_id(62).is_none());
+        }
+    }
+
+    const ALL: &[&[Entry]] = &[
+        APPLICATIONS,
+        AUDIO,
+        FONTS,
+        IMAGES,
+        MESSAGES,
+        MODELS,
+        TEXT,
+        VIDEOS,
+        OTHER,
+    ];
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_tables_cover_database() {
+        let total: usize = ALL.iter().map(|table| table.len()).sum();
+        let db = crate::Db::new().unwrap();
+        assert_eq!(total, db.ext_db.len());
+        for entry in ALL.iter().copied().flatten() {
+            assert_eq!(
+                db.lookup_by_extension(entry.extension),
+                Some(&entry.to_info())
+            );
+        }
+    }
+
+    #[test]
+    fn test_whole_tables_are_sorted() {
+        assert!(BY_EXTENSION
+            .windows(2)
+            .all(|w| w[0].extension < w[1].extension));
+        assert!(BY_CONTENT_TYPE.windows(2).all(
+            |w| w[0].content_type.to_ascii_lowercase() < w[1].content_type.to_ascii_lowercase()
+        ));
+        assert!(BY_EXTENSION
+            .iter()
+            .all(|entry| !entry.extension.bytes().any(|b| b.is_ascii_uppercase())));
+        let total: usize = ALL.iter().map(|table| table.len()).sum();
+        assert_eq!(total, BY_EXTENSION.len());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_const_lookups() {
+        const ZIP: Option<&Entry> = find_by_extension("ZiP");
+        assert_eq!(ZIP.unwrap().content_type, "application/zip");
+        assert!(find_by_extension("frog").is_none());
+        assert!(find_by_extension("").is_none());
+        assert_eq!(find_by_content_type("text/plain").unwrap().extension, "txt");
+        assert_eq!(find_by_content_type("TEXT/PLAIN").unwrap().extension, "txt");
+        assert_eq!(
+            find_by_content_type("video/mp2t").unwrap().content_type,
+            "video/MP2T"
+        );
+        assert!(find_by_content_type("something-fake").is_none());
+        assert!(find_by_extension("pdf").unwrap().is_binary());
+        assert!(!find_by_extension("txt").unwrap().is_binary());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_tables_are_scoped() {
+        assert!(IMAGES.iter().all(|e| e.content_type.starts_with("image/")));
+        assert!(VIDEOS.iter().all(|e| e.content_type.starts_with("video/")));
+        assert!(OTHER
+            .iter()
+            .any(|e| e.content_type.starts_with("chemical/")));
+        assert!(IMAGES.windows(2).all(|w| w[0].extension < w[1].extension));
+        assert!(IMAGES.iter().any(|e| e.extension == "png" && e.is_binary()));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_tags() {
+        let tagged = |tag| by_tag(tag).map(|e| e.extension).collect::<Vec<_>>();
+        assert!(tagged("executable").contains(&"exe"));
+        assert!(tagged("executable").contains(&"bat"));
+        assert!(tagged("archive").contains(&"zip"));
+        assert!(tagged("office").contains(&"docx"));
+        assert!(tagged("web").contains(&"html"));
+        assert!(tagged("font").contains(&"woff2"));
+        assert!(tagged("media").len() >= IMAGES.len());
+        assert!(tagged("nonexistent").is_empty());
+
+        let jar = find_by_extension("jar").unwrap();
+        assert!(jar.has_tag("archive") && jar.has_tag("executable"));
+        assert!(!find_by_extension("txt").unwrap().has_tag("executable"));
+        assert!(BY_EXTENSION
+            .iter()
+            .all(|e| e.tags.iter().all(|tag| TAGS.contains(tag))
+                && e.tags.windows(2).all(|w| w[0] < w[1])));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_ids() {
+        // IDs are part of the public API and must never change.
+        assert_eq!(find_by_content_type("application/pdf").unwrap().id, 62);
+        assert_eq!(find_by_id(62).unwrap().content_type, "application/pdf");
+        assert_eq!(find_by_id(0), None);
+        assert_eq!(find_by_id(u16::MAX), None);
+        assert!(BY_ID.windows(2).all(|w| w[0].id < w[1].id));
+        assert_eq!(BY_ID.len(), BY_CONTENT_TYPE.len());
+        for entry in BY_EXTENSION {
+            let by_id = find_by_id(entry.id).unwrap();
+            assert_eq!(by_id.content_type, entry.content_type);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_extensions() {
+        let db = crate::Db::new().unwrap();
+        for entry in BY_EXTENSION.iter().chain(BY_CONTENT_TYPE) {
+            assert_eq!(
+                entry.extensions,
+                db.all_extensions(entry.content_type),
+                "{}",
+                entry.content_type
+            );
+            assert!(entry.extensions.contains(&entry.extension));
+        }
+        let jpeg = find_by_extension("jpg").unwrap();
+        assert_eq!(jpeg.extensions, ["jpeg", "jfif", "jpe", "jpg"]);
+        let info = jpeg.to_info();
+        assert_eq!(info.extensions(), jpeg.extensions);
+        let custom = Info::new("acme application/vnd.acme base64").unwrap();
+        assert!(custom.extensions().is_empty());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_macros() {
+        const PDF: &Entry = crate::mime!("PDF");
+        const CSS: &Entry = crate::content_type!("Text/CSS");
+        assert_eq!(PDF, find_by_extension("pdf").unwrap());
+        assert_eq!(CSS, find_by_content_type("text/css").unwrap());
+        assert!(core::ptr::eq(PDF, crate::mime!("pdf")));
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_extraction_hint() {
+        use crate::extraction::ExtractionHint;
+
+        let hint = |ext| find_by_extension(ext).unwrap().extraction_hint();
+        for ext in ["txt", "html", "json", "svg", "csv", "md", "eml"] {
+            assert_eq!(hint(ext), ExtractionHint::Text, "{ext}");
+        }
+        for ext in ["pdf", "epub", "docx", "xlsx", "odt", "rtf"] {
+            assert_eq!(hint(ext), ExtractionHint::Extract, "{ext}");
+        }
+        for ext in ["png", "zip", "exe", "mp4"] {
+            assert_eq!(hint(ext), ExtractionHint::Opaque, "{ext}");
+        }
+        assert!(find_by_extension("js").unwrap().is_indexable_text());
+    }
+}
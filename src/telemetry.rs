@@ -0,0 +1,95 @@
+//! Lookup latency histograms for the `metrics` feature.
+//!
+//! Lookups and sniffing record their latency, in seconds, through the
+//! [`metrics`](https://docs.rs/metrics) facade, so any installed recorder
+//! (Prometheus, StatsD, ...) can export them:
+//!
+//! * `minimime.lookup.duration`, labelled with `kind` = `extension`,
+//!   `content_type`, `filename` or `magic`
+//! * `minimime.sniff.duration`, for [`sniff::content_type`](crate::sniff::content_type)
+//!
+//! Without the feature these helpers just call the closure and compile away.
+
+/// Histogram of [`Db`](crate::Db) lookup latencies.
+#[cfg(feature = "metrics")]
+const LOOKUP_DURATION: &str = "minimime.lookup.duration";
+/// Histogram of content sniffing latencies.
+#[cfg(feature = "metrics")]
+const SNIFF_DURATION: &str = "minimime.sniff.duration";
+
+/// Runs a lookup, recording its latency under the given `kind` label.
+#[cfg(feature = "metrics")]
+pub(crate) fn time_lookup<T>(kind: &'static str, lookup: impl FnOnce() -> T) -> T {
+    let start = std::time::Instant::now();
+    let result = lookup();
+    metrics::histogram!(LOOKUP_DURATION, "kind" => kind).record(start.elapsed());
+    result
+}
+
+/// Runs a sniff, recording its latency.
+#[cfg(feature = "metrics")]
+pub(crate) fn time_sniff<T>(sniff: impl FnOnce() -> T) -> T {
+    let start = std::time::Instant::now();
+    let result = sniff();
+    metrics::histogram!(SNIFF_DURATION).record(start.elapsed());
+    result
+}
+
+#[cfg(all(feature = "std", not(feature = "metrics")))]
+#[inline(always)]
+pub(crate) fn time_lookup<T>(_kind: &'static str, lookup: impl FnOnce() -> T) -> T {
+    lookup()
+}
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+pub(crate) fn time_sniff<T>(sniff: impl FnOnce() -> T) -> T {
+    sniff()
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::Db;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn test_records_histograms() {
+        let db = Db::new().unwrap();
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            db.lookup_by_filename("a.pdf");
+            db.lookup_by_extension("png");
+            db.lookup_by_content_type("text/plain");
+            db.lookup_by_magic(b"FUJIFILMCCD-RAW 0201");
+        });
+
+        let mut recorded: Vec<(String, Option<String>, usize)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                let kind = key.labels().next().map(|label| label.value().to_string());
+                let DebugValue::Histogram(samples) = value else {
+                    panic!("unexpected metric type");
+                };
+                (key.name().to_string(), kind, samples.len())
+            })
+            .collect();
+        recorded.sort();
+        let expected =
+            |name: &str, kind: Option<&str>| (name.to_string(), kind.map(str::to_string), 1);
+        assert_eq!(
+            recorded,
+            [
+                expected(LOOKUP_DURATION, Some("content_type")),
+                expected(LOOKUP_DURATION, Some("extension")),
+                expected(LOOKUP_DURATION, Some("filename")),
+                expected(LOOKUP_DURATION, Some("magic")),
+                expected(SNIFF_DURATION, None),
+            ]
+        );
+    }
+}
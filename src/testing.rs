@@ -0,0 +1,271 @@
+//! Helpers for tests of code built on this crate.
+//!
+//! [`db`] and [`embedded_with`] build throwaway databases from inline lines
+//! in the format of [`Db::parse`], so a test can stub the types it needs
+//! instead of depending on the embedded data. [`assert_round_trip`] checks
+//! that a database, such as one assembled from override files, agrees with
+//! itself in both directions, extension to content type and back.
+//!
+//! Requires the `testing` feature; enable it in `[dev-dependencies]` only.
+
+use crate::{Db, MergeStrategy};
+
+/// Builds a database holding only the entries of `lines`.
+///
+/// # Panics
+///
+/// Panics if a line is malformed
+///
+/// # Examples
+///
+/// ```
+/// let db = minimime::testing::db("rpt application/vnd.acme.report base64\n");
+/// assert!(db.lookup_by_filename("q3.RPT").is_some());
+/// assert!(db.lookup_by_extension("pdf").is_none());
+/// ```
+#[track_caller]
+pub fn db(lines: &str) -> Db {
+    Db::parse(lines).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Builds the embedded database with the entries of `lines` on top,
+/// overriding embedded entries for the same extensions and content types.
+///
+/// # Panics
+///
+/// Panics if a line is malformed
+///
+/// # Examples
+///
+/// ```
+/// let db = minimime::testing::embedded_with("pdf application/x-acme-pdf base64");
+/// assert_eq!(&*db.lookup_by_extension("pdf").unwrap().content_type, "application/x-acme-pdf");
+/// assert!(db.lookup_by_extension("png").is_some());
+/// ```
+#[track_caller]
+pub fn embedded_with(lines: &str) -> Db {
+    let mut embedded = Db::new().unwrap_or_else(|err| panic!("{err}"));
+    embedded
+        .merge(db(lines), MergeStrategy::PreferOther)
+        .unwrap_or_else(|err| panic!("{err}"));
+    embedded
+}
+
+/// Checks the round-trip invariants of a database, returning a description
+/// of each violation.
+///
+/// For every extension entry:
+///
+/// * looking up its extension finds it, in uppercase too unless the
+///   database is case-sensitive
+/// * its content type has an entry
+/// * [`Db::all_extensions`] of its content type lists its extension
+///
+/// For every content type entry, looking up its content type finds an
+/// entry, with parameters too (`; charset=utf-8`).
+///
+/// The preferred extension of a content type may map to another type (the
+/// embedded `c` is `text/plain`, while `text/x-c` prefers `c`), so that
+/// direction is not checked.
+pub fn round_trip_violations(db: &Db) -> Vec<String> {
+    let mut violations = Vec::new();
+    let mut sorted: Vec<_> = db.ext_db.values().collect();
+    sorted.sort_by(|a, b| a.extension.cmp(&b.extension));
+    for info in sorted {
+        let extension = &*info.extension;
+        let content_type = &*info.content_type;
+        if db.lookup_by_extension(extension) != Some(info) {
+            violations.push(format!("extension {extension:?} doesn't find its entry"));
+        }
+        if !db.case_sensitive && db.lookup_by_extension(&extension.to_uppercase()) != Some(info) {
+            violations.push(format!(
+                "extension {extension:?} doesn't find its entry in uppercase"
+            ));
+        }
+        if db.lookup_by_content_type(content_type).is_none() {
+            violations.push(format!(
+                "extension {extension:?} maps to {content_type:?}, which has no entry"
+            ));
+        } else if !db.all_extensions(content_type).contains(&extension) {
+            violations.push(format!(
+                "extension {extension:?} is missing from the extensions of {content_type:?}"
+            ));
+        }
+    }
+    let mut content_types: Vec<_> = db
+        .content_type_db
+        .values()
+        .map(|info| &*info.content_type)
+        .collect();
+    content_types.sort_unstable();
+    content_types.dedup();
+    for content_type in content_types {
+        if db.lookup_by_content_type(content_type).is_none() {
+            violations.push(format!(
+                "content type {content_type:?} doesn't find an entry"
+            ));
+        } else if db
+            .lookup_by_content_type(&format!("{content_type}; charset=utf-8"))
+            .is_none()
+        {
+            violations.push(format!(
+                "content type {content_type:?} doesn't find an entry with parameters"
+            ));
+        }
+    }
+    violations
+}
+
+/// Asserts the round-trip invariants of [`round_trip_violations`].
+///
+/// # Panics
+///
+/// Panics listing every violation, if there is any
+///
+/// # Examples
+///
+/// ```
+/// use minimime::{testing, Db};
+///
+/// testing::assert_round_trip(&Db::new().unwrap());
+/// testing::assert_round_trip(&testing::embedded_with("rpt application/vnd.acme.report base64"));
+/// ```
+#[track_caller]
+pub fn assert_round_trip(db: &Db) {
+    let violations = round_trip_violations(db);
+    assert!(
+        violations.is_empty(),
+        "{} round-trip violations:\n{}",
+        violations.len(),
+        violations.join("\n")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    use crate::{mapped::MappedDb, tables};
+
+    /// The embedded database in the compact format, built once.
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    fn mapped() -> &'static MappedDb<Vec<u8>> {
+        use std::sync::OnceLock;
+
+        static MAPPED: OnceLock<MappedDb<Vec<u8>>> = OnceLock::new();
+        MAPPED.get_or_init(|| MappedDb::new(Db::new().unwrap().to_mapped_bytes()).unwrap())
+    }
+
+    /// Changes the case of the letters of `input` whose bit is set in `mask`.
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    fn recase(input: &str, mask: u64) -> String {
+        input
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if mask >> (i % 64) & 1 == 1 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_embedded_round_trip() {
+        assert_round_trip(&Db::new().unwrap());
+        assert_round_trip(&embedded_with(
+            "pdf application/x-acme-pdf base64\n\
+             rpt application/vnd.acme.report base64\n\
+             c text/x-c quoted-printable\n",
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_violations() {
+        let mut stub = db("rpt application/vnd.acme.report base64");
+        assert_round_trip(&stub);
+        stub.register(crate::Info::new("log text/x-acme-log 8bit").unwrap());
+        std::sync::Arc::make_mut(&mut stub.content_type_db).remove("text/x-acme-log");
+        assert_eq!(
+            round_trip_violations(&stub),
+            ["extension \"log\" maps to \"text/x-acme-log\", which has no entry"]
+        );
+    }
+
+    proptest! {
+        #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+        #[test]
+        fn prop_extension_lookups_ignore_case(
+            index in 0..tables::BY_EXTENSION.len(),
+            mask in any::<u64>(),
+        ) {
+            let db = Db::new().unwrap();
+            let entry = &tables::BY_EXTENSION[index];
+            let query = recase(entry.extension, mask);
+            let info = db.lookup_by_extension(&query);
+            prop_assert_eq!(info, db.lookup_by_extension(entry.extension));
+            prop_assert_eq!(info.map(|info| &*info.content_type), Some(entry.content_type));
+            prop_assert_eq!(tables::find_by_extension(&query), Some(entry));
+            let mapped = mapped().lookup_by_extension(&query);
+            prop_assert_eq!(mapped.as_ref(), info);
+        }
+
+        #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+        #[test]
+        fn prop_filename_lookups_find_the_extension(
+            index in 0..tables::BY_EXTENSION.len(),
+            dir in "([a-zA-Z0-9_]{1,8}/){0,3}",
+            stem in "[a-zA-Z0-9_-]{1,12}",
+            mask in any::<u64>(),
+        ) {
+            let db = Db::new().unwrap();
+            let entry = &tables::BY_EXTENSION[index];
+            let filename = format!("{dir}{stem}.{}", recase(entry.extension, mask));
+            let info = db.lookup_by_filename(&filename);
+            prop_assert_eq!(info.map(|info| &*info.content_type), Some(entry.content_type));
+            prop_assert_eq!(db.lookup_by_path(&filename), info);
+        }
+
+        #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+        #[test]
+        fn prop_content_type_lookups_ignore_case_and_parameters(
+            index in 0..tables::BY_CONTENT_TYPE.len(),
+            mask in any::<u64>(),
+            parameters in "(; ?[a-z]{1,8}=[a-z0-9-]{1,8}){0,2}",
+        ) {
+            let db = Db::new().unwrap();
+            let entry = &tables::BY_CONTENT_TYPE[index];
+            let query = format!("{}{parameters}", recase(entry.content_type, mask));
+            let info = db.lookup_by_content_type(&query);
+            prop_assert_eq!(info, db.lookup_by_content_type(entry.content_type));
+            prop_assert_eq!(info.map(|info| &*info.extension), Some(entry.extension));
+        }
+
+        #[test]
+        fn prop_parsed_databases_round_trip(
+            lines in prop::collection::vec(
+                (
+                    "[a-z0-9]{1,6}",
+                    "[a-z]{1,6}/[a-z0-9.+-]{1,10}",
+                    prop::sample::select(&["7bit", "8bit", "base64", "quoted-printable"][..]),
+                    0..3u16,
+                ),
+                1..16,
+            ),
+        ) {
+            let text: String = lines
+                .iter()
+                .map(|(extension, content_type, encoding, weight)| {
+                    format!("{extension} {content_type} {encoding} {weight}\n")
+                })
+                .collect();
+            let parsed = db(&text);
+            prop_assert_eq!(round_trip_violations(&parsed), Vec::<String>::new());
+        }
+    }
+}
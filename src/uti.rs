@@ -0,0 +1,250 @@
+//! Apple Uniform Type Identifiers for content types.
+//!
+//! macOS and iOS name file types with reverse-DNS Uniform Type Identifiers
+//! (`public.jpeg`, `com.adobe.pdf`) rather than MIME types, in the
+//! pasteboard, drag and drop and document pickers. [`uti_for_content_type`]
+//! and [`content_type_for_uti`] convert between the two, so a file dropped
+//! on an AppKit or UIKit view can be uploaded with the right
+//! `Content-Type`, and the other way around.
+
+#[cfg(feature = "std")]
+use crate::{Db, Info};
+
+/// `(UTI, content type)` pairs. The first pair of a UTI gives its content
+/// type, and the first pair of a content type its UTI.
+const UTIS: &[(&str, &str)] = &[
+    // Images
+    ("public.jpeg", "image/jpeg"),
+    ("public.png", "image/png"),
+    ("com.compuserve.gif", "image/gif"),
+    ("public.tiff", "image/tiff"),
+    ("com.microsoft.bmp", "image/bmp"),
+    ("com.microsoft.ico", "image/vnd.microsoft.icon"),
+    ("com.microsoft.ico", "image/x-icon"),
+    ("public.heic", "image/heic"),
+    ("public.heif", "image/heif"),
+    ("org.webmproject.webp", "image/webp"),
+    ("public.avif", "image/avif"),
+    ("public.jpeg-xl", "image/jxl"),
+    ("public.jpeg-2000", "image/jp2"),
+    ("public.svg-image", "image/svg+xml"),
+    ("com.adobe.photoshop-image", "image/vnd.adobe.photoshop"),
+    ("com.adobe.raw-image", "image/x-adobe-dng"),
+    // Documents
+    ("com.adobe.pdf", "application/pdf"),
+    (
+        "com.adobe.encapsulated-postscript",
+        "application/postscript",
+    ),
+    ("public.rtf", "application/rtf"),
+    ("public.rtf", "text/rtf"),
+    ("com.microsoft.word.doc", "application/msword"),
+    (
+        "org.openxmlformats.wordprocessingml.document",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    ("com.microsoft.excel.xls", "application/vnd.ms-excel"),
+    (
+        "org.openxmlformats.spreadsheetml.sheet",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    (
+        "com.microsoft.powerpoint.ppt",
+        "application/vnd.ms-powerpoint",
+    ),
+    (
+        "org.openxmlformats.presentationml.presentation",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    (
+        "org.oasis-open.opendocument.text",
+        "application/vnd.oasis.opendocument.text",
+    ),
+    (
+        "org.oasis-open.opendocument.spreadsheet",
+        "application/vnd.oasis.opendocument.spreadsheet",
+    ),
+    (
+        "org.oasis-open.opendocument.presentation",
+        "application/vnd.oasis.opendocument.presentation",
+    ),
+    (
+        "com.apple.iwork.pages.sffpages",
+        "application/x-iwork-pages-sffpages",
+    ),
+    (
+        "com.apple.iwork.numbers.sffnumbers",
+        "application/x-iwork-numbers-sffnumbers",
+    ),
+    (
+        "com.apple.iwork.keynote.sffkey",
+        "application/x-iwork-keynote-sffkey",
+    ),
+    ("org.idpf.epub-container", "application/epub+zip"),
+    // Text and code
+    ("public.plain-text", "text/plain"),
+    ("public.utf8-plain-text", "text/plain"),
+    ("public.html", "text/html"),
+    ("public.xhtml", "application/xhtml+xml"),
+    ("public.xml", "application/xml"),
+    ("public.xml", "text/xml"),
+    ("public.css", "text/css"),
+    ("com.netscape.javascript-source", "text/javascript"),
+    ("com.netscape.javascript-source", "application/javascript"),
+    ("public.json", "application/json"),
+    ("public.yaml", "text/x-yaml"),
+    ("public.yaml", "application/yaml"),
+    ("net.daringfireball.markdown", "text/markdown"),
+    ("public.comma-separated-values-text", "text/csv"),
+    (
+        "public.tab-separated-values-text",
+        "text/tab-separated-values",
+    ),
+    ("public.python-script", "application/x-python"),
+    ("public.python-script", "text/x-python"),
+    ("public.shell-script", "application/x-sh"),
+    ("public.c-source", "text/x-c"),
+    ("com.sun.java-source", "text/x-java-source"),
+    ("public.vcard", "text/vcard"),
+    ("public.vcard", "text/x-vcard"),
+    ("com.apple.ical.ics", "text/calendar"),
+    ("com.apple.mail.email", "message/rfc822"),
+    // Audio
+    ("public.mp3", "audio/mpeg"),
+    ("public.mpeg-4-audio", "audio/mp4"),
+    ("public.aac-audio", "audio/aac"),
+    ("public.aiff-audio", "audio/x-aiff"),
+    ("public.aiff-audio", "audio/aiff"),
+    ("com.microsoft.waveform-audio", "audio/wav"),
+    ("com.microsoft.waveform-audio", "audio/x-wav"),
+    ("org.xiph.flac", "audio/flac"),
+    ("org.xiph.ogg-audio", "audio/ogg"),
+    // Video
+    ("public.mpeg-4", "video/mp4"),
+    ("com.apple.quicktime-movie", "video/quicktime"),
+    ("public.mpeg", "video/mpeg"),
+    ("public.avi", "video/x-msvideo"),
+    ("public.3gpp", "video/3gpp"),
+    ("public.mpeg-2-transport-stream", "video/mp2t"),
+    ("org.webmproject.webm", "video/webm"),
+    ("org.matroska.mkv", "video/x-matroska"),
+    // Archives and applications
+    ("public.zip-archive", "application/zip"),
+    ("org.gnu.gnu-zip-archive", "application/gzip"),
+    ("public.tar-archive", "application/x-tar"),
+    ("public.bzip2-archive", "application/x-bzip2"),
+    ("org.7-zip.7-zip-archive", "application/x-7z-compressed"),
+    ("com.rarlab.rar-archive", "application/x-rar-compressed"),
+    ("com.rarlab.rar-archive", "application/vnd.rar"),
+    ("com.apple.disk-image-udif", "application/x-apple-diskimage"),
+    ("com.sun.java-archive", "application/java-archive"),
+    // Fonts
+    ("public.truetype-ttf-font", "font/ttf"),
+    ("public.opentype-font", "font/otf"),
+    ("org.w3.woff", "font/woff"),
+    ("org.w3.woff2", "font/woff2"),
+    // Anything else
+    ("public.data", "application/octet-stream"),
+];
+
+/// Returns the Uniform Type Identifier of a content type.
+///
+/// Parameters and ASCII case are ignored. Types without a well-known UTI
+/// return `None`; macOS gives those a dynamic `dyn.` identifier, which
+/// can't be derived here.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::uti;
+///
+/// assert_eq!(uti::uti_for_content_type("image/jpeg"), Some("public.jpeg"));
+/// assert_eq!(uti::uti_for_content_type("Text/Plain; charset=utf-8"), Some("public.plain-text"));
+/// assert_eq!(uti::uti_for_content_type("application/x-acme"), None);
+/// ```
+pub fn uti_for_content_type(content_type: &str) -> Option<&'static str> {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    UTIS.iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(essence))
+        .map(|(uti, _)| *uti)
+}
+
+/// Returns the content type of a Uniform Type Identifier.
+///
+/// UTIs are compared ignoring ASCII case. Some have several content types
+/// in use (`public.xml` is `application/xml` and `text/xml`); the one the
+/// embedded database prefers is returned.
+///
+/// # Examples
+///
+/// ```
+/// use minimime::uti;
+///
+/// assert_eq!(uti::content_type_for_uti("com.adobe.pdf"), Some("application/pdf"));
+/// assert_eq!(uti::content_type_for_uti("public.utf8-plain-text"), Some("text/plain"));
+/// assert_eq!(uti::content_type_for_uti("com.example.unknown"), None);
+/// ```
+pub fn content_type_for_uti(uti: &str) -> Option<&'static str> {
+    UTIS.iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(uti.trim()))
+        .map(|(_, content_type)| *content_type)
+}
+
+#[cfg(feature = "std")]
+impl Db {
+    /// Looks up the entry for a Uniform Type Identifier, e.g. to name a
+    /// file dropped from a macOS application.
+    ///
+    /// See [`content_type_for_uti`] for the identifiers known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use minimime::Db;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let info = db.lookup_by_uti("public.comma-separated-values-text").unwrap();
+    /// assert_eq!(&*info.extension, "csv");
+    /// ```
+    pub fn lookup_by_uti(&self, uti: &str) -> Option<&Info> {
+        content_type_for_uti(uti).and_then(|content_type| self.lookup_by_content_type(content_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        // The UTI of every content type maps back to a content type with
+        // that same UTI.
+        for (_, content_type) in UTIS {
+            let uti = uti_for_content_type(content_type).unwrap();
+            let back = content_type_for_uti(uti).unwrap();
+            assert_eq!(uti_for_content_type(back), Some(uti), "{content_type}");
+        }
+        assert_eq!(content_type_for_uti("PUBLIC.PNG"), Some("image/png"));
+        assert_eq!(
+            uti_for_content_type("video/MP2T"),
+            Some("public.mpeg-2-transport-stream")
+        );
+        assert_eq!(uti_for_content_type(""), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_known_to_database() {
+        let db = Db::new().unwrap();
+        for (uti, content_type) in UTIS {
+            // Rows listed first for their UTI must be in the database.
+            if content_type_for_uti(uti) == Some(content_type) {
+                assert!(db.lookup_by_uti(uti).is_some(), "{uti}");
+            }
+        }
+        assert_eq!(&*db.lookup_by_uti("public.jpeg").unwrap().extension, "jpeg");
+        let png = db.lookup_by_extension("png").unwrap();
+        assert_eq!(png.uti(), Some("public.png"));
+    }
+}
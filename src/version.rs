@@ -0,0 +1,76 @@
+//! Metadata of the embedded database.
+
+use core::fmt;
+
+include!(concat!(env!("OUT_DIR"), "/version.rs"));
+
+/// Which dataset the embedded database was built from, returned by
+/// [`database_version`].
+///
+/// Log it at startup to tell which MIME dataset a deployment runs with, or
+/// compare it across deployments to spot stale ones. It describes the
+/// embedded files only: entries registered or loaded at runtime don't change
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DatabaseVersion {
+    /// Date (`YYYY-MM-DD`) the database files were last regenerated from
+    /// the upstream registries
+    pub updated: &'static str,
+    /// Hash of the database files (64-bit FNV-1a), which changes with any
+    /// edit to them, including hand edits between regenerations
+    pub hash: u64,
+}
+
+impl fmt::Display for DatabaseVersion {
+    /// Formats as `2026-10-16 (0123456789abcdef)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:016x})", self.updated, self.hash)
+    }
+}
+
+/// Returns the version of the embedded database.
+///
+/// The result doesn't depend on the `ext-db` and `content-type-db` features:
+/// a build without a table still reports the dataset the other comes from.
+///
+/// # Examples
+///
+/// ```
+/// let version = minimime::database_version();
+/// println!("MIME database {version}");
+/// assert_eq!(version.updated.len(), "2026-10-16".len());
+/// ```
+pub const fn database_version() -> DatabaseVersion {
+    DatabaseVersion {
+        updated: UPDATED,
+        hash: HASH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_version() {
+        // The hash covers the database files as they are on disk.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for file in [
+            include_str!("db/ext_mime.db"),
+            include_str!("db/content_type_mime.db"),
+            include_str!("db/content_type_ids.db"),
+        ] {
+            for &byte in file.as_bytes().iter().chain(&[0xff]) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        let version = database_version();
+        assert_eq!(version.hash, hash);
+        assert!(include_str!("db/version.db").contains(&format!("updated {}", version.updated)));
+
+        let shown = version.to_string();
+        assert_eq!(shown.len(), "2026-10-16 (0123456789abcdef)".len());
+        assert!(shown.starts_with(version.updated));
+    }
+}
@@ -0,0 +1,320 @@
+//! Classification of directory trees.
+//!
+//! Backup tools and asset pipelines need the type of every file under a
+//! directory. [`walk`] traverses one and yields each regular file with its
+//! entry, found by name and, optionally, by the magic bytes at the start of
+//! its content. Filters such as [`Walk::only_media_type`] narrow the
+//! results down.
+//!
+//! Requires the `walk` feature; [`Walk::par_iter`] also requires the
+//! `rayon` feature.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{db::read_file_prefix, Db, Info};
+
+/// A traversal of a directory tree, returned by [`walk`].
+///
+/// Iterate over it (or call [`Walk::par_iter`]) to classify the files.
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    db: &'a Db,
+    root: PathBuf,
+    media_type: Option<String>,
+    sniff: bool,
+    follow_links: bool,
+    max_depth: Option<usize>,
+}
+
+/// Traverses a directory tree, classifying every regular file in it by
+/// name.
+///
+/// Files are yielded in a stable order: depth-first, entries of a
+/// directory sorted by name. A file that can't be classified is yielded
+/// with `None`, unless a filter excludes it. Errors, such as a directory
+/// that can't be read, are yielded in place of the entries they hide;
+/// the traversal goes on after them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use minimime::{walk, Db};
+///
+/// let db = Db::new().unwrap();
+/// for file in walk::walk(&db, "assets").only_media_type("image") {
+///     let (path, info) = file?;
+///     println!("{}: {}", path.display(), info.unwrap().content_type);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn walk(db: &Db, root: impl AsRef<Path>) -> Walk<'_> {
+    Walk {
+        db,
+        root: root.as_ref().to_path_buf(),
+        media_type: None,
+        sniff: false,
+        follow_links: false,
+        max_depth: None,
+    }
+}
+
+impl<'a> Walk<'a> {
+    /// Only yields files whose entry has the given top-level type (`image`,
+    /// `video`, ...), compared ignoring ASCII case. Files without an entry
+    /// are skipped.
+    pub fn only_media_type(mut self, top_level: &str) -> Self {
+        self.media_type = Some(top_level.to_ascii_lowercase());
+        self
+    }
+
+    /// Also reads the first bytes of every file and sniffs them, combined
+    /// with the name as in [`Db::lookup_by_filename_and_magic`]. Off by
+    /// default.
+    pub fn sniff(mut self, sniff: bool) -> Self {
+        self.sniff = sniff;
+        self
+    }
+
+    /// Follows symbolic links to files and directories. Off by default, so
+    /// links are skipped; loops are detected and reported as errors.
+    pub fn follow_links(mut self, follow_links: bool) -> Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    /// Descends at most `depth` directories below the root; at 0 only the
+    /// root itself is classified, if it is a file.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Classifies the files on the rayon thread pool.
+    ///
+    /// The traversal itself stays sequential; reading and sniffing the
+    /// files, the costly part, runs in parallel. Results come in no
+    /// particular order. Requires the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use minimime::{walk, Db};
+    /// use rayon::prelude::*;
+    ///
+    /// let db = Db::new().unwrap();
+    /// let videos = walk::walk(&db, "library")
+    ///     .only_media_type("video")
+    ///     .sniff(true)
+    ///     .par_iter()
+    ///     .filter_map(Result::ok)
+    ///     .count();
+    /// println!("{videos} videos");
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(
+        self,
+    ) -> impl rayon::iter::ParallelIterator<Item = io::Result<(PathBuf, Option<&'a Info>)>> + 'a
+    {
+        use rayon::iter::{ParallelBridge, ParallelIterator};
+
+        let classifier = Classifier {
+            db: self.db,
+            media_type: self.media_type.clone(),
+            sniff: self.sniff,
+        };
+        self.entries()
+            .par_bridge()
+            .filter_map(move |entry| classifier.classify(entry))
+    }
+
+    fn entries(&self) -> walkdir::IntoIter {
+        let mut walker = walkdir::WalkDir::new(&self.root)
+            .follow_links(self.follow_links)
+            .sort_by_file_name();
+        if let Some(depth) = self.max_depth {
+            walker = walker.max_depth(depth);
+        }
+        walker.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for Walk<'a> {
+    type Item = io::Result<(PathBuf, Option<&'a Info>)>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        Iter {
+            entries: self.entries(),
+            classifier: Classifier {
+                db: self.db,
+                media_type: self.media_type,
+                sniff: self.sniff,
+            },
+        }
+    }
+}
+
+/// Iterator over the files of a [`Walk`].
+#[derive(Debug)]
+pub struct Iter<'a> {
+    entries: walkdir::IntoIter,
+    classifier: Classifier<'a>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = io::Result<(PathBuf, Option<&'a Info>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries
+            .by_ref()
+            .find_map(|entry| self.classifier.classify(entry))
+    }
+}
+
+/// The options of a [`Walk`] that classifying a file needs.
+#[derive(Debug, Clone)]
+struct Classifier<'a> {
+    db: &'a Db,
+    media_type: Option<String>,
+    sniff: bool,
+}
+
+impl<'a> Classifier<'a> {
+    /// Classifies a directory entry, returning `None` if it is skipped:
+    /// not a regular file, or excluded by the media type filter.
+    fn classify(
+        &self,
+        entry: walkdir::Result<walkdir::DirEntry>,
+    ) -> Option<io::Result<(PathBuf, Option<&'a Info>)>> {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => return Some(Err(err.into())),
+        };
+        if !entry.file_type().is_file() {
+            return None;
+        }
+        let path = entry.into_path();
+        let info = if self.sniff {
+            let prefix = match read_file_prefix(&path) {
+                Ok(prefix) => prefix,
+                Err(err) => return Some(Err(err)),
+            };
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            self.db.lookup_by_filename_and_magic(&name, &prefix)
+        } else {
+            self.db.lookup_by_path(&path)
+        };
+        if let Some(media_type) = &self.media_type {
+            let top_level = info?.content_type.split('/').next().unwrap_or_default();
+            if !top_level.eq_ignore_ascii_case(media_type) {
+                return None;
+            }
+        }
+        Some(Ok((path, info)))
+    }
+}
+
+#[cfg(all(test, feature = "ext-db", feature = "content-type-db"))]
+mod tests {
+    use super::*;
+
+    /// Creates a directory tree with an image, a misnamed PDF, a text file
+    /// and an unknown file, returning its root.
+    fn fixture(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("minimime-walk-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("docs/nested")).unwrap();
+        std::fs::write(root.join("logo.PNG"), b"\x89PNG\r\n\x1a\n").unwrap();
+        std::fs::write(root.join("docs/report.txt"), b"%PDF-1.7\n").unwrap();
+        std::fs::write(root.join("docs/nested/notes.md"), b"# Notes\n").unwrap();
+        std::fs::write(root.join("docs/nested/data.unknownext"), b"\0\0").unwrap();
+        root
+    }
+
+    fn names<'a>(
+        files: impl IntoIterator<Item = (PathBuf, Option<&'a Info>)>,
+    ) -> Vec<(String, Option<&'a str>)> {
+        files
+            .into_iter()
+            .map(|(path, info)| {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                (name, info.map(|info| &*info.content_type))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_walk() {
+        let db = Db::new().unwrap();
+        let root = fixture("sequential");
+
+        let files: Vec<_> = walk(&db, &root).into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            names(files),
+            [
+                ("data.unknownext".to_string(), None),
+                ("notes.md".to_string(), Some("text/markdown")),
+                ("report.txt".to_string(), Some("text/plain")),
+                ("logo.PNG".to_string(), Some("image/png")),
+            ]
+        );
+
+        let files = walk(&db, &root).only_media_type("Image").into_iter();
+        assert_eq!(
+            names(files.map(Result::unwrap)),
+            [("logo.PNG".to_string(), Some("image/png"))]
+        );
+
+        let files = walk(&db, &root)
+            .only_media_type("application")
+            .sniff(true)
+            .into_iter();
+        assert_eq!(
+            names(files.map(Result::unwrap)),
+            [("report.txt".to_string(), Some("application/pdf"))]
+        );
+
+        let files = walk(&db, &root).max_depth(1).into_iter();
+        assert_eq!(
+            names(files.map(Result::unwrap)),
+            [("logo.PNG".to_string(), Some("image/png"))]
+        );
+
+        let missing = walk(&db, root.join("missing"))
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(
+            missing[0].as_ref().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_iter() {
+        use rayon::iter::ParallelIterator;
+
+        let db = Db::new().unwrap();
+        let root = fixture("parallel");
+        let mut files: Vec<_> = walk(&db, &root)
+            .only_media_type("text")
+            .par_iter()
+            .map(Result::unwrap)
+            .collect();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(
+            names(files),
+            [
+                ("notes.md".to_string(), Some("text/markdown")),
+                ("report.txt".to_string(), Some("text/plain")),
+            ]
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+//! JavaScript bindings built with wasm-bindgen.
+//!
+//! Enabled by the `wasm` feature for the `wasm32-unknown-unknown` target, so
+//! browsers and Node.js use the same embedded database as the Rust API:
+//!
+//! ```js
+//! import init, { lookupByFilename } from "./minimime.js";
+//!
+//! await init();
+//! const info = lookupByFilename("report.pdf");
+//! console.assert(info.contentType === "application/pdf");
+//! console.assert(info.isBinary());
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+/// JavaScript view of [`crate::Info`].
+#[wasm_bindgen(js_name = Info, getter_with_clone)]
+#[derive(Clone, PartialEq)]
+pub struct JsInfo {
+    /// File extension (without the dot)
+    #[wasm_bindgen(readonly)]
+    pub extension: String,
+    /// MIME content type
+    #[wasm_bindgen(readonly, js_name = contentType)]
+    pub content_type: String,
+    /// Encoding type
+    #[wasm_bindgen(readonly)]
+    pub encoding: String,
+    /// Priority among rows sharing an extension or content type
+    #[wasm_bindgen(readonly)]
+    pub weight: u16,
+    /// Tags such as `archive` or `document`
+    #[wasm_bindgen(readonly)]
+    pub tags: Vec<String>,
+}
+
+#[wasm_bindgen(js_class = Info)]
+impl JsInfo {
+    /// Whether the file type is binary.
+    #[wasm_bindgen(js_name = isBinary)]
+    pub fn is_binary(&self) -> bool {
+        crate::BINARY_ENCODINGS.contains(&self.encoding.as_str())
+    }
+}
+
+impl From<crate::Info> for JsInfo {
+    fn from(info: crate::Info) -> Self {
+        JsInfo {
+            extension: info.extension.into(),
+            content_type: info.content_type.into(),
+            encoding: info.encoding.into(),
+            weight: info.weight,
+            tags: info.tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+}
+
+/// Looks up MIME information by filename.
+#[wasm_bindgen(js_name = lookupByFilename)]
+pub fn lookup_by_filename(filename: &str) -> Option<JsInfo> {
+    crate::lookup_by_filename(filename).map(JsInfo::from)
+}
+
+/// Looks up MIME information by file extension.
+#[wasm_bindgen(js_name = lookupByExtension)]
+pub fn lookup_by_extension(extension: &str) -> Option<JsInfo> {
+    crate::lookup_by_extension(extension).map(JsInfo::from)
+}
+
+/// Looks up MIME information by content type.
+#[wasm_bindgen(js_name = lookupByContentType)]
+pub fn lookup_by_content_type(content_type: &str) -> Option<JsInfo> {
+    crate::lookup_by_content_type(content_type).map(JsInfo::from)
+}
+
+/// Looks up MIME information by the magic bytes at the start of a file.
+#[wasm_bindgen(js_name = lookupByMagic)]
+pub fn lookup_by_magic(data: &[u8]) -> Option<JsInfo> {
+    crate::lookup_by_magic(data).map(JsInfo::from)
+}
@@ -0,0 +1,478 @@
+//! The freedesktop.org shared-mime-info database.
+//!
+//! Linux desktops describe file types in `/usr/share/mime`, compiled by
+//! `update-mime-database` from the packages installed on the system.
+//! [`Db::with_system_mime_info`] reads its `globs2`, `aliases` and `magic`
+//! files over the embedded database, so an application agrees with the file
+//! manager on types the embedded data lacks or names differently.
+
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{Db, Error, Info, Origin};
+
+/// Where shared-mime-info installs the compiled database.
+const SYSTEM_DIR: &str = "/usr/share/mime";
+
+/// First line of a compiled `magic` file.
+const MAGIC_HEADER: &[u8] = b"MIME-Magic\0\n";
+
+/// A `[priority:type]` section of the `magic` file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct MagicRule {
+    pub(crate) priority: u16,
+    content_type: String,
+    /// The section's lines in file order; see [`any_matches`]
+    matches: Vec<MagicMatch>,
+}
+
+/// One line of a magic section: `value` (under `mask`) is looked for at
+/// each of the `range` offsets from `offset`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct MagicMatch {
+    indent: usize,
+    offset: usize,
+    range: usize,
+    value: Vec<u8>,
+    mask: Option<Vec<u8>>,
+}
+
+impl MagicMatch {
+    fn matches(&self, bytes: &[u8]) -> bool {
+        (self.offset..self.offset.saturating_add(self.range)).any(|start| {
+            // Offsets come from the file; one ending past `usize::MAX`
+            // matches nothing.
+            let Some(window) = start
+                .checked_add(self.value.len())
+                .and_then(|end| bytes.get(start..end))
+            else {
+                return false;
+            };
+            match &self.mask {
+                Some(mask) => window
+                    .iter()
+                    .zip(mask)
+                    .zip(&self.value)
+                    .all(|((byte, mask), value)| byte & mask == value & mask),
+                None => window == self.value,
+            }
+        })
+    }
+}
+
+impl Db {
+    /// Creates a database from the embedded data overlaid with the system's
+    /// shared-mime-info database in `/usr/share/mime`.
+    ///
+    /// See [`Db::with_mime_info_dir`] for how the two are combined. Requires
+    /// the `xdg` feature.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Db)` with the system entries on top of the embedded ones
+    /// * `Err` if `globs2` can't be read or a file is malformed, e.g. on a
+    ///   system without shared-mime-info
+    pub fn with_system_mime_info() -> Result<Self, Error> {
+        Self::with_mime_info_dir(SYSTEM_DIR)
+    }
+
+    /// Creates a database from the embedded data overlaid with a
+    /// shared-mime-info database directory, such as `~/.local/share/mime`.
+    ///
+    /// * `globs2`: `*.ext` globs replace the embedded entries for their
+    ///   extensions, keeping the embedded encoding and tags of the same
+    ///   content type. Other globs become filename rules (see
+    ///   [`Db::add_filename_rule`]) for types that have an extension.
+    ///   Character classes such as `*.[1-9]` and case-sensitive globs with
+    ///   uppercase letters can't be matched by this crate and are skipped.
+    /// * `aliases`: alias content types the embedded data doesn't know look
+    ///   up their canonical type.
+    /// * `magic`: the signatures are tried, by priority, before the built-in
+    ///   ones in [`Db::lookup_by_magic`].
+    ///
+    /// Anything the directory doesn't describe falls back to the embedded
+    /// database. `aliases` and `magic` are optional.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Db)` with the directory's entries on top of the embedded ones
+    /// * `Err` if `globs2` can't be read or a file is malformed
+    pub fn with_mime_info_dir(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let in_file = |name: &str| {
+            let path = dir.join(name);
+            move |err: io::Error| Error::from(err).in_file(&path)
+        };
+        let mut db = Db::new()?;
+        let globs = std::fs::read_to_string(dir.join("globs2")).map_err(in_file("globs2"))?;
+        db.load_globs(&globs);
+        if let Some(aliases) = read_optional(dir.join("aliases")).map_err(in_file("aliases"))? {
+            db.load_aliases(&String::from_utf8_lossy(&aliases));
+        }
+        if let Some(magic) = read_optional(dir.join("magic")).map_err(in_file("magic"))? {
+            db.magic_rules = Arc::new(parse_magic(&magic).map_err(in_file("magic"))?);
+        }
+        Ok(db)
+    }
+
+    /// Registers the entries of a `globs2` file, whose lines read
+    /// `weight:type:glob[:flags]`.
+    fn load_globs(&mut self, text: &str) {
+        let mut globs: Vec<(u16, &str, &str)> = text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, ':');
+                let weight = fields.next()?.parse().ok()?;
+                let content_type = fields.next()?;
+                let glob = fields.next()?;
+                let case_sensitive = fields
+                    .next()
+                    .is_some_and(|flags| flags.split(',').any(|flag| flag == "cs"));
+                let supported = !glob.contains('[')
+                    && glob != "__NOGLOBS__"
+                    && !(case_sensitive && glob.bytes().any(|b| b.is_ascii_uppercase()));
+                supported.then_some((weight, content_type, glob))
+            })
+            .collect();
+        // Heaviest first, as the file is generated. At equal weights,
+        // lowercase globs go first: lookups ignore case, and `*.C` (C++)
+        // must not take `.c` files from `*.c` (C).
+        globs.sort_by_key(|&(weight, _, glob)| {
+            (
+                std::cmp::Reverse(weight),
+                glob.bytes().any(|b| b.is_ascii_uppercase()),
+            )
+        });
+
+        let mut seen = HashSet::new();
+        let mut rules = Vec::new();
+        for (weight, content_type, glob) in globs {
+            match glob.strip_prefix("*.") {
+                Some(ext) if !ext.contains(['*', '?']) && ext.matches('.').count() <= 1 => {
+                    if seen.insert(ext.to_lowercase()) {
+                        let info = self.derived_info(ext, content_type, weight);
+                        self.register(info);
+                    }
+                }
+                _ => rules.push((glob, content_type)),
+            }
+        }
+        // Rules reuse the entry of their type, so add them once every
+        // extension is known.
+        for (glob, content_type) in rules {
+            if let Some(info) = self.find_content_type(content_type) {
+                let info = Info {
+                    origin: Origin::System,
+                    ..info.clone()
+                };
+                self.add_filename_rule(glob, info);
+            }
+        }
+    }
+
+    /// Maps the unknown types of an `aliases` file, whose lines read
+    /// `alias canonical`, to their canonical entries.
+    fn load_aliases(&mut self, text: &str) {
+        for line in text.lines() {
+            let Some((alias, canonical)) = line.split_once(' ') else {
+                continue;
+            };
+            if self.find_content_type(alias).is_some() {
+                continue;
+            }
+            if let Some(info) = self.find_content_type(canonical.trim()).cloned() {
+                Arc::make_mut(&mut self.content_type_db)
+                    .insert(Cow::Owned(alias.to_ascii_lowercase()), info);
+            }
+        }
+    }
+
+    /// Finds the entry of the first shared-mime-info signature matching
+    /// `bytes` whose type is in the database.
+    pub(crate) fn lookup_by_system_magic(&self, bytes: &[u8]) -> Option<&Info> {
+        self.magic_rules
+            .iter()
+            .filter(|rule| any_matches(&rule.matches, 0, bytes))
+            .find_map(|rule| self.find_content_type(&rule.content_type))
+    }
+}
+
+/// Reads a file, or returns `None` if it doesn't exist.
+fn read_optional(path: PathBuf) -> io::Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Whether any of the lines at `indent` in `matches` matches along with,
+/// if it has any, one of its children: the lines below it indented deeper.
+fn any_matches(matches: &[MagicMatch], indent: usize, bytes: &[u8]) -> bool {
+    let mut rest = matches;
+    while let Some((first, tail)) = rest.split_first() {
+        let children = tail.iter().take_while(|m| m.indent > indent).count();
+        if first.indent == indent
+            && first.matches(bytes)
+            && (children == 0 || any_matches(&tail[..children], indent + 1, bytes))
+        {
+            return true;
+        }
+        rest = &tail[children..];
+    }
+    false
+}
+
+/// Parses a compiled `magic` file, returning its sections by descending
+/// priority.
+fn parse_magic(data: &[u8]) -> io::Result<Vec<MagicRule>> {
+    let mut data = data
+        .strip_prefix(MAGIC_HEADER)
+        .ok_or_else(|| invalid("not a shared-mime-info magic file"))?;
+    let mut rules: Vec<MagicRule> = Vec::new();
+    while !data.is_empty() {
+        if let Some(section) = data.strip_prefix(b"[") {
+            let end = section
+                .iter()
+                .position(|&b| b == b'\n')
+                .ok_or_else(|| invalid("truncated magic section header"))?;
+            let header = std::str::from_utf8(&section[..end])
+                .ok()
+                .and_then(|header| header.strip_suffix(']'))
+                .and_then(|header| header.split_once(':'))
+                .ok_or_else(|| invalid("malformed magic section header"))?;
+            rules.push(MagicRule {
+                priority: header
+                    .0
+                    .parse()
+                    .map_err(|_| invalid("malformed magic priority"))?,
+                content_type: header.1.to_string(),
+                matches: Vec::new(),
+            });
+            data = &section[end + 1..];
+        } else {
+            let rule = rules
+                .last_mut()
+                .ok_or_else(|| invalid("magic line outside a section"))?;
+            let (line, rest) = parse_magic_line(data)?;
+            rule.matches.push(line);
+            data = rest;
+        }
+    }
+    rules.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+    Ok(rules)
+}
+
+/// Parses a `[indent]>offset=value[&mask][~word-size][+range]` line,
+/// returning it and the data after it.
+fn parse_magic_line(data: &[u8]) -> io::Result<(MagicMatch, &[u8])> {
+    let (indent, data) = parse_number(data);
+    let data = data
+        .strip_prefix(b">")
+        .ok_or_else(|| invalid("malformed magic line"))?;
+    let (offset, data) = parse_number(data);
+    let data = data
+        .strip_prefix(b"=")
+        .ok_or_else(|| invalid("malformed magic line"))?;
+    let len = match data {
+        [hi, lo, ..] => usize::from(u16::from_be_bytes([*hi, *lo])),
+        _ => return Err(invalid("truncated magic value")),
+    };
+    let (mut value, mut data) = take_value(&data[2..], len)?;
+    let mut mask = None;
+    if let Some(rest) = data.strip_prefix(b"&") {
+        let (bytes, rest) = take_value(rest, len)?;
+        mask = Some(bytes);
+        data = rest;
+    }
+    let mut word_size = 1;
+    if let Some(rest) = data.strip_prefix(b"~") {
+        (word_size, data) = parse_number(rest);
+    }
+    let mut range = 1;
+    if let Some(rest) = data.strip_prefix(b"+") {
+        (range, data) = parse_number(rest);
+    }
+    let data = data
+        .strip_prefix(b"\n")
+        .ok_or_else(|| invalid("malformed magic line"))?;
+
+    // Words are stored big-endian but compared in host byte order.
+    if cfg!(target_endian = "little") && word_size > 1 {
+        for bytes in std::iter::once(&mut value).chain(mask.as_mut()) {
+            bytes
+                .chunks_exact_mut(word_size)
+                .for_each(|word| word.reverse());
+        }
+    }
+    Ok((
+        MagicMatch {
+            indent,
+            offset,
+            range: range.max(1),
+            value,
+            mask,
+        },
+        data,
+    ))
+}
+
+/// Splits a `len`-byte value off the front of `data`.
+fn take_value(data: &[u8], len: usize) -> io::Result<(Vec<u8>, &[u8])> {
+    if data.len() < len {
+        return Err(invalid("truncated magic value"));
+    }
+    Ok((data[..len].to_vec(), &data[len..]))
+}
+
+/// Parses a run of ASCII digits, which is 0 if empty.
+fn parse_number(data: &[u8]) -> (usize, &[u8]) {
+    let digits = data.iter().take_while(|b| b.is_ascii_digit()).count();
+    let number = data[..digits].iter().fold(0usize, |number, &digit| {
+        number
+            .saturating_mul(10)
+            .saturating_add(usize::from(digit - b'0'))
+    });
+    (number, &data[digits..])
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A magic line as `(prefix, value, suffix)` around the length-prefixed
+    /// value.
+    type Line<'a> = (&'a str, &'a [u8], &'a [u8]);
+
+    /// Builds a `magic` file from `(section header, lines)` pairs.
+    fn magic(sections: &[(&str, &[Line])]) -> Vec<u8> {
+        let mut data = MAGIC_HEADER.to_vec();
+        for (header, lines) in sections {
+            data.extend_from_slice(header.as_bytes());
+            data.push(b'\n');
+            for (prefix, value, suffix) in lines.iter() {
+                data.extend_from_slice(prefix.as_bytes());
+                data.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                data.extend_from_slice(value);
+                data.extend_from_slice(suffix);
+                data.push(b'\n');
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_magic_matching() {
+        let rules = parse_magic(&magic(&[
+            ("[40:application/x-acme]", &[(">4=", b"ACME", b"+8")]),
+            (
+                "[60:application/x-nested]",
+                &[
+                    (">0=", b"NEST", b""),
+                    ("1>4=", b"A", b""),
+                    ("1>4=", b"\x40", b"&\xF0"),
+                ],
+            ),
+        ]))
+        .unwrap();
+        assert_eq!(rules[0].content_type, "application/x-nested");
+        let matching = |bytes: &[u8]| {
+            rules
+                .iter()
+                .find(|rule| any_matches(&rule.matches, 0, bytes))
+                .map(|rule| &*rule.content_type)
+        };
+        assert_eq!(matching(b"....xxACME"), Some("application/x-acme"));
+        assert_eq!(matching(b"...........ACME"), Some("application/x-acme"));
+        assert_eq!(matching(b"............ACME"), None);
+        assert_eq!(matching(b"NESTA"), Some("application/x-nested"));
+        assert_eq!(matching(b"NESTO"), Some("application/x-nested"));
+        assert_eq!(matching(b"NEST!"), None);
+
+        let hostile = MagicMatch {
+            indent: 0,
+            offset: usize::MAX - 1,
+            range: 4,
+            value: b"ACME".to_vec(),
+            mask: None,
+        };
+        assert!(!hostile.matches(b"ACME"));
+
+        assert!(parse_magic(b"not magic").is_err());
+        assert!(parse_magic(&magic(&[("[50:x/y]", &[(">0=", b"AB", b"&A")])])).is_err());
+    }
+
+    #[cfg(all(feature = "ext-db", feature = "content-type-db"))]
+    #[test]
+    fn test_with_mime_info_dir() {
+        let dir = std::env::temp_dir().join(format!("minimime-xdg-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("globs2"),
+            "# generated\n\
+             60:application/x-acme:*.acme\n\
+             50:application/x-acme-old:*.acme\n\
+             50:text/x-makefile:makefile\n\
+             50:text/x-makefile:*.mk\n\
+             50:application/x-troff-man:*.[1-9]\n\
+             50:text/x-c++src:*.C:cs\n\
+             50:text/x-c++src:*.C\n\
+             50:text/x-csrc:*.c\n\
+             50:application/x-gtar:*.tar.gz\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("aliases"),
+            "application/x-acme-legacy application/x-acme\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("magic"),
+            magic(&[("[50:application/x-acme]", &[(">0=", b"ACME", b"")])]),
+        )
+        .unwrap();
+
+        let db = Db::with_mime_info_dir(&dir).unwrap();
+        let acme = db.lookup_by_filename("a.ACME").unwrap();
+        assert_eq!(&*acme.content_type, "application/x-acme");
+        assert_eq!(&*acme.encoding, "base64");
+        assert_eq!(acme.origin, Origin::System);
+        let makefile = db.lookup_by_filename("src/Makefile").unwrap();
+        assert_eq!(&*makefile.content_type, "text/x-makefile");
+        assert_eq!(makefile.origin, Origin::System);
+        assert!(!db.lookup_by_filename("rules.mk").unwrap().is_binary());
+        assert_eq!(
+            &*db.lookup_by_content_type("application/x-acme-legacy")
+                .unwrap()
+                .extension,
+            "acme"
+        );
+        assert_eq!(&*db.lookup_by_magic(b"ACME....").unwrap().extension, "acme");
+        assert_eq!(
+            &*db.lookup_by_extension("c").unwrap().content_type,
+            "text/x-csrc"
+        );
+        assert!(db.lookup_by_extension("1").is_none());
+        // Everything else comes from the embedded database.
+        assert_eq!(&*db.lookup_by_magic(b"%PDF-1.7").unwrap().extension, "pdf");
+        let png = db.lookup_by_extension("png").unwrap();
+        assert_eq!(&*png.content_type, "image/png");
+        assert_eq!(png.origin, Origin::Embedded);
+
+        std::fs::remove_file(dir.join("globs2")).unwrap();
+        assert!(Db::with_mime_info_dir(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,554 @@
+//! Maintenance tasks, run with `cargo xtask <task>`.
+//!
+//! ```text
+//! cargo xtask update-db                 # merge upstream entries into src/db
+//! cargo xtask update-db --check         # report only; exit 1 if out of date
+//! cargo xtask update-db --apache mime.types --iana iana/
+//! ```
+//!
+//! `update-db` reads the Apache httpd `mime.types` file and the IANA media
+//! type registry (downloaded with `curl`, or read from local copies) and
+//! merges them into the embedded database files:
+//!
+//! - Extensions Apache lists that `ext_mime.db` lacks are added.
+//! - Content types Apache lists that `content_type_mime.db` lacks are added,
+//!   with the first extension Apache gives them.
+//! - New content types get the next IDs in `content_type_ids.db`.
+//! - The `updated` date in `version.db` becomes today's (UTC).
+//!
+//! Existing rows are curated by hand and are never changed or removed; where
+//! they disagree with upstream the report says so instead. Output is sorted
+//! and aligned the way the files already are, so running the task twice on
+//! the same inputs changes nothing.
+
+mod sources;
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sources::{Apache, Iana};
+
+const USAGE: &str = "\
+usage: cargo xtask update-db [--check] [--apache <file>] [--iana <dir>]
+
+options:
+  --check            print the report without writing; exit 1 if entries
+                     would be added
+  --apache <file>    read this mime.types instead of downloading it
+  --iana <dir>       read the registry CSVs (application.csv, ...) from this
+                     directory instead of downloading them
+";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some((task, options)) = args.split_first() else {
+        eprint!("{USAGE}");
+        return ExitCode::from(2);
+    };
+    if task != "update-db" {
+        eprint!("{USAGE}");
+        return ExitCode::from(2);
+    }
+    let mut check = false;
+    let mut apache = None;
+    let mut iana = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--check" => check = true,
+            "--apache" => apache = options.next().map(PathBuf::from),
+            "--iana" => iana = options.next().map(PathBuf::from),
+            _ => {
+                eprint!("{USAGE}");
+                return ExitCode::from(2);
+            }
+        }
+    }
+    match update_db(check, apache.as_deref(), iana.as_deref()) {
+        Ok(false) if check => ExitCode::FAILURE,
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("xtask: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs `update-db`, returning whether the files were already up to date.
+fn update_db(check: bool, apache: Option<&Path>, iana: Option<&Path>) -> io::Result<bool> {
+    let apache = match apache {
+        Some(path) => Apache::parse(&fs::read_to_string(path)?),
+        None => Apache::parse(&sources::download(sources::APACHE_URL)?),
+    };
+    let iana = match iana {
+        Some(dir) => Iana::read_dir(dir)?,
+        None => Iana::download()?,
+    };
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/db");
+    let mut files = DbFiles {
+        ext: DbFile::parse(&fs::read_to_string(dir.join("ext_mime.db"))?, 0),
+        content_type: DbFile::parse(&fs::read_to_string(dir.join("content_type_mime.db"))?, 1),
+        ids: fs::read_to_string(dir.join("content_type_ids.db"))?,
+    };
+    let report = files.merge(&apache, &iana);
+    print!("{report}");
+    if report.is_up_to_date() || check {
+        return Ok(report.is_up_to_date());
+    }
+    fs::write(dir.join("ext_mime.db"), files.ext.render())?;
+    fs::write(
+        dir.join("content_type_mime.db"),
+        files.content_type.render(),
+    )?;
+    fs::write(dir.join("content_type_ids.db"), files.ids)?;
+    let version = fs::read_to_string(dir.join("version.db"))?;
+    fs::write(dir.join("version.db"), set_updated(&version, &today()))?;
+    Ok(false)
+}
+
+/// Replaces the date of the `updated` line of `version.db`.
+fn set_updated(version: &str, date: &str) -> String {
+    let mut text = String::new();
+    for line in version.lines() {
+        if line.starts_with("updated ") {
+            text.push_str(&format!("updated {date}"));
+        } else {
+            text.push_str(line);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    civil_date(seconds / 86_400)
+}
+
+/// Converts days since 1970-01-01 to a `YYYY-MM-DD` date (Howard Hinnant's
+/// `civil_from_days`, for the proleptic Gregorian calendar).
+fn civil_date(days: u64) -> String {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Encoding of a new row: what the mime-types gem, where the original
+/// files come from, defaults to, except that JSON is `8bit` like the JSON
+/// types already in the database.
+fn encoding_for(content_type: &str) -> &'static str {
+    if content_type.starts_with("text/") {
+        "quoted-printable"
+    } else if content_type == "application/json" || content_type.ends_with("+json") {
+        "8bit"
+    } else {
+        "base64"
+    }
+}
+
+/// Formats a row with the fixed-width columns of the embedded files.
+fn format_row(extension: &str, content_type: &str, encoding: &str) -> String {
+    format!("{extension:<11} {content_type:<73} {encoding:<16}")
+}
+
+/// A database file, kept line by line so that curated rows and comments
+/// survive the rewrite byte for byte.
+struct DbFile {
+    lines: Vec<String>,
+    /// Rows by key (one of the columns, lowercased); the first row wins
+    rows: HashMap<String, String>,
+    /// Column holding the key
+    key: usize,
+}
+
+impl DbFile {
+    fn parse(text: &str, key: usize) -> Self {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let mut rows = HashMap::new();
+        for line in &lines {
+            if let Some(field) = Self::key_of(line, key) {
+                rows.entry(field).or_insert_with(|| line.clone());
+            }
+        }
+        DbFile { lines, rows, key }
+    }
+
+    fn key_of(line: &str, key: usize) -> Option<String> {
+        if line.starts_with('#') {
+            return None;
+        }
+        line.split_whitespace()
+            .nth(key)
+            .map(str::to_ascii_lowercase)
+    }
+
+    /// The content type of the row for a key.
+    fn content_type(&self, key: &str) -> Option<&str> {
+        self.rows
+            .get(key)
+            .and_then(|row| row.split_whitespace().nth(1))
+    }
+
+    /// Adds a row before the first row with a greater key. The files are
+    /// sorted by key, give or take a few hand-placed rows, so this keeps
+    /// them sorted without moving existing rows.
+    fn insert(&mut self, extension: &str, content_type: &str) {
+        let key = if self.key == 0 {
+            extension
+        } else {
+            content_type
+        }
+        .to_ascii_lowercase();
+        let row = format_row(extension, content_type, encoding_for(content_type));
+        let at = self
+            .lines
+            .iter()
+            .position(|line| Self::key_of(line, self.key).is_some_and(|other| other > key))
+            .unwrap_or(self.lines.len());
+        self.lines.insert(at, row.clone());
+        self.rows.insert(key, row);
+    }
+
+    fn render(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+struct DbFiles {
+    ext: DbFile,
+    content_type: DbFile,
+    /// `content_type_ids.db`, appended to as is
+    ids: String,
+}
+
+impl DbFiles {
+    fn merge(&mut self, apache: &Apache, iana: &Iana) -> Report {
+        let mut report = Report::default();
+        // Known content types in the spelling the database uses.
+        let mut spellings: HashMap<String, String> = HashMap::new();
+        for row in self
+            .ext
+            .rows
+            .values()
+            .chain(self.content_type.rows.values())
+        {
+            if let Some(content_type) = row.split_whitespace().nth(1) {
+                spellings
+                    .entry(content_type.to_ascii_lowercase())
+                    .or_insert_with(|| content_type.to_string());
+            }
+        }
+
+        for (content_type, extensions) in &apache.types {
+            let lower = content_type.to_ascii_lowercase();
+            let content_type = spellings.get(&lower).unwrap_or(content_type).clone();
+            for extension in extensions {
+                match self.ext.content_type(extension) {
+                    None => {
+                        self.ext.insert(extension, &content_type);
+                        report
+                            .added_extensions
+                            .push((extension.clone(), content_type.clone()));
+                    }
+                    Some(ours) if !ours.eq_ignore_ascii_case(&content_type) => {
+                        report.differing.push((
+                            extension.clone(),
+                            ours.to_string(),
+                            content_type.clone(),
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+            if self.content_type.rows.contains_key(&lower) {
+                continue;
+            }
+            // Prefer an extension the merged extension table maps back to
+            // this type.
+            let Some(extension) = extensions
+                .iter()
+                .find(|ext| {
+                    self.ext
+                        .content_type(ext)
+                        .is_some_and(|ours| ours.eq_ignore_ascii_case(&content_type))
+                })
+                .or(extensions.first())
+            else {
+                continue;
+            };
+            self.content_type.insert(extension, &content_type);
+            report
+                .added_content_types
+                .push((content_type.clone(), extension.clone()));
+        }
+
+        self.assign_ids(&mut report);
+
+        let ours: BTreeSet<&str> = self
+            .content_type
+            .rows
+            .values()
+            .filter_map(|row| row.split_whitespace().nth(1))
+            .collect();
+        for content_type in ours {
+            let lower = content_type.to_ascii_lowercase();
+            if iana.obsolete.contains(&lower) {
+                report.obsolete.push(content_type.to_string());
+            } else if !iana.registered.contains(&lower)
+                && !apache.types.contains_key(&lower)
+                && !is_unregistered_tree(&lower)
+            {
+                report.unknown_upstream.push(content_type.to_string());
+            }
+        }
+        report
+    }
+
+    /// Appends an ID for every added content type that has none yet.
+    fn assign_ids(&mut self, report: &mut Report) {
+        let mut next = 1;
+        let mut known = HashMap::new();
+        for line in self.ids.lines().filter(|line| !line.starts_with('#')) {
+            let mut fields = line.split_whitespace();
+            if let (Some(id), Some(content_type)) = (fields.next(), fields.next()) {
+                let id: u32 = id.parse().unwrap_or_default();
+                next = next.max(id + 1);
+                known.insert(content_type.to_ascii_lowercase(), id);
+            }
+        }
+        for (content_type, _) in &report.added_content_types {
+            if known.contains_key(&content_type.to_ascii_lowercase()) {
+                continue;
+            }
+            if !self.ids.is_empty() && !self.ids.ends_with('\n') {
+                self.ids.push('\n');
+            }
+            self.ids.push_str(&format!("{next:<5} {content_type}\n"));
+            next += 1;
+        }
+    }
+}
+
+/// Whether a content type is in a tree that isn't registered with IANA:
+/// `x-` subtypes and `x-` top-level types.
+fn is_unregistered_tree(content_type: &str) -> bool {
+    let (top_level, subtype) = content_type.split_once('/').unwrap_or((content_type, ""));
+    top_level.starts_with("x-") || subtype.starts_with("x-") || subtype.starts_with("x.")
+}
+
+/// What `update-db` changed, and what it left alone.
+#[derive(Default)]
+struct Report {
+    /// `(extension, content type)`
+    added_extensions: Vec<(String, String)>,
+    /// `(content type, extension)`
+    added_content_types: Vec<(String, String)>,
+    /// `(extension, ours, Apache's)`
+    differing: Vec<(String, String, String)>,
+    /// Content types IANA marks obsoleted or deprecated
+    obsolete: Vec<String>,
+    /// Content types neither registered with IANA nor listed by Apache,
+    /// outside the `x-` trees
+    unknown_upstream: Vec<String>,
+}
+
+impl Report {
+    fn is_up_to_date(&self) -> bool {
+        self.added_extensions.is_empty() && self.added_content_types.is_empty()
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "ext_mime.db: {} added", self.added_extensions.len())?;
+        for (extension, content_type) in &self.added_extensions {
+            writeln!(f, "  + {extension} {content_type}")?;
+        }
+        writeln!(
+            f,
+            "content_type_mime.db: {} added",
+            self.added_content_types.len()
+        )?;
+        for (content_type, extension) in &self.added_content_types {
+            writeln!(f, "  + {content_type} ({extension})")?;
+        }
+        writeln!(f, "kept, Apache disagrees: {}", self.differing.len())?;
+        for (extension, ours, theirs) in &self.differing {
+            writeln!(f, "  {extension}: {ours} (Apache: {theirs})")?;
+        }
+        writeln!(
+            f,
+            "obsoleted or deprecated by IANA: {}",
+            self.obsolete.len()
+        )?;
+        for content_type in &self.obsolete {
+            writeln!(f, "  {content_type}")?;
+        }
+        writeln!(
+            f,
+            "not registered with IANA nor listed by Apache: {}",
+            self.unknown_upstream.len()
+        )?;
+        for content_type in &self.unknown_upstream {
+            writeln!(f, "  {content_type}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APACHE: &str = "\
+# This file maps Internet media types to unique file extension(s).
+application/json\t\t\t\tjson
+# application/jose
+application/vnd.acme.report\t\t\trpt rep
+image/jpeg\t\t\t\t\tjpeg jpg jpe
+text/x-acme\t\t\t\t\tjpg
+";
+
+    fn files() -> DbFiles {
+        DbFiles {
+            ext: DbFile::parse(
+                &[
+                    format_row("jpeg", "image/jpeg", "base64"),
+                    format_row("jpg", "image/jpeg", "base64"),
+                    format_row("json", "application/json", "8bit"),
+                ]
+                .join("\n"),
+                0,
+            ),
+            content_type: DbFile::parse(
+                &[
+                    format_row("json", "application/json", "8bit"),
+                    format_row("jpeg", "image/jpeg", "base64"),
+                    format_row("zz", "application/vnd.acme.zz", "base64"),
+                ]
+                .join("\n"),
+                1,
+            ),
+            ids: "# IDs\n1     application/json\n2     image/jpeg\n".to_string(),
+        }
+    }
+
+    fn iana() -> Iana {
+        Iana::parse(
+            "application",
+            "Name,Template,Reference\n\
+             json,application/json,[RFC8259]\n\
+             \"vnd.acme.zz - OBSOLETED, use vnd.acme.report\",,[Acme]\n",
+        )
+    }
+
+    #[test]
+    fn test_format_row_round_trips() {
+        for file in ["ext_mime.db", "content_type_mime.db"] {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../src/db")
+                .join(file);
+            let text = fs::read_to_string(path).unwrap();
+            for line in text.lines() {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                assert_eq!(format_row(fields[0], fields[1], fields[2]), line);
+            }
+            let key = usize::from(file != "ext_mime.db");
+            assert_eq!(DbFile::parse(&text, key).render(), text, "{file}");
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut files = files();
+        let report = files.merge(&Apache::parse(APACHE), &iana());
+        assert_eq!(
+            report.added_extensions,
+            [
+                ("rpt".to_string(), "application/vnd.acme.report".to_string()),
+                ("rep".to_string(), "application/vnd.acme.report".to_string()),
+                ("jpe".to_string(), "image/jpeg".to_string()),
+            ]
+        );
+        assert_eq!(
+            report.added_content_types,
+            [
+                ("application/vnd.acme.report".to_string(), "rpt".to_string()),
+                ("text/x-acme".to_string(), "jpg".to_string()),
+            ]
+        );
+        assert_eq!(
+            report.differing,
+            [(
+                "jpg".to_string(),
+                "image/jpeg".to_string(),
+                "text/x-acme".to_string()
+            )]
+        );
+        assert_eq!(report.obsolete, ["application/vnd.acme.zz"]);
+        assert!(report.unknown_upstream.is_empty());
+        assert!(files
+            .ids
+            .ends_with("2     image/jpeg\n3     application/vnd.acme.report\n4     text/x-acme\n"));
+        assert_eq!(
+            files.content_type.rows["text/x-acme"],
+            format_row("jpg", "text/x-acme", "quoted-printable")
+        );
+
+        // A second run on the merged files finds nothing to add.
+        let mut again = DbFiles {
+            ext: DbFile::parse(&files.ext.render(), 0),
+            content_type: DbFile::parse(&files.content_type.render(), 1),
+            ids: files.ids.clone(),
+        };
+        let report = again.merge(&Apache::parse(APACHE), &iana());
+        assert!(report.is_up_to_date());
+        assert_eq!(again.ext.render(), files.ext.render());
+        assert_eq!(again.ids, files.ids);
+    }
+
+    #[test]
+    fn test_civil_date() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(59), "1970-03-01");
+        assert_eq!(civil_date(11_016), "2000-02-29");
+        assert_eq!(civil_date(20_742), "2026-10-16");
+        assert_eq!(
+            set_updated("# note\nupdated 2020-01-01\n", "2026-10-16"),
+            "# note\nupdated 2026-10-16\n"
+        );
+    }
+
+    #[test]
+    fn test_encoding_for() {
+        assert_eq!(encoding_for("text/csv"), "quoted-printable");
+        assert_eq!(encoding_for("application/json"), "8bit");
+        assert_eq!(encoding_for("application/ld+json"), "8bit");
+        assert_eq!(encoding_for("image/png"), "base64");
+    }
+}
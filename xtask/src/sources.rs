@@ -0,0 +1,210 @@
+//! The upstream registries `update-db` merges from.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, io,
+    path::Path,
+    process::Command,
+};
+
+/// The Apache httpd `mime.types` file.
+pub const APACHE_URL: &str =
+    "https://svn.apache.org/repos/asf/httpd/httpd/trunk/docs/conf/mime.types";
+
+/// The IANA media type registry, one CSV file per top-level type.
+const IANA_URL: &str = "https://www.iana.org/assignments/media-types";
+
+/// Top-level types with a registry in [`IANA_URL`].
+const IANA_REGISTRIES: &[&str] = &[
+    "application",
+    "audio",
+    "font",
+    "haptics",
+    "image",
+    "message",
+    "model",
+    "multipart",
+    "text",
+    "video",
+];
+
+/// Fetches a URL with `curl`, which keeps the task free of dependencies.
+pub fn download(url: &str) -> io::Result<String> {
+    eprintln!("xtask: downloading {url}");
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", url])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "downloading {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Content types with extensions, from Apache's `mime.types`.
+pub struct Apache {
+    /// Extensions by lowercase content type, in the order the file lists
+    /// them; types without extensions are left out
+    pub types: BTreeMap<String, Vec<String>>,
+}
+
+impl Apache {
+    /// Parses `type/subtype ext1 ext2 ...` lines. Comment lines, including
+    /// the commented-out registered types without an extension, are
+    /// skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut types: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for line in text.lines().filter(|line| !line.starts_with('#')) {
+            let mut fields = line.split_whitespace();
+            let Some(content_type) = fields.next() else {
+                continue;
+            };
+            let extensions = types.entry(content_type.to_ascii_lowercase()).or_default();
+            for extension in fields {
+                let extension = extension.to_ascii_lowercase();
+                if !extensions.contains(&extension) {
+                    extensions.push(extension);
+                }
+            }
+        }
+        types.retain(|_, extensions| !extensions.is_empty());
+        Apache { types }
+    }
+}
+
+/// Registered content types, from the IANA registry.
+#[derive(Default)]
+pub struct Iana {
+    /// Lowercase content types in use
+    pub registered: HashSet<String>,
+    /// Lowercase content types marked obsoleted or deprecated
+    pub obsolete: HashSet<String>,
+}
+
+impl Iana {
+    pub fn download() -> io::Result<Self> {
+        let mut iana = Iana::default();
+        for top_level in IANA_REGISTRIES {
+            let text = download(&format!("{IANA_URL}/{top_level}.csv"))?;
+            iana.extend(top_level, &text);
+        }
+        Ok(iana)
+    }
+
+    /// Reads `<top-level>.csv` files saved from the registry.
+    pub fn read_dir(dir: &Path) -> io::Result<Self> {
+        let mut iana = Iana::default();
+        for top_level in IANA_REGISTRIES {
+            let path = dir.join(format!("{top_level}.csv"));
+            let text = fs::read_to_string(&path)
+                .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?;
+            iana.extend(top_level, &text);
+        }
+        Ok(iana)
+    }
+
+    #[cfg(test)]
+    pub fn parse(top_level: &str, text: &str) -> Self {
+        let mut iana = Iana::default();
+        iana.extend(top_level, text);
+        iana
+    }
+
+    /// Adds the rows of one registry: `Name,Template,Reference`, with a
+    /// header row. Obsoleted entries have no template and a note after the
+    /// name (`vnd.foo - OBSOLETED in favor of vnd.bar`).
+    fn extend(&mut self, top_level: &str, text: &str) {
+        for record in csv_records(text).into_iter().skip(1) {
+            let (Some(name), Some(template)) = (record.first(), record.get(1)) else {
+                continue;
+            };
+            let content_type = if template.is_empty() {
+                let subtype = name.split_whitespace().next().unwrap_or_default();
+                format!("{top_level}/{subtype}")
+            } else {
+                template.clone()
+            }
+            .to_ascii_lowercase();
+            let upper = name.to_ascii_uppercase();
+            if upper.contains("OBSOLETE") || upper.contains("DEPRECATED") {
+                self.obsolete.insert(content_type);
+            } else {
+                self.registered.insert(content_type);
+            }
+        }
+    }
+}
+
+/// Splits CSV text into records of fields, unquoting `"..."` fields (with
+/// `""` for a quote), which may span lines.
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apache() {
+        let apache = Apache::parse(
+            "# comment\n# application/jose\nimage/JPEG\tjpeg JPG jpe jpg\napplication/x-empty\n",
+        );
+        assert_eq!(apache.types.len(), 1);
+        assert_eq!(apache.types["image/jpeg"], ["jpeg", "jpg", "jpe"]);
+    }
+
+    #[test]
+    fn test_csv_records() {
+        assert_eq!(
+            csv_records("a,b\r\n\"x, \"\"y\"\"\",\"multi\nline\"\nlast,"),
+            [
+                vec!["a", "b"],
+                vec!["x, \"y\"", "multi\nline"],
+                vec!["last", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iana() {
+        let iana = Iana::parse(
+            "text",
+            "Name,Template,Reference\n\
+             csv,text/csv,[RFC4180]\n\
+             ecmascript (OBSOLETED in favor of text/javascript),text/ecmascript,[RFC9239]\n\
+             vnd.old - DEPRECATED,,[Old]\n",
+        );
+        assert!(iana.registered.contains("text/csv"));
+        assert!(iana.obsolete.contains("text/ecmascript"));
+        assert!(iana.obsolete.contains("text/vnd.old"));
+        assert_eq!(iana.registered.len(), 1);
+    }
+}